@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) 2019 Zachary Nielsen
+ *
+ * Licensed under the Apache License, Version 2.0
+ * <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+ * license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. All files in the project carrying such notice may not be copied,
+ * modified, or distributed except according to those terms.
+ */
+
+/* C interface to the numconverter library, built with the `ffi` feature.
+ * Kept in step with src/ffi.rs, `cbindgen --config cbindgen.toml` regenerates it.
+ *
+ * The caller owns all memory: results are written to buffers the caller
+ * provides and nothing needs to be freed. */
+
+#ifndef NUMCONVERTER_H
+#define NUMCONVERTER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Write `num` in `base` (2 to 36) to `out` as a NUL terminated string.
+ * Returns the number of digits written, not counting the NUL, or -1 if the
+ * base is invalid, `out` is NULL or `out_len` has no room for the digits and
+ * the NUL. 65 bytes is enough for any base. */
+int32_t numconv_as_string_base(uint64_t num, uint32_t base, uint8_t *out, size_t out_len);
+
+/* Parse the NUL terminated `input` in `from_base` (2 to 36) into `result`,
+ * ignoring `_` separators. Returns 0 on success, or -1 if a pointer is NULL,
+ * `input` isn't a number in `from_base` or the value doesn't fit in 64 bits.
+ * `result` is only written on success. */
+int32_t numconv_convert_to_base_10(const char *input, uint32_t from_base, uint64_t *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NUMCONVERTER_H */
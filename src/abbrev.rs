@@ -0,0 +1,182 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Compact forms of large numbers for display, like `1_000_000` or `1M`.
+
+use std::str::FromStr;
+
+use crate::insert_separators;
+
+/// SI prefixes for each power of 1000, starting at 1000^1
+pub const SI_PREFIXES: [&str; 10] = ["k", "M", "G", "T", "P", "E", "Z", "Y", "R", "Q"];
+/// Binary prefixes for each power of 1024, starting at 1024^1
+pub const BINARY_PREFIXES: [&str; 10] = ["Ki", "Mi", "Gi", "Ti", "Pi", "Ei", "Zi", "Yi", "Ri", "Qi"];
+
+/// Most decimal places `abbreviate_to` will show
+pub const MAX_ABBREV_PRECISION: u32 = 6;
+
+/// How `abbreviate` shortens a number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbbrevStyle {
+    /// The base 10 digits in groups of three, `1_000_000`
+    Decimal,
+    /// A prefix for the largest power of 1000 (1024 in power of two bases), `1M`
+    Si,
+}
+
+impl FromStr for AbbrevStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(AbbrevStyle::Decimal),
+            "si"      => Ok(AbbrevStyle::Si),
+            _         => Err(format!("Invalid abbreviation style {}, expected decimal or si", s)),
+        }
+    }
+}
+
+/// `abbreviate_to` with one decimal place
+pub fn abbreviate(n: u128, style: AbbrevStyle, base: u32) -> String {
+    abbreviate_to(n, style, base, 1)
+}
+
+/// Shorten `n` for display in `style`. With `AbbrevStyle::Si`, values for a
+/// power of two `base` step by 1024 instead of 1000, values below the first
+/// step are left as they are, and the rest are rounded to `precision`
+/// decimal places (at most `MAX_ABBREV_PRECISION`) without trailing zeros.
+pub fn abbreviate_to(n: u128, style: AbbrevStyle, base: u32, precision: u32) -> String {
+    if style == AbbrevStyle::Decimal {
+        return insert_separators(&n.to_string(), '_', 3);
+    }
+    let (step, prefixes) = if base.is_power_of_two() { (1024, &BINARY_PREFIXES) } else { (1000, &SI_PREFIXES) };
+    let scale = 10u128.pow(precision.min(MAX_ABBREV_PRECISION));
+    let mut unit = 1u128;
+    let mut prefix = 0;
+    while prefix < prefixes.len() && n / unit >= step {
+        unit *= step;
+        prefix += 1;
+    }
+    if prefix == 0 {
+        return n.to_string();
+    }
+    loop {
+        let (mut whole, rem) = (n / unit, n % unit);
+        let mut frac = (rem * scale + unit / 2) / unit;
+        if frac == scale {
+            whole += 1;
+            frac = 0;
+        }
+        // 999_999 rounds up to 1000k, which is 1M
+        if whole >= step && prefix < prefixes.len() {
+            unit *= step;
+            prefix += 1;
+            continue;
+        }
+        let mut digits = whole.to_string();
+        if frac > 0 {
+            let frac = format!("{:0width$}", frac, width = scale.to_string().len() - 1);
+            digits = digits + "." + frac.trim_end_matches('0');
+        }
+        return digits + prefixes[prefix - 1];
+    }
+}
+
+/// `n` scaled down by the largest power of 1000 (or of 1024 without `si`)
+/// it reaches once rounded to three decimal places, and that power's SI or
+/// binary prefix. Values below the first step have an empty prefix.
+pub fn to_metric_prefix(n: u128, si: bool) -> (f64, &'static str) {
+    let (step, prefixes) = if si { (1000.0, &SI_PREFIXES) } else { (1024.0, &BINARY_PREFIXES) };
+    let mut value = n as f64;
+    let mut prefix = 0;
+    while prefix < prefixes.len() && (value * 1000.0).round() / 1000.0 >= step {
+        value /= step;
+        prefix += 1;
+    }
+    (value, if prefix == 0 { "" } else { prefixes[prefix - 1] })
+}
+
+/// `to_metric_prefix` for display, to three decimal places with a space
+/// before the prefix, like `1.074 G`
+pub fn format_metric_prefix(n: u128, si: bool) -> String {
+    match to_metric_prefix(n, si) {
+        (_, "")         => n.to_string(),
+        (value, prefix) => format!("{:.3} {}", value, prefix),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abbreviate_si() {
+        assert_eq!(abbreviate(255, AbbrevStyle::Si, 10), "255");
+        assert_eq!(abbreviate(999, AbbrevStyle::Si, 10), "999");
+        assert_eq!(abbreviate(1000, AbbrevStyle::Si, 10), "1k");
+        assert_eq!(abbreviate(1023, AbbrevStyle::Si, 10), "1k");
+        assert_eq!(abbreviate(1024, AbbrevStyle::Si, 10), "1k");
+        assert_eq!(abbreviate(1_000_000, AbbrevStyle::Si, 10), "1M");
+        assert_eq!(abbreviate(10u128.pow(12), AbbrevStyle::Si, 10), "1T");
+        assert_eq!(abbreviate(1 << 40, AbbrevStyle::Si, 10), "1.1T");
+        assert_eq!(abbreviate(999_999, AbbrevStyle::Si, 10), "1M");
+        assert_eq!(abbreviate_to(1 << 40, AbbrevStyle::Si, 10, 3), "1.1T");
+        assert_eq!(abbreviate_to(1 << 40, AbbrevStyle::Si, 10, 4), "1.0995T");
+        assert_eq!(abbreviate_to(1500, AbbrevStyle::Si, 10, 0), "2k");
+        assert_eq!(abbreviate(u128::MAX, AbbrevStyle::Si, 10), "340282366.9Q");
+    }
+
+    #[test]
+    fn test_abbreviate_binary() {
+        assert_eq!(abbreviate(999, AbbrevStyle::Si, 2), "999");
+        assert_eq!(abbreviate(1000, AbbrevStyle::Si, 2), "1000");
+        assert_eq!(abbreviate(1023, AbbrevStyle::Si, 2), "1023");
+        assert_eq!(abbreviate(1024, AbbrevStyle::Si, 2), "1Ki");
+        assert_eq!(abbreviate(1536, AbbrevStyle::Si, 16), "1.5Ki");
+        assert_eq!(abbreviate(10u128.pow(12), AbbrevStyle::Si, 2), "931.3Gi");
+        assert_eq!(abbreviate(1 << 40, AbbrevStyle::Si, 2), "1Ti");
+    }
+
+    #[test]
+    fn test_abbreviate_decimal() {
+        assert_eq!(abbreviate(255, AbbrevStyle::Decimal, 10), "255");
+        assert_eq!(abbreviate(1000, AbbrevStyle::Decimal, 2), "1_000");
+        assert_eq!(abbreviate(1_000_000, AbbrevStyle::Decimal, 10), "1_000_000");
+        assert_eq!(abbreviate(1 << 40, AbbrevStyle::Decimal, 2), "1_099_511_627_776");
+    }
+
+    #[test]
+    fn test_to_metric_prefix() {
+        assert_eq!(to_metric_prefix(999, true), (999.0, ""));
+        assert_eq!(to_metric_prefix(1000, true), (1.0, "k"));
+        assert_eq!(to_metric_prefix(10u128.pow(6), true), (1.0, "M"));
+        assert_eq!(to_metric_prefix(10u128.pow(9), true), (1.0, "G"));
+        assert_eq!(to_metric_prefix(10u128.pow(12), true), (1.0, "T"));
+        assert_eq!(to_metric_prefix(1023, false), (1023.0, ""));
+        assert_eq!(to_metric_prefix(1 << 10, false), (1.0, "Ki"));
+        assert_eq!(to_metric_prefix(1 << 20, false), (1.0, "Mi"));
+        assert_eq!(to_metric_prefix(1 << 30, false), (1.0, "Gi"));
+        assert_eq!(to_metric_prefix(1 << 30, true).1, "G");
+    }
+
+    #[test]
+    fn test_format_metric_prefix() {
+        assert_eq!(format_metric_prefix(1 << 30, true), "1.074 G");
+        assert_eq!(format_metric_prefix(1 << 30, false), "1.000 Gi");
+        assert_eq!(format_metric_prefix(1 << 10, true), "1.024 k");
+        assert_eq!(format_metric_prefix(1000, false), "1000");
+        assert_eq!(format_metric_prefix(10u128.pow(6) - 1, true), "999.999 k");
+        assert_eq!(format_metric_prefix(10u128.pow(9) - 1, true), "1.000 G");
+        assert_eq!(format_metric_prefix((1 << 20) - 1, false), "1023.999 Ki");
+        assert_eq!(format_metric_prefix((1 << 30) - 1, false), "1.000 Gi");
+        assert_eq!(format_metric_prefix(10u128.pow(12), false), "931.323 Gi");
+        assert_eq!(format_metric_prefix(0, true), "0");
+        assert!(format_metric_prefix(u128::MAX, true).ends_with(" Q"));
+    }
+}
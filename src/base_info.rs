@@ -0,0 +1,156 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Properties of a base itself, for `--base-info`.
+
+use std::fmt;
+use crate::{as_string_base, bits::{mask, COMMON_WIDTHS}, stats::prime_factors};
+
+/// Digit counts shown in the largest value table
+pub const DIGIT_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// What a base looks like and how it relates to binary data types
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaseInfo {
+    pub base: u32,
+    /// The digits as a range, e.g. `0-9A-F`
+    pub alphabet: String,
+    /// The largest value with each of `DIGIT_COUNTS` digits
+    pub max_values: Vec<(u32, u128)>,
+    /// Digits needed for the largest value of each of `COMMON_WIDTHS` bits
+    pub type_digits: Vec<(u32, usize)>,
+    pub is_prime: bool,
+    /// `(p, k)` when the base is `p^k` for a prime `p` and `k >= 2`
+    pub prime_power: Option<(u32, u32)>,
+    /// Whether the base has more divisors than every smaller number
+    pub is_highly_composite: bool,
+}
+
+/// The digits of `base` as ranges, e.g. `0-9A-F` for 16 or `0-7` for 8
+fn alphabet(base: u32) -> String {
+    let last = std::char::from_digit(base - 1, base).unwrap().to_ascii_uppercase();
+    if base <= 10 {
+        format!("0-{}", last)
+    } else if base == 11 {
+        "0-9A".to_string()
+    } else {
+        format!("0-9A-{}", last)
+    }
+}
+
+fn divisor_count(n: u32) -> u32 {
+    (1..=n).filter(|d| n.is_multiple_of(*d)).count() as u32
+}
+
+/// Describe `base`, which must be from 2 to 36
+pub fn base_info(base: u32) -> Result<BaseInfo, String> {
+    if !(2..=36).contains(&base) {
+        return Err(format!("Base must be between 2 and 36, not {}", base));
+    }
+    let factors = prime_factors(base as u128);
+    let divisors = divisor_count(base);
+    Ok(BaseInfo {
+        base,
+        alphabet: alphabet(base),
+        max_values: DIGIT_COUNTS.iter().map(|&n| (n, (base as u128).pow(n) - 1)).collect(),
+        type_digits: COMMON_WIDTHS.iter()
+            .map(|&width| (width, as_string_base(&mask(width), base).unwrap().len()))
+            .collect(),
+        is_prime: factors == [(base as u128, 1)],
+        prime_power: match factors.as_slice() {
+            &[(p, k)] if k >= 2 => Some((p as u32, k)),
+            _ => None,
+        },
+        is_highly_composite: (1..base).all(|n| divisor_count(n) < divisors),
+    })
+}
+
+impl fmt::Display for BaseInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        writeln!(f, "Base {}", self.base)?;
+        writeln!(f, "Digits: {}", self.alphabet)?;
+        writeln!(f, "Largest values:")?;
+        for &(digits, max) in &self.max_values {
+            writeln!(f, "  {:<9} {} ({})", format!("{} digit{}:", digits, if digits == 1 { "" } else { "s" }),
+                     as_string_base(&max, self.base).unwrap(), max)?;
+        }
+        writeln!(f, "Data types:")?;
+        for &(width, digits) in &self.type_digits {
+            let name = if width == 8 { "byte".to_string() } else { format!("u{}", width) };
+            writeln!(f, "  {:<5} = {} digits", name, digits)?;
+        }
+        writeln!(f, "Prime: {}", yes_no(self.is_prime))?;
+        match self.prime_power {
+            Some((p, k)) => writeln!(f, "Prime power: yes, {}^{}", p, k)?,
+            None         => writeln!(f, "Prime power: no")?,
+        }
+        write!(f, "Highly composite: {}", yes_no(self.is_highly_composite))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_info_2() {
+        let info = base_info(2).unwrap();
+        assert_eq!(info.alphabet, "0-1");
+        assert_eq!(info.max_values, vec![(1, 1), (2, 3), (4, 15), (8, 255)]);
+        assert_eq!(info.type_digits, vec![(8, 8), (16, 16), (32, 32), (64, 64), (128, 128)]);
+        assert!(info.is_prime);
+        assert_eq!(info.prime_power, None);
+        assert!(info.is_highly_composite);
+    }
+
+    #[test]
+    fn test_base_info_10() {
+        let info = base_info(10).unwrap();
+        assert_eq!(info.alphabet, "0-9");
+        assert_eq!(info.max_values[3], (8, 99_999_999));
+        assert_eq!(info.type_digits[0], (8, 3));
+        assert_eq!(info.type_digits[4], (128, 39));
+        assert!(!info.is_prime);
+        assert_eq!(info.prime_power, None);
+        assert!(!info.is_highly_composite);
+    }
+
+    #[test]
+    fn test_base_info_16() {
+        let info = base_info(16).unwrap();
+        assert_eq!(info.alphabet, "0-9A-F");
+        assert_eq!(info.max_values[1], (2, 255));
+        assert_eq!(info.type_digits, vec![(8, 2), (16, 4), (32, 8), (64, 16), (128, 32)]);
+        assert!(!info.is_prime);
+        assert_eq!(info.prime_power, Some((2, 4)));
+        assert!(!info.is_highly_composite);
+        assert!(info.to_string().contains("  byte  = 2 digits\n"));
+        assert!(info.to_string().contains("  2 digits: FF (255)\n"));
+    }
+
+    #[test]
+    fn test_base_info_36() {
+        let info = base_info(36).unwrap();
+        assert_eq!(info.alphabet, "0-9A-Z");
+        assert_eq!(info.max_values[3], (8, 36u128.pow(8) - 1));
+        assert_eq!(info.prime_power, None);
+        assert!(info.is_highly_composite);
+        assert_eq!(base_info(11).unwrap().alphabet, "0-9A");
+        assert_eq!(base_info(12).unwrap().alphabet, "0-9A-B");
+        assert_eq!(base_info(9).unwrap().prime_power, Some((3, 2)));
+    }
+
+    #[test]
+    fn test_base_info_invalid() {
+        assert!(base_info(1).is_err());
+        assert!(base_info(37).is_err());
+        assert!(base_info(0).is_err());
+    }
+}
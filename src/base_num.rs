@@ -0,0 +1,127 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `BaseNum`, a u128 that works with `format!` in any base.
+
+use std::fmt;
+use crate::as_string_base;
+
+/// A u128 for `format!` and friends. `{}`, `{:b}`, `{:o}`, `{:x}` and `{:X}`
+/// work as they do for u128, and `in_base` gives any base from 2 to 36.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BaseNum(pub u128);
+
+impl BaseNum {
+    /// Write the value in `base` to `f`, honouring its width, fill,
+    /// alignment, `+`, `0` and `#` flags. `#` adds `0b`, `0o` or `0x` for
+    /// bases 2, 8 and 16 and nothing for the others.
+    ///
+    /// A base outside 2 to 36 is a `fmt::Error`, which makes `format!` panic.
+    pub fn fmt_base(&self, f: &mut fmt::Formatter, base: u32) -> fmt::Result {
+        let digits = as_string_base(&self.0, base).map_err(|_| fmt::Error)?;
+        let prefix = match base {
+            2  => "0b",
+            8  => "0o",
+            16 => "0x",
+            _  => "",
+        };
+        f.pad_integral(true, prefix, &digits)
+    }
+
+    /// Display the value in `base`, e.g. `format!("{:>8}", BaseNum(35).in_base(36))`
+    pub fn in_base(self, base: u32) -> InBase {
+        InBase { num: self, base }
+    }
+}
+
+impl From<u128> for BaseNum {
+    fn from(num: u128) -> Self {
+        BaseNum(num)
+    }
+}
+
+impl fmt::Display for BaseNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Binary for BaseNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Octal for BaseNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Octal::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for BaseNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for BaseNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+/// A `BaseNum` that displays in a given base, from `BaseNum::in_base`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InBase {
+    num: BaseNum,
+    base: u32,
+}
+
+impl fmt::Display for InBase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.num.fmt_base(f, self.base)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_std_traits() {
+        for &num in &[0, 1, 187, 255, u64::MAX as u128, u128::MAX] {
+            assert_eq!(format!("{}", BaseNum(num)), as_string_base(&num, 10).unwrap());
+            assert_eq!(format!("{:b}", BaseNum(num)), as_string_base(&num, 2).unwrap());
+            assert_eq!(format!("{:o}", BaseNum(num)), as_string_base(&num, 8).unwrap());
+            assert_eq!(format!("{:X}", BaseNum(num)), as_string_base(&num, 16).unwrap());
+            assert_eq!(format!("{:x}", BaseNum(num)), as_string_base(&num, 16).unwrap().to_lowercase());
+        }
+        assert_eq!(format!("{:#06x}", BaseNum(255)), "0x00ff");
+        assert_eq!(format!("{:>5}", BaseNum::from(42)), "   42");
+    }
+
+    #[test]
+    fn test_in_base() {
+        assert_eq!(format!("{}", BaseNum(35).in_base(36)), "Z");
+        assert_eq!(format!("{}", BaseNum(u128::MAX).in_base(36)), as_string_base(&u128::MAX, 36).unwrap());
+        assert_eq!(format!("{}", BaseNum(255).in_base(16)), format!("{:X}", BaseNum(255)));
+        assert_eq!(format!("{:#}", BaseNum(5).in_base(2)), "0b101");
+        assert_eq!(format!("{:#}", BaseNum(5).in_base(3)), "12");
+        assert_eq!(format!("{:08}", BaseNum(5).in_base(3)), "00000012");
+        assert_eq!(format!("{:*<4}|", BaseNum(5).in_base(3)), "12**|");
+    }
+
+    #[test]
+    fn test_fmt_base_invalid() {
+        use std::fmt::Write;
+        let mut out = String::new();
+        assert!(write!(out, "{}", BaseNum(5).in_base(37)).is_err());
+        assert!(write!(out, "{}", BaseNum(5).in_base(1)).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Bit width helpers.
+
+/// The integer widths shown by `--interpret`
+pub const COMMON_WIDTHS: [u32; 5] = [8, 16, 32, 64, 128];
+
+/// The low `width` bits set
+pub fn mask(width: u32) -> u128 {
+    if width >= 128 { u128::MAX } else { (1 << width) - 1 }
+}
+
+/// Number of bits needed to hold `n`, at least 1
+pub fn min_width(n: u128) -> u32 {
+    (128 - n.leading_zeros()).max(1)
+}
+
+/// Whether `n` fits in `width` bits as an unsigned value
+pub fn fits_in_width(n: u128, width: u32) -> bool {
+    n & !mask(width) == 0
+}
+
+/// Read the low `width` bits of `raw` as a two's complement value
+pub fn as_signed(raw: u128, width: u32) -> i128 {
+    if width == 0 {
+        return 0;
+    }
+    let raw = raw & mask(width);
+    if width < 128 && (raw >> (width - 1)) & 1 == 1 {
+        (raw | !mask(width)) as i128
+    } else {
+        raw as i128
+    }
+}
+
+/// The unsigned and two's complement readings of `n` at `width`, or `None`
+/// if `n` doesn't fit in `width` bits.
+pub fn interpret(n: u128, width: u32) -> Option<(u128, i128)> {
+    if fits_in_width(n, width) {
+        Some((n, as_signed(n, width)))
+    } else {
+        None
+    }
+}
+
+
+/// Widen the low `from` bits of `raw` to `to` bits, copying the sign bit
+/// into the new high bits
+pub fn sign_extend(raw: u128, from: u32, to: u32) -> u128 {
+    let raw = raw & mask(from);
+    if from > 0 && from < to && (raw >> (from - 1)) & 1 == 1 {
+        raw | (mask(to) & !mask(from))
+    } else {
+        raw
+    }
+}
+
+/// Rotate the low `width` bits of `n` left by `shift`, for any width from 1
+/// to 128. Bits above `width` are dropped and `shift` is taken mod `width`.
+pub fn rotate_left(n: u128, shift: u32, width: u32) -> u128 {
+    assert!((1..=128).contains(&width), "rotation width must be 1 to 128 bits");
+    let n = n & mask(width);
+    let shift = shift % width;
+    if shift == 0 {
+        n
+    } else {
+        ((n << shift) | (n >> (width - shift))) & mask(width)
+    }
+}
+
+/// Rotate the low `width` bits of `n` right by `shift`, see `rotate_left`
+pub fn rotate_right(n: u128, shift: u32, width: u32) -> u128 {
+    assert!((1..=128).contains(&width), "rotation width must be 1 to 128 bits");
+    rotate_left(n, width - shift % width, width)
+}
+
+
+/// Reverse the order of the low `width` bits of `n`, so bit 0 swaps with bit
+/// `width - 1`. Bits above `width` are dropped.
+pub fn reverse_bits(n: u128, width: u32) -> u128 {
+    assert!((1..=128).contains(&width), "reversal width must be 1 to 128 bits");
+    (n & mask(width)).reverse_bits() >> (128 - width)
+}
+
+/// Reverse the order of the bytes of `n` that hold its low `width` bits,
+/// `width` rounded up to whole bytes
+pub fn reverse_bytes(n: u128, width: u32) -> u128 {
+    assert!((1..=128).contains(&width), "reversal width must be 1 to 128 bits");
+    let len = width.div_ceil(8) * 8;
+    (n & mask(len)).swap_bytes() >> (128 - len)
+}
+
+
+/// A bitwise operation of a value with a mask
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    /// Flip every bit, keeping only the bits set in the mask
+    Not,
+}
+
+pub fn apply_mask(n: u128, mask: u128, op: BitOp) -> u128 {
+    match op {
+        BitOp::And => n & mask,
+        BitOp::Or  => n | mask,
+        BitOp::Xor => n ^ mask,
+        BitOp::Not => !n & mask,
+    }
+}
+
+
+/// The bit to append to `n` so the count of set bits is even, or odd with `odd`
+pub fn parity_bit(n: u128, odd: bool) -> u32 {
+    (n.count_ones() + odd as u32) % 2
+}
+
+/// Whether the low `width` bits of `n`, the top one a parity bit, have an
+/// even count of set bits, or odd with `odd`
+pub fn check_parity(n: u128, width: u32, odd: bool) -> bool {
+    (n & mask(width)).count_ones() % 2 == odd as u32
+}
+
+
+/// The low `width` bits of `n` as bytes, rounding up to whole bytes, most
+/// significant first with `big_endian`
+pub fn to_bytes(n: u128, width: u32, big_endian: bool) -> Vec<u8> {
+    let len = width.min(128).div_ceil(8) as usize;
+    let mut bytes = n.to_le_bytes()[..len].to_vec();
+    if big_endian {
+        bytes.reverse();
+    }
+    bytes
+}
+
+
+/// The number of trailing zero bits of `n` and the largest power of two
+/// dividing it, `None` for 0, which every power of two divides
+pub fn alignment(n: u128) -> Option<(u32, u128)> {
+    if n == 0 {
+        None
+    } else {
+        Some((n.trailing_zeros(), 1 << n.trailing_zeros()))
+    }
+}
+
+
+/// The smallest power of two at or above `n` and how far `n` is below it,
+/// `(n, 0)` for a power of two. `None` when that power is 2^128.
+pub fn power2_complement(n: u128) -> Option<(u128, u128)> {
+    power_complement(n, 2)
+}
+
+/// `power2_complement` for powers of any `base` from 2 up
+pub fn power_complement(n: u128, base: u32) -> Option<(u128, u128)> {
+    assert!(base >= 2, "powers of base {} don't grow", base);
+    let mut power = 1u128;
+    while power < n {
+        power = power.checked_mul(base as u128)?;
+    }
+    Some((power, power - n))
+}
+
+
+/// Where a value sits between powers of two
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pow2Info {
+    pub is_power_of_two: bool,
+    /// Exponent of the largest power of two at or below the value, `None` for 0
+    pub below: Option<u32>,
+    /// Exponent of the smallest power of two at or above the value, `None`
+    /// when that is 2^128 and doesn't fit in a u128
+    pub above: Option<u32>,
+}
+
+pub fn pow2_info(n: u128) -> Pow2Info {
+    if n == 0 {
+        return Pow2Info { is_power_of_two: false, below: None, above: Some(0) };
+    }
+    let below = 127 - n.leading_zeros();
+    let is_power_of_two = n.is_power_of_two();
+    let above = if is_power_of_two {
+        Some(below)
+    } else if below < 127 {
+        Some(below + 1)
+    } else {
+        None
+    };
+    Pow2Info { is_power_of_two, below: Some(below), above }
+}
+
+
+/// Number of digits `2^width - 1` takes in `base`
+fn digits_for_width(width: u32, base: u32) -> usize {
+    let mut digits = 0;
+    let mut tmp = mask(width);
+    while tmp > 0 {
+        tmp /= base as u128;
+        digits += 1;
+    }
+    digits
+}
+
+/// Round a `len` digit value in `base` up to the digit count of the next
+/// standard integer width (8, 16, 32, 64 or 128 bits, plus a 4 bit nibble
+/// in binary). Lengths past 128 bits are returned unchanged.
+pub fn round_to_natural_width(len: usize, base: u32) -> usize {
+    if base < 2 {
+        return len;
+    }
+    let widths: &[u32] = if base == 2 { &[4, 8, 16, 32, 64, 128] } else { &[8, 16, 32, 64, 128] };
+    widths.iter()
+        .map(|&w| digits_for_width(w, base))
+        .find(|&digits| digits >= len)
+        .unwrap_or(len)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask(0), 0);
+        assert_eq!(mask(8), 0xFF);
+        assert_eq!(mask(128), u128::MAX);
+        assert_eq!(min_width(0), 1);
+        assert_eq!(min_width(0xFF), 8);
+        assert_eq!(min_width(0x100), 9);
+    }
+
+    #[test]
+    fn test_interpret() {
+        assert_eq!(interpret(0xFFFE, 8),  None);
+        assert_eq!(interpret(0xFFFE, 16), Some((65534, -2)));
+        assert_eq!(interpret(0xFFFE, 32), Some((65534, 65534)));
+        assert_eq!(interpret(0x80, 8),    Some((128, -128)));
+        assert_eq!(interpret(0x7F, 8),    Some((127, 127)));
+        assert_eq!(interpret(u128::MAX, 128), Some((u128::MAX, -1)));
+        assert_eq!(interpret(u64::MAX as u128, 64), Some((u64::MAX as u128, -1)));
+    }
+
+    #[test]
+    fn test_pow2_info() {
+        assert_eq!(pow2_info(0),    Pow2Info { is_power_of_two: false, below: None,     above: Some(0) });
+        assert_eq!(pow2_info(1),    Pow2Info { is_power_of_two: true,  below: Some(0),  above: Some(0) });
+        assert_eq!(pow2_info(187),  Pow2Info { is_power_of_two: false, below: Some(7),  above: Some(8) });
+        assert_eq!(pow2_info(4096), Pow2Info { is_power_of_two: true,  below: Some(12), above: Some(12) });
+        assert_eq!(pow2_info(1 << 127), Pow2Info { is_power_of_two: true, below: Some(127), above: Some(127) });
+        assert_eq!(pow2_info(u128::MAX), Pow2Info { is_power_of_two: false, below: Some(127), above: None });
+    }
+
+    #[test]
+    fn test_round_to_natural_width() {
+        assert_eq!(round_to_natural_width(1, 16), 2);
+        assert_eq!(round_to_natural_width(3, 16), 4);
+        assert_eq!(round_to_natural_width(9, 2), 16);
+        assert_eq!(round_to_natural_width(127, 2), 128);
+        assert_eq!(round_to_natural_width(1, 2), 4);
+        assert_eq!(round_to_natural_width(8, 2), 8);
+        assert_eq!(round_to_natural_width(4, 10), 5);
+        assert_eq!(round_to_natural_width(3, 8), 3);
+        assert_eq!(round_to_natural_width(200, 2), 200);
+    }
+
+    #[test]
+    fn test_sign_extend() {
+        assert_eq!(sign_extend(0xFB, 8, 32), 0xFFFF_FFFB);
+        assert_eq!(sign_extend(0x7B, 8, 32), 0x7B);
+        assert_eq!(sign_extend(0x8000, 16, 128), u128::MAX << 16 | 0x8000);
+        assert_eq!(sign_extend(0xFB, 8, 8), 0xFB);
+    }
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(rotate_left(0xAB, 4, 8), 0xBA);
+        assert_eq!(rotate_right(0xAB, 4, 8), 0xBA);
+        let left = [0xAB, 0x57, 0xAE, 0x5D, 0xBA, 0x75, 0xEA, 0xD5];
+        for (shift, &expected) in left.iter().enumerate() {
+            assert_eq!(rotate_left(0xAB, shift as u32, 8), expected, "left by {}", shift);
+            assert_eq!(rotate_right(expected, shift as u32, 8), 0xAB, "right by {}", shift);
+        }
+        assert_eq!(rotate_left(0b101, 1, 3), 0b011);
+        assert_eq!(rotate_right(0b101, 1, 3), 0b110);
+        assert_eq!(rotate_left(1, 1, 1), 1);
+        assert_eq!(rotate_left(1 << 127, 1, 128), 1);
+        assert_eq!(rotate_right(1, 1, 128), 1 << 127);
+        assert_eq!(rotate_left(0x1AB, 0, 8), 0xAB);
+    }
+
+    #[test]
+    fn test_apply_mask() {
+        assert_eq!(apply_mask(0x1234, 0xFF, BitOp::And), 0x34);
+        assert_eq!(apply_mask(0x1234, 0xFF, BitOp::Or), 0x12FF);
+        assert_eq!(apply_mask(0x1234, 0xFF, BitOp::Xor), 0x12CB);
+        assert_eq!(apply_mask(0x1234, 0xFFFF, BitOp::Not), 0xEDCB);
+        for &op in &[BitOp::And, BitOp::Not] {
+            assert_eq!(apply_mask(0x1234, 0, op), 0);
+        }
+        assert_eq!(apply_mask(0x1234, 0, BitOp::Or), 0x1234);
+        assert_eq!(apply_mask(0x1234, 0, BitOp::Xor), 0x1234);
+        assert_eq!(apply_mask(0x1234, u128::MAX, BitOp::And), 0x1234);
+        assert_eq!(apply_mask(0x1234, u128::MAX, BitOp::Or), u128::MAX);
+        assert_eq!(apply_mask(0x1234, u128::MAX, BitOp::Xor), !0x1234);
+        assert_eq!(apply_mask(0x1234, u128::MAX, BitOp::Not), !0x1234);
+        assert_eq!(apply_mask(u128::MAX, u128::MAX, BitOp::Not), 0);
+    }
+
+    #[test]
+    fn test_parity() {
+        assert_eq!(parity_bit(0, false), 0);
+        assert_eq!(parity_bit(0, true), 1);
+        assert_eq!(parity_bit(0b1011, false), 1);
+        assert_eq!(parity_bit(0b1011, true), 0);
+        assert_eq!(parity_bit(u128::MAX, false), 0);
+        assert!(check_parity(0b1000_1011, 8, false));
+        assert!(!check_parity(0b1000_1011, 8, true));
+        assert!(check_parity(0b0000_1011, 8, true));
+        // Only the low width bits count
+        assert!(check_parity(0x100, 8, false));
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        assert_eq!(to_bytes(0x1234, 16, false), vec![0x34, 0x12]);
+        assert_eq!(to_bytes(0x1234, 16, true), vec![0x12, 0x34]);
+        assert_eq!(to_bytes(0x1234, 13, true), vec![0x12, 0x34]);
+        assert_eq!(to_bytes(0x1234, 32, true), vec![0, 0, 0x12, 0x34]);
+        assert_eq!(to_bytes(1, 1, false), vec![1]);
+        assert_eq!(to_bytes(u128::MAX, 128, false).len(), 16);
+    }
+
+    #[test]
+    fn test_alignment() {
+        assert_eq!(alignment(0x1000), Some((12, 4096)));
+        assert_eq!(alignment(0x1800), Some((11, 2048)));
+        assert_eq!(alignment(1), Some((0, 1)));
+        assert_eq!(alignment(6), Some((1, 2)));
+        assert_eq!(alignment(1 << 127), Some((127, 1 << 127)));
+        assert_eq!(alignment(0), None);
+    }
+
+    #[test]
+    fn test_power2_complement() {
+        assert_eq!(power2_complement(0), Some((1, 1)));
+        assert_eq!(power2_complement(1), Some((1, 0)));
+        assert_eq!(power2_complement(240), Some((256, 16)));
+        assert_eq!(power2_complement(255), Some((256, 1)));
+        assert_eq!(power2_complement(256), Some((256, 0)));
+        assert_eq!(power2_complement(1000), Some((1024, 24)));
+        assert_eq!(power2_complement(1 << 127), Some((1 << 127, 0)));
+        assert_eq!(power2_complement((1 << 127) + 1), None);
+        assert_eq!(power2_complement(u128::MAX), None);
+    }
+
+    #[test]
+    fn test_power_complement() {
+        assert_eq!(power_complement(1000, 10), Some((1000, 0)));
+        assert_eq!(power_complement(1001, 10), Some((10_000, 8999)));
+        assert_eq!(power_complement(5, 3), Some((9, 4)));
+        assert_eq!(power_complement(0, 16), Some((1, 1)));
+        assert_eq!(power_complement(u128::MAX, 10), None);
+        assert_eq!(power_complement(10u128.pow(38), 10), Some((10u128.pow(38), 0)));
+    }
+
+    #[test]
+    fn test_reverse_bits() {
+        assert_eq!(reverse_bits(0b1101, 8), 0b1011_0000);
+        assert_eq!(reverse_bits(0b1101, 4), 0b1011);
+        assert_eq!(reverse_bits(1, 1), 1);
+        assert_eq!(reverse_bits(1, 128), 1 << 127);
+        assert_eq!(reverse_bits(0x1_0F, 8), 0xF0);
+        for n in 0..=0xFFF {
+            assert_eq!(reverse_bits(reverse_bits(n, 12), 12), n);
+        }
+    }
+
+    #[test]
+    fn test_reverse_bytes() {
+        assert_eq!(reverse_bytes(0x1234, 16), 0x3412);
+        assert_eq!(reverse_bytes(0x12_3456, min_width(0x12_3456)), 0x56_3412);
+        assert_eq!(reverse_bytes(0x1234, 32), 0x3412_0000);
+        assert_eq!(reverse_bytes(0x0ABC, 12), 0xBC0A);
+        assert_eq!(reverse_bytes(0xAB, 8), 0xAB);
+        assert_eq!(reverse_bytes(1, 128), 1 << 120);
+        assert_eq!(reverse_bytes(0xFF_1234, 16), 0x3412);
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Check digits and checksums.
+
+/// The reflected CRC-32 polynomial used by zlib, Ethernet and PNG
+pub const CRC32_IEEE: u32 = 0xEDB8_8320;
+/// The reflected CRC-32C (Castagnoli) polynomial used by iSCSI and ext4
+pub const CRC32_CASTAGNOLI: u32 = 0x82F6_3B78;
+
+static IEEE_TABLE: [u32; 256] = crc32_table(CRC32_IEEE);
+static CASTAGNOLI_TABLE: [u32; 256] = crc32_table(CRC32_CASTAGNOLI);
+
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ poly } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32_with(table: &[u32; 256], bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &b| (crc >> 8) ^ table[((crc ^ u32::from(b)) & 0xFF) as usize])
+}
+
+/// CRC-32 (IEEE) of `bytes`
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32_with(&IEEE_TABLE, bytes)
+}
+
+/// CRC-32C (Castagnoli) of `bytes`
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    crc32_with(&CASTAGNOLI_TABLE, bytes)
+}
+
+/// Luhn sum of the base 10 digits of `n`, doubling every second digit from
+/// the right starting with the last one when `double_last`
+fn luhn_sum(n: u128, double_last: bool) -> u32 {
+    let mut sum = 0;
+    let mut tmp = n;
+    let mut double = double_last;
+    while tmp > 0 {
+        let digit = (tmp % 10) as u32;
+        sum += if double && digit > 4 { digit * 2 - 9 } else if double { digit * 2 } else { digit };
+        double = !double;
+        tmp /= 10;
+    }
+    sum
+}
+
+/// Whether the last base 10 digit of `n` is its Luhn check digit
+pub fn luhn_valid(n: u128) -> bool {
+    luhn_sum(n, false).is_multiple_of(10)
+}
+
+/// The Luhn check digit to append to the base 10 digits of `n`
+pub fn luhn_check_digit(n: u128) -> u32 {
+    (10 - luhn_sum(n, true) % 10) % 10
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(&[0]), 0xD202_EF8D);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414F_A339);
+        assert_eq!(crc32c(&[0; 32]), 0x8A91_36AA);
+    }
+
+    #[test]
+    fn test_luhn() {
+        assert!(luhn_valid(79_927_398_713));
+        assert_eq!(luhn_check_digit(7_992_739_871), 3);
+        for &n in &[49_927_398_716, 1_234_567_812_345_670, 4_539_148_803_436_467, 0, 18, 59] {
+            assert!(luhn_valid(n), "{}", n);
+            assert_eq!(luhn_check_digit(n / 10), (n % 10) as u32, "{}", n);
+        }
+        for &n in &[79_927_398_710, 49_927_398_717, 1_234_567_812_345_678, 1] {
+            assert!(!luhn_valid(n), "{}", n);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The system clipboard, for `--watch-clipboard`.
+//!
+//! The clipboard is read with the platform's own tool (`pbpaste`,
+//! `Get-Clipboard`, `wl-paste`, `xclip` or `xsel`) rather than a library,
+//! so nothing extra needs to be linked.
+
+use std::{
+    io,
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+/// How often the clipboard is checked
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Commands that print the clipboard, tried in order
+#[cfg(target_os = "macos")]
+const PASTE_COMMANDS: &[&[&str]] = &[&["pbpaste"]];
+#[cfg(windows)]
+const PASTE_COMMANDS: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+#[cfg(not(any(target_os = "macos", windows)))]
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+/// The text on the clipboard, from the first paste command that works
+pub fn read_clipboard() -> io::Result<String> {
+    for command in PASTE_COMMANDS {
+        if let Ok(output) = Command::new(command[0]).args(&command[1..]).output() {
+            if output.status.success() {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound,
+                       format!("no clipboard tool worked, tried {}",
+                               PASTE_COMMANDS.iter().map(|c| c[0]).collect::<Vec<_>>().join(", "))))
+}
+
+/// Picks the new numbers out of successive clipboard contents
+#[derive(Debug, Default)]
+pub struct ClipboardWatcher {
+    last: Option<String>,
+}
+
+impl ClipboardWatcher {
+    /// The trimmed `contents` if it has changed since the last call and
+    /// `is_number` accepts it. Anything else is ignored without a message.
+    pub fn update(&mut self, contents: &str, is_number: impl Fn(&str) -> bool) -> Option<String> {
+        let contents = contents.trim();
+        if self.last.as_deref() == Some(contents) {
+            return None;
+        }
+        self.last = Some(contents.to_string());
+        Some(contents.to_string()).filter(|v| is_number(v))
+    }
+}
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Make Ctrl-C set the flag returned, so a watch loop can finish cleanly
+/// instead of being killed mid-line
+#[cfg(unix)]
+pub fn stop_on_ctrl_c() -> &'static AtomicBool {
+    extern "C" fn on_sigint(_: libc::c_int) {
+        STOP.store(true, Ordering::SeqCst);
+    }
+    unsafe {
+        libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t);
+    }
+    &STOP
+}
+
+/// Ctrl-C ends the process as usual where there are no signal handlers
+#[cfg(not(unix))]
+pub fn stop_on_ctrl_c() -> &'static AtomicBool {
+    &STOP
+}
+
+/// Poll the clipboard until `stop` is set, calling `on_value` with each new
+/// number `is_number` accepts
+pub fn watch<E>(stop: &AtomicBool, is_number: impl Fn(&str) -> bool,
+                mut on_value: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    let mut watcher = ClipboardWatcher::default();
+    while !stop.load(Ordering::SeqCst) {
+        // A clipboard that can't be read right now is the same as no number
+        if let Some(value) = read_clipboard().ok().and_then(|c| watcher.update(&c, &is_number)) {
+            on_value(&value)?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_hex(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    #[test]
+    fn test_watcher_stream() {
+        let mut watcher = ClipboardWatcher::default();
+        let stream = ["DEADBEEF", "DEADBEEF\n", "some text", "", "FF", "FF", "DEADBEEF"];
+        let values: Vec<String> = stream.iter().filter_map(|c| watcher.update(c, is_hex)).collect();
+        // Repeats are debounced, but copying a value again after something else prints it again
+        assert_eq!(values, ["DEADBEEF", "FF", "DEADBEEF"]);
+    }
+
+    #[test]
+    fn test_watch_stops() {
+        let stop = AtomicBool::new(true);
+        let mut calls = 0;
+        watch(&stop, is_hex, |_| -> Result<(), ()> {
+            calls += 1;
+            Ok(())
+        }).unwrap();
+        assert_eq!(calls, 0);
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Named constants for `--const`, as IEEE 754 double bit patterns.
+
+/// Each constant's name and the bits of its nearest f64
+pub static CONSTANTS: &[(&str, u128)] = &[
+    ("pi",    0x4009_21FB_5444_2D18),
+    ("e",     0x4005_BF0A_8B14_5769),
+    ("phi",   0x3FF9_E377_9B97_F4A8),
+    ("sqrt2", 0x3FF6_A09E_667F_3BCD),
+    ("ln2",   0x3FE6_2E42_FEFA_39EF),
+];
+
+/// The bits of the constant called `name`, ignoring case
+pub fn constant(name: &str) -> Option<u128> {
+    CONSTANTS.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, bits)| bits)
+}
+
+/// The names of all the constants, e.g. `pi, e, phi, sqrt2, ln2`
+pub fn constant_names() -> String {
+    CONSTANTS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts;
+
+    #[test]
+    fn test_constant_bits() {
+        assert_eq!(constant("pi"), Some(0x400921FB54442D18));
+        assert_eq!(constant("PI"), Some(0x400921FB54442D18));
+        assert_eq!(format!("{:X}", constant("e").unwrap()), "4005BF0A8B145769");
+        assert_eq!(constant("tau"), None);
+        let expected = [consts::PI, consts::E, (1.0 + 5f64.sqrt()) / 2.0, consts::SQRT_2, consts::LN_2];
+        for (&(name, bits), value) in CONSTANTS.iter().zip(&expected) {
+            assert_eq!(bits, value.to_bits() as u128, "{}", name);
+        }
+        assert_eq!(constant_names(), "pi, e, phi, sqrt2, ln2");
+    }
+}
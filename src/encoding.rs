@@ -0,0 +1,418 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Fitting signed and unsigned values into a fixed bit width.
+
+use std::{fmt, str::FromStr};
+use crate::{bits::{fits_in_width, mask, sign_extend}, ErrorCode};
+
+/// What to do with a value that doesn't fit its width
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverflowMode {
+    Error,
+    /// Keep the low bits, like hardware registers
+    Wrap,
+    /// Clamp to the largest or smallest representable value, like DSP code
+    Saturate,
+}
+
+impl FromStr for OverflowMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error"    => Ok(OverflowMode::Error),
+            "wrap"     => Ok(OverflowMode::Wrap),
+            "saturate" => Ok(OverflowMode::Saturate),
+            _ => Err(format!("Invalid overflow mode {}, expected error, wrap or saturate", s)),
+        }
+    }
+}
+
+impl fmt::Display for OverflowMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            OverflowMode::Error    => "error",
+            OverflowMode::Wrap     => "wrap",
+            OverflowMode::Saturate => "saturate",
+        })
+    }
+}
+
+/// Bits needed to write `n`, 0 for 0
+fn bit_len(n: u128) -> u32 {
+    128 - n.leading_zeros()
+}
+
+/// How negative values are laid out in a fixed width
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// Invert the magnitude and add one, the range is -2^(w-1) to 2^(w-1)-1
+    TwosComplement,
+    /// Invert the magnitude, the range is -(2^(w-1)-1) to 2^(w-1)-1 with
+    /// both +0 (all zeros) and -0 (all ones)
+    OnesComplement,
+    /// The top bit is the sign and the rest the magnitude, the range is
+    /// -(2^(w-1)-1) to 2^(w-1)-1 with both +0 and -0 (only the sign bit)
+    SignMagnitude,
+    /// Offset binary: store the value plus the bias K, the range is -K to
+    /// 2^w-1-K. IEEE 754 exponents are excess-127 in 8 bits
+    Excess(u128),
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "twos"           => Ok(Encoding::TwosComplement),
+            "ones"           => Ok(Encoding::OnesComplement),
+            "sign-magnitude" => Ok(Encoding::SignMagnitude),
+            _ => match s.strip_prefix("excess-").map(str::parse) {
+                Some(Ok(bias)) => Ok(Encoding::Excess(bias)),
+                _ => Err(format!("Invalid encoding {}, expected twos, ones, sign-magnitude or excess-K", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::TwosComplement => write!(f, "twos"),
+            Encoding::OnesComplement => write!(f, "ones"),
+            Encoding::SignMagnitude  => write!(f, "sign-magnitude"),
+            Encoding::Excess(bias)   => write!(f, "excess-{}", bias),
+        }
+    }
+}
+
+impl Encoding {
+    /// Human readable name, e.g. "Two's complement"
+    pub fn description(self) -> String {
+        match self {
+            Encoding::TwosComplement => "Two's complement".to_string(),
+            Encoding::OnesComplement => "Ones' complement".to_string(),
+            Encoding::SignMagnitude  => "Sign-magnitude".to_string(),
+            Encoding::Excess(bias)   => format!("Excess-{}", bias),
+        }
+    }
+
+    /// Whether the value is below the range of the encoding at any width,
+    /// which only happens for excess-K values below -K
+    pub fn below_range(self, negative: bool, magnitude: u128) -> bool {
+        match self {
+            Encoding::Excess(bias) => negative && magnitude > bias,
+            _ => false,
+        }
+    }
+
+    /// Smallest width that holds the value with the given sign and magnitude.
+    ///
+    /// Negative values and `signed` values need room for a sign bit, so 200
+    /// needs 8 bits unsigned but 9 signed, and -200 needs 9. Excess-K values
+    /// need room for the value plus the bias, whether or not `signed`.
+    pub fn required_width(self, negative: bool, magnitude: u128, signed: bool) -> u32 {
+        if let Encoding::Excess(bias) = self {
+            let biased = if negative { Some(bias.saturating_sub(magnitude)) } else { bias.checked_add(magnitude) };
+            return biased.map_or(129, |b| bit_len(b).max(1));
+        }
+        if magnitude == 0 {
+            1
+        } else if negative {
+            match self {
+                // Two's complement reaches -2^(w-1)
+                Encoding::TwosComplement => bit_len(magnitude - 1) + 1,
+                _ => bit_len(magnitude) + 1,
+            }
+        } else if signed {
+            bit_len(magnitude) + 1
+        } else {
+            bit_len(magnitude)
+        }
+    }
+
+    /// Whether the value fits in `width` bits
+    pub fn fits(self, negative: bool, magnitude: u128, signed: bool, width: u32) -> bool {
+        !self.below_range(negative, magnitude) && self.required_width(negative, magnitude, signed) <= width
+    }
+
+    /// The `width` bit pattern for the value, wrapping if it doesn't fit
+    pub fn encode(self, negative: bool, magnitude: u128, width: u32) -> u128 {
+        match self {
+            _ if negative          => self.encode_negative(magnitude, width),
+            Encoding::Excess(bias) => bias.wrapping_add(magnitude) & mask(width),
+            _                      => magnitude & mask(width),
+        }
+    }
+
+    /// The `width` bit pattern for `-magnitude`
+    pub fn encode_negative(self, magnitude: u128, width: u32) -> u128 {
+        match self {
+            Encoding::TwosComplement => twos_complement(magnitude, width),
+            Encoding::OnesComplement => ones_complement(magnitude, width),
+            Encoding::SignMagnitude  => 1 << (width - 1) | magnitude & mask(width - 1),
+            Encoding::Excess(bias)   => bias.wrapping_sub(magnitude) & mask(width),
+        }
+    }
+
+    /// The sign and magnitude a signed `width` bit pattern stands for
+    pub fn decode(self, raw: u128, width: u32) -> (bool, u128) {
+        let raw = raw & mask(width);
+        if let Encoding::Excess(bias) = self {
+            return if raw < bias { (true, bias - raw) } else { (false, raw - bias) };
+        }
+        if raw >> (width - 1) & 1 == 0 {
+            return (false, raw);
+        }
+        match self {
+            Encoding::TwosComplement => (true, twos_complement(raw, width)),
+            Encoding::OnesComplement => (true, ones_complement(raw, width)),
+            _                        => (true, raw & mask(width - 1)),
+        }
+    }
+
+    /// The `width` bit pattern for the negation of the value `raw` stands
+    /// for, wrapping the one value with no negation in two's complement
+    pub fn negate(self, raw: u128, width: u32) -> u128 {
+        let (negative, magnitude) = self.decode(raw, width);
+        self.encode(!negative, magnitude, width)
+    }
+
+    /// Widen a signed `from` bit pattern to `to` bits, keeping its value
+    pub fn extend(self, raw: u128, from: u32, to: u32) -> u128 {
+        match self {
+            Encoding::TwosComplement |
+            Encoding::OnesComplement => sign_extend(raw, from, to),
+            _ => {
+                let (negative, magnitude) = self.decode(raw, from);
+                self.encode(negative, magnitude, to)
+            },
+        }
+    }
+
+    /// The `width` bit pattern of the value clamped to the range of `width`
+    /// bits, signed if `signed` or `negative`.
+    pub fn saturate(self, negative: bool, magnitude: u128, signed: bool, width: u32) -> u128 {
+        if let Encoding::Excess(bias) = self {
+            return match negative {
+                true  => bias.saturating_sub(magnitude).min(mask(width)),
+                false => bias.saturating_add(magnitude).min(mask(width)),
+            };
+        }
+        if negative {
+            let min = match self {
+                Encoding::TwosComplement => 1u128 << (width - 1),
+                _ => mask(width - 1),
+            };
+            self.encode_negative(magnitude.min(min), width)
+        } else if signed {
+            magnitude.min(mask(width - 1))
+        } else {
+            magnitude.min(mask(width))
+        }
+    }
+}
+
+/// The `width` bit two's complement pattern for `-magnitude`
+pub fn twos_complement(magnitude: u128, width: u32) -> u128 {
+    (!magnitude).wrapping_add(1) & mask(width)
+}
+
+/// The `width` bit ones' complement pattern for `-magnitude`
+pub fn ones_complement(magnitude: u128, width: u32) -> u128 {
+    !magnitude & mask(width)
+}
+
+/// `-n` in `width` bit two's complement, `n` being a `width` bit pattern.
+/// -0 is 0 and the most negative value is its own negation.
+pub fn twos_complement_negate(n: u128, width: u32) -> Result<u128, ErrorCode> {
+    check_pattern(n, width)?;
+    Ok(twos_complement(n, width))
+}
+
+/// `-n` in `width` bit ones' complement, every bit of `n` flipped
+pub fn ones_complement_negate(n: u128, width: u32) -> Result<u128, ErrorCode> {
+    check_pattern(n, width)?;
+    Ok(ones_complement(n, width))
+}
+
+/// Whether `n` is a `width` bit pattern, with `width` from 1 to 128
+fn check_pattern(n: u128, width: u32) -> Result<(), ErrorCode> {
+    if (1..=128).contains(&width) && fits_in_width(n, width) {
+        Ok(())
+    } else {
+        Err(ErrorCode::WidthErr)
+    }
+}
+
+/// Write a decoded value, keeping the sign of -0
+pub fn format_signed(negative: bool, magnitude: u128) -> String {
+    format!("{}{}", if negative { "-" } else { "" }, magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWOS: Encoding = Encoding::TwosComplement;
+    const ONES: Encoding = Encoding::OnesComplement;
+
+    #[test]
+    fn test_required_width() {
+        assert_eq!(TWOS.required_width(false, 0, false), 1);
+        assert_eq!(TWOS.required_width(false, 0, true), 1);
+        assert_eq!(TWOS.required_width(false, 200, false), 8);
+        assert_eq!(TWOS.required_width(false, 200, true), 9);
+        assert_eq!(TWOS.required_width(true, 200, true), 9);
+        assert_eq!(TWOS.required_width(true, 128, true), 8);
+        assert_eq!(TWOS.required_width(false, 127, true), 8);
+        assert_eq!(TWOS.required_width(false, 128, true), 9);
+        assert_eq!(TWOS.required_width(true, 1, true), 1);
+        assert_eq!(TWOS.required_width(false, 0x12345, false), 17);
+        assert_eq!(TWOS.required_width(false, u128::MAX, true), 129);
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(TWOS.fits(false, 200, false, 8));
+        assert!(!TWOS.fits(true, 200, true, 8));
+        assert!(!TWOS.fits(false, 0x12345, false, 16));
+        assert!(TWOS.fits(true, 1 << 127, true, 128));
+    }
+
+    #[test]
+    fn test_twos_complement() {
+        assert_eq!(twos_complement(1, 8), 0xFF);
+        assert_eq!(twos_complement(128, 8), 0x80);
+        assert_eq!(twos_complement(2, 16), 0xFFFE);
+        assert_eq!(twos_complement(0, 8), 0);
+        assert_eq!(twos_complement(1, 128), u128::MAX);
+    }
+
+    #[test]
+    fn test_negate() {
+        for &width in &[4, 8, 16, 32, 64] {
+            let max = mask(width);
+            let half = (max >> 1) + 1;
+            assert_eq!(twos_complement_negate(0, width), Ok(0));
+            assert_eq!(twos_complement_negate(1, width), Ok(max));
+            assert_eq!(twos_complement_negate(max, width), Ok(1));
+            assert_eq!(twos_complement_negate(half, width), Ok(half));
+            assert_eq!(twos_complement_negate(max + 1, width), Err(ErrorCode::WidthErr));
+            assert_eq!(ones_complement_negate(0, width), Ok(max));
+            assert_eq!(ones_complement_negate(1, width), Ok(max - 1));
+            assert_eq!(ones_complement_negate(half, width), Ok(half - 1));
+            assert_eq!(ones_complement_negate(max + 1, width), Err(ErrorCode::WidthErr));
+        }
+        assert_eq!(twos_complement_negate(1, 128), Ok(u128::MAX));
+        assert_eq!(twos_complement_negate(1, 0), Err(ErrorCode::WidthErr));
+        assert_eq!(TWOS.negate(0xFB, 8), 5);
+        assert_eq!(TWOS.negate(0x80, 8), 0x80);
+        assert_eq!(ONES.negate(0xFA, 8), 5);
+        assert_eq!(ONES.negate(0, 8), 0xFF);
+        assert_eq!(Encoding::SignMagnitude.negate(0x85, 8), 5);
+        assert_eq!(Encoding::SignMagnitude.negate(5, 8), 0x85);
+        assert_eq!(Encoding::Excess(127).negate(0x7E, 8), 0x80);
+    }
+
+    #[test]
+    fn test_saturate() {
+        assert_eq!(TWOS.saturate(false, 300, false, 8), 0xFF);
+        assert_eq!(TWOS.saturate(false, 300, true,  8), 0x7F);
+        assert_eq!(TWOS.saturate(true,  300, true,  8), 0x80);
+        assert_eq!(TWOS.saturate(false, 100, true,  8), 100);
+        assert_eq!(TWOS.saturate(true,  5,   true,  8), 0xFB);
+        assert_eq!(TWOS.saturate(false, u128::MAX, true, 128), u128::MAX >> 1);
+    }
+
+    #[test]
+    fn test_ones_complement() {
+        // -0 is all ones, distinct from +0
+        assert_eq!(ONES.encode_negative(0, 8), 0xFF);
+        assert_eq!(ONES.decode(0xFF, 8), (true, 0));
+        assert_eq!(ONES.decode(0x00, 8), (false, 0));
+        assert_eq!(ONES.encode_negative(1, 8), 0xFE);
+        assert_eq!(ONES.decode(0xFE, 8), (true, 1));
+        // The most negative value is -(2^(w-1)-1)
+        assert_eq!(ONES.encode_negative(127, 8), 0x80);
+        assert_eq!(ONES.decode(0x80, 8), (true, 127));
+        assert!(ONES.fits(true, 127, true, 8));
+        assert!(!ONES.fits(true, 128, true, 8));
+        assert_eq!(ONES.required_width(true, 0, true), 1);
+        assert_eq!(ONES.saturate(true, 300, true, 8), 0x80);
+        assert_eq!(ONES.decode(1 << 127, 128), (true, u128::MAX >> 1));
+    }
+
+    #[test]
+    fn test_decode_twos_complement() {
+        assert_eq!(TWOS.decode(0xFF, 8), (true, 1));
+        assert_eq!(TWOS.decode(0x80, 8), (true, 128));
+        assert_eq!(TWOS.decode(0x7F, 8), (false, 127));
+        assert_eq!(TWOS.decode(1 << 127, 128), (true, 1 << 127));
+    }
+
+    #[test]
+    fn test_format_signed() {
+        assert_eq!(format_signed(true, 0), "-0");
+        assert_eq!(format_signed(false, 0), "0");
+        assert_eq!(format_signed(true, 127), "-127");
+    }
+
+    #[test]
+    fn test_sign_magnitude() {
+        const SM: Encoding = Encoding::SignMagnitude;
+        assert_eq!(SM.encode_negative(0, 8), 0x80);
+        assert_eq!(SM.decode(0x80, 8), (true, 0));
+        assert_eq!(SM.decode(0x00, 8), (false, 0));
+        assert_eq!(SM.encode_negative(127, 8), 0xFF);
+        assert_eq!(SM.decode(0xFF, 8), (true, 127));
+        assert!(!SM.fits(true, 128, true, 8));
+        assert_eq!(SM.saturate(true, 300, true, 8), 0xFF);
+        assert_eq!(SM.extend(0x85, 8, 16), 0x8005);
+        assert_eq!(SM.extend(0x05, 8, 16), 0x05);
+    }
+
+    #[test]
+    fn test_encodings_of_minus_five() {
+        let table = [
+            (TWOS,                    0xFB, 1 << 7),
+            (ONES,                    0xFA, 127),
+            (Encoding::SignMagnitude, 0x85, 127),
+        ];
+        for &(encoding, minus_five, most_negative) in table.iter() {
+            assert_eq!(encoding.encode_negative(5, 8), minus_five, "{}", encoding);
+            assert_eq!(encoding.decode(minus_five, 8), (true, 5), "{}", encoding);
+            assert!(encoding.fits(true, most_negative, true, 8), "{}", encoding);
+            assert!(!encoding.fits(true, most_negative + 1, true, 8), "{}", encoding);
+            assert_eq!(encoding.extend(minus_five, 8, 16) >> 15, 1, "{}", encoding);
+            assert_eq!(encoding.to_string().parse::<Encoding>(), Ok(encoding));
+        }
+    }
+
+    #[test]
+    fn test_excess() {
+        const EXCESS_127: Encoding = Encoding::Excess(127);
+        assert_eq!(EXCESS_127.encode(true, 1, 8), 0x7E);
+        assert_eq!(EXCESS_127.encode(false, 0, 8), 0x7F);
+        assert_eq!(EXCESS_127.encode(false, 128, 8), 0xFF);
+        assert_eq!(EXCESS_127.encode(true, 127, 8), 0);
+        assert_eq!(EXCESS_127.decode(0x7E, 8), (true, 1));
+        assert_eq!(EXCESS_127.decode(0x81, 8), (false, 2));
+        assert!(EXCESS_127.fits(false, 128, true, 8));
+        assert!(!EXCESS_127.fits(false, 129, false, 8));
+        assert!(!EXCESS_127.fits(true, 128, true, 128));
+        assert!(EXCESS_127.below_range(true, 128));
+        assert_eq!(EXCESS_127.saturate(true, 200, true, 8), 0);
+        assert_eq!(EXCESS_127.saturate(false, 200, true, 8), 0xFF);
+        assert_eq!(EXCESS_127.extend(0x7E, 8, 16), 0x7E);
+        assert_eq!(Encoding::Excess(u128::MAX).required_width(false, 1, false), 129);
+        assert_eq!("excess-127".parse::<Encoding>(), Ok(EXCESS_127));
+        assert_eq!(EXCESS_127.to_string(), "excess-127");
+    }
+}
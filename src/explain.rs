@@ -0,0 +1,180 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Step-by-step working for `--explain`.
+
+/// Maximum number of steps `--explain` prints before eliding the middle
+pub const EXPLAIN_MAX_STEPS: usize = 64;
+
+/// Working for converting `digits` in `base` to base 10: each digit times its
+/// place value, with the running sum.
+pub fn explain_to_base_10(digits: &str, base: u32) -> Vec<String> {
+    let len = digits.chars().count() as u32;
+    let mut sum: u128 = 0;
+    let mut steps = Vec::new();
+    for (i, ch) in digits.chars().enumerate() {
+        let place = len - 1 - i as u32;
+        let digit = match ch.to_digit(base) {
+            Some(v) => v,
+            None    => {
+                steps.push(format!("'{}' is not a digit in base {}", ch, base));
+                return steps;
+            },
+        };
+        let step = (base as u128)
+            .checked_pow(place)
+            .and_then(|p| p.checked_mul(digit as u128));
+        match step.and_then(|v| sum.checked_add(v)) {
+            Some(new_sum) => {
+                sum = new_sum;
+                steps.push(format!("{} = {} * {}^{} = {}, sum = {}",
+                                   ch, digit, base, place, step.unwrap_or(0), sum));
+            },
+            None => {
+                steps.push(format!("{} = {} * {}^{} overflows", ch, digit, base, place));
+                return steps;
+            },
+        }
+    }
+    steps
+}
+
+/// Working for converting `num` to `base` by repeated division, ending with
+/// the remainders read from last to first.
+pub fn explain_from_base_10(num: u128, base: u32) -> Vec<String> {
+    let mut steps = Vec::new();
+    if !(2..=36).contains(&base) {
+        return steps;
+    }
+    let base = base as u128;
+    let mut remainders = String::new();
+    let mut tmp = num;
+    loop {
+        let quotient  = tmp / base;
+        let remainder = (tmp % base) as u32;
+        let ch = std::char::from_digit(remainder, 36).unwrap_or('?').to_ascii_uppercase();
+        steps.push(format!("{} / {} = {} remainder {} ({})", tmp, base, quotient, remainder, ch));
+        remainders.insert(0, ch);
+        tmp = quotient;
+        if tmp == 0 {
+            break;
+        }
+    }
+    steps.push(format!("Reading the remainders from last to first gives {}", remainders));
+    steps
+}
+
+/// Keep the first and last `EXPLAIN_MAX_STEPS / 2` steps, replacing the
+/// rest with a single note.
+pub fn elide_steps(steps: Vec<String>) -> Vec<String> {
+    if steps.len() <= EXPLAIN_MAX_STEPS {
+        return steps;
+    }
+    let keep = EXPLAIN_MAX_STEPS / 2;
+    let omitted = steps.len() - 2 * keep;
+    let mut elided: Vec<String> = steps[..keep].to_vec();
+    elided.push(format!("... {} steps omitted ...", omitted));
+    elided.extend_from_slice(&steps[steps.len() - keep..]);
+    elided
+}
+
+
+/// Digits of `num` in `base` paired with their exponent, most significant
+/// first. Empty for bases below 2.
+pub fn decompose(num: u128, base: u32) -> Vec<(u32, u32)> {
+    let mut terms = Vec::new();
+    if base < 2 {
+        return terms;
+    }
+    let mut tmp = num;
+    let mut exp = 0;
+    loop {
+        terms.insert(0, ((tmp % base as u128) as u32, exp));
+        tmp /= base as u128;
+        exp += 1;
+        if tmp == 0 {
+            break;
+        }
+    }
+    terms
+}
+
+/// Write `terms` from `decompose` as a sum, either as `d·b^e` terms or
+/// as the value of each term.
+pub fn format_decomposition(terms: &[(u32, u32)], base: u32, values: bool, nonzero_only: bool) -> String {
+    let parts: Vec<String> = terms.iter()
+        .filter(|&&(digit, _)| !nonzero_only || digit != 0)
+        .map(|&(digit, exp)| if values {
+            (digit as u128 * (base as u128).pow(exp)).to_string()
+        } else {
+            format!("{}·{}^{}", digit, base, exp)
+        })
+        .collect();
+    if parts.is_empty() {
+        String::from("0")
+    } else {
+        parts.join(" + ")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_to_base_10() {
+        assert_eq!(explain_to_base_10("BB", 16), vec![
+            "B = 11 * 16^1 = 176, sum = 176",
+            "B = 11 * 16^0 = 11, sum = 187",
+        ]);
+        assert_eq!(explain_to_base_10("102", 2).last().unwrap(), "'2' is not a digit in base 2");
+    }
+
+    #[test]
+    fn test_explain_from_base_10() {
+        assert_eq!(explain_from_base_10(12, 2), vec![
+            "12 / 2 = 6 remainder 0 (0)",
+            "6 / 2 = 3 remainder 0 (0)",
+            "3 / 2 = 1 remainder 1 (1)",
+            "1 / 2 = 0 remainder 1 (1)",
+            "Reading the remainders from last to first gives 1100",
+        ]);
+        assert_eq!(explain_from_base_10(0, 16), vec![
+            "0 / 16 = 0 remainder 0 (0)",
+            "Reading the remainders from last to first gives 0",
+        ]);
+    }
+
+    #[test]
+    fn test_explain_elides_long_working() {
+        let steps = elide_steps(explain_from_base_10(u128::MAX, 2));
+        assert_eq!(steps.len(), EXPLAIN_MAX_STEPS + 1);
+        assert_eq!(steps[EXPLAIN_MAX_STEPS / 2], "... 65 steps omitted ...");
+        assert_eq!(steps.last().unwrap(), &format!("Reading the remainders from last to first gives {}", "1".repeat(128)));
+    }
+
+    #[test]
+    fn test_decompose() {
+        let terms = decompose(187, 2);
+        assert_eq!(format_decomposition(&terms, 2, true, true), "128 + 32 + 16 + 8 + 2 + 1");
+        assert_eq!(format_decomposition(&terms, 2, false, false),
+                   "1·2^7 + 0·2^6 + 1·2^5 + 1·2^4 + 1·2^3 + 0·2^2 + 1·2^1 + 1·2^0");
+        assert_eq!(format_decomposition(&terms, 2, false, true),
+                   "1·2^7 + 1·2^5 + 1·2^4 + 1·2^3 + 1·2^1 + 1·2^0");
+
+        let terms = decompose(187, 16);
+        assert_eq!(terms, vec![(11, 1), (11, 0)]);
+        assert_eq!(format_decomposition(&terms, 16, true, false), "176 + 11");
+
+        let terms = decompose(0, 10);
+        assert_eq!(format_decomposition(&terms, 10, false, false), "0·10^0");
+        assert_eq!(format_decomposition(&terms, 10, false, true), "0");
+        assert_eq!(decompose(u128::MAX, 2).len(), 128);
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Primality and prime factorization for `--factor`, fast enough for the
+//! whole u128 range, unlike the trial division `--stats` uses.
+
+use std::time::Instant;
+
+use crate::fraction::gcd;
+
+/// Miller-Rabin bases that make `is_prime` exact below 3.3 * 10^24
+const WITNESSES: [u128; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// Primes below this are found by trial division before Pollard's rho
+const TRIAL_LIMIT: u128 = 1000;
+
+/// `a * b % m` without overflowing
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a < 1 << 64 && b < 1 << 64 {
+        return a * b % m;
+    }
+    let (mut a, mut b, mut product) = (a % m, b % m, 0);
+    while b > 0 {
+        if b & 1 == 1 {
+            product = add_mod(product, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    product
+}
+
+/// `(a + b) % m` for `a` and `b` below `m`, without overflowing
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b { a - (m - b) } else { a + b }
+}
+
+fn pow_mod(base: u128, exp: u128, m: u128) -> u128 {
+    let (mut base, mut exp, mut result) = (base % m, exp, 1 % m);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Whether `n` is prime, by Miller-Rabin with the first 13 primes as
+/// witnesses. That is exact below 3.3 * 10^24, and above it no composite
+/// is known to pass.
+pub fn is_prime(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = WITNESSES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// A factor of the odd composite `n` other than 1 and `n`, by Pollard's
+/// rho, or `None` if `deadline` passes first
+fn pollard_rho(n: u128, deadline: Instant) -> Option<u128> {
+    for c in 1.. {
+        let f = |x: u128| add_mod(mul_mod(x, x, n), c, n);
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        let mut steps = 0u32;
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y), n);
+            steps = steps.wrapping_add(1);
+            if steps.is_multiple_of(256) && Instant::now() >= deadline {
+                return None;
+            }
+        }
+        if d != n {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Prime factors of `n` with their exponents, smallest first, or `None` if
+/// `deadline` passes before they are all found. 0 and 1 have none.
+pub fn factorize(n: u128, deadline: Instant) -> Option<Vec<(u128, u32)>> {
+    let mut primes = Vec::new();
+    let mut rest = n;
+    if n > 1 {
+        let mut p = 2;
+        while p < TRIAL_LIMIT && p * p <= rest {
+            while rest.is_multiple_of(p) {
+                primes.push(p);
+                rest /= p;
+            }
+            p += if p == 2 { 1 } else { 2 };
+        }
+    }
+    let mut composites = if rest > 1 { vec![rest] } else { Vec::new() };
+    while let Some(m) = composites.pop() {
+        if is_prime(m) {
+            primes.push(m);
+        } else {
+            let d = pollard_rho(m, deadline)?;
+            composites.push(d);
+            composites.push(m / d);
+        }
+    }
+    primes.sort_unstable();
+    let mut factors: Vec<(u128, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exp)) if *last == p => *exp += 1,
+            _                               => factors.push((p, 1)),
+        }
+    }
+    Some(factors)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn soon() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    #[test]
+    fn test_is_prime_small() {
+        let primes: Vec<u128> = (0..100).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97]);
+        assert!(is_prime(7919));
+        assert!(!is_prime(7917));
+    }
+
+    #[test]
+    fn test_is_prime_pseudoprimes() {
+        // Carmichael numbers fool Fermat's test, not Miller-Rabin
+        for &n in &[561, 1105, 1729, 2465, 2821, 6601, 8911] {
+            assert!(!is_prime(n), "{}", n);
+        }
+        // Strong pseudoprime to bases 2, 3, 5 and 7
+        assert!(!is_prime(3_215_031_751));
+    }
+
+    #[test]
+    fn test_is_prime_large() {
+        assert!(is_prime((1 << 61) - 1));
+        assert!(is_prime((1 << 89) - 1));
+        assert!(is_prime((1 << 127) - 1));
+        assert!(!is_prime(u128::MAX));
+        assert!(!is_prime(((1 << 61) - 1) * ((1 << 31) - 1)));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(0, soon()), Some(vec![]));
+        assert_eq!(factorize(1, soon()), Some(vec![]));
+        assert_eq!(factorize(2, soon()), Some(vec![(2, 1)]));
+        assert_eq!(factorize(360, soon()), Some(vec![(2, 3), (3, 2), (5, 1)]));
+        assert_eq!(factorize(561, soon()), Some(vec![(3, 1), (11, 1), (17, 1)]));
+        assert_eq!(factorize(1 << 100, soon()), Some(vec![(2, 100)]));
+        assert_eq!(factorize((1 << 127) - 1, soon()), Some(vec![((1 << 127) - 1, 1)]));
+    }
+
+    #[test]
+    fn test_factorize_semiprime() {
+        // Two primes just below 2^32, so about 2^64
+        let (p, q) = (4_294_967_279, 4_294_967_291);
+        assert_eq!(factorize(p * q, soon()), Some(vec![(p, 1), (q, 1)]));
+        assert_eq!(factorize(p * q * 1009 * 1009, soon()), Some(vec![(1009, 2), (p, 1), (q, 1)]));
+        // Out of time before rho finds a factor
+        assert_eq!(factorize(p * q, Instant::now()), None);
+        // Primes and small factors don't need the time
+        assert_eq!(factorize(p, Instant::now()), Some(vec![(p, 1)]));
+        assert_eq!(factorize(720, Instant::now()), Some(vec![(2, 4), (3, 2), (5, 1)]));
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A C interface for embedding the conversions, with the `ffi` feature.
+//! `include/numconverter.h` declares these functions for C and C++.
+//!
+//! The caller owns all memory: output goes into buffers the caller passes
+//! in, and nothing is allocated that the caller would have to free.
+
+use std::{ffi::CStr, os::raw::c_char, ptr, slice};
+use crate::{as_string_base, convert_to_base_10};
+
+/// Write `num` in `base` to `out` as a NUL terminated string.
+///
+/// Returns the number of digits written, not counting the NUL, or -1 if
+/// `base` isn't from 2 to 36, `out` is null or `out_len` doesn't leave room
+/// for the digits and the NUL.
+///
+/// # Safety
+///
+/// `out` must be null or point to at least `out_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn numconv_as_string_base(num: u64, base: u32, out: *mut u8, out_len: usize) -> i32 {
+    if out.is_null() {
+        return -1;
+    }
+    let digits = match as_string_base(&(num as u128), base) {
+        Ok(v)  => v,
+        Err(_) => return -1,
+    };
+    if digits.len() >= out_len {
+        return -1;
+    }
+    let out = slice::from_raw_parts_mut(out, out_len);
+    out[..digits.len()].copy_from_slice(digits.as_bytes());
+    out[digits.len()] = 0;
+    digits.len() as i32
+}
+
+/// Parse the NUL terminated `input` in `from_base` into `result`. `_`
+/// separators are ignored.
+///
+/// Returns 0 on success, or -1 if a pointer is null, the input isn't a
+/// number in `from_base` or the value doesn't fit in 64 bits. `result` is
+/// only written on success.
+///
+/// # Safety
+///
+/// `input` must be null or a NUL terminated string, and `result` null or
+/// valid to write a `u64` to.
+#[no_mangle]
+pub unsafe extern "C" fn numconv_convert_to_base_10(input: *const c_char, from_base: u32, result: *mut u64) -> i32 {
+    if input.is_null() || result.is_null() || !(2..=36).contains(&from_base) {
+        return -1;
+    }
+    let input = match CStr::from_ptr(input).to_str() {
+        Ok(v)  => v,
+        Err(_) => return -1,
+    };
+    match convert_to_base_10(Some(input.to_string()), from_base, '_') {
+        Ok(v) if v <= u64::MAX as u128 => {
+            ptr::write(result, v as u64);
+            0
+        },
+        _ => -1,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numconv_as_string_base() {
+        let mut buf = [0xAAu8; 8];
+        unsafe {
+            assert_eq!(numconv_as_string_base(255, 16, buf.as_mut_ptr(), buf.len()), 2);
+            assert_eq!(&buf[..3], b"FF\0");
+            assert_eq!(numconv_as_string_base(255, 2, buf.as_mut_ptr(), buf.len()), -1);
+            assert_eq!(numconv_as_string_base(127, 2, buf.as_mut_ptr(), buf.len()), 7);
+            assert_eq!(&buf, b"1111111\0");
+            assert_eq!(numconv_as_string_base(1, 37, buf.as_mut_ptr(), buf.len()), -1);
+            assert_eq!(numconv_as_string_base(1, 10, ptr::null_mut(), 8), -1);
+        }
+    }
+
+    #[test]
+    fn test_numconv_convert_to_base_10() {
+        let mut result = 0u64;
+        unsafe {
+            assert_eq!(numconv_convert_to_base_10(b"DEAD_BEEF\0".as_ptr() as *const c_char, 16, &mut result), 0);
+            assert_eq!(result, 0xDEAD_BEEF);
+            assert_eq!(numconv_convert_to_base_10(b"18446744073709551616\0".as_ptr() as *const c_char, 10,
+                                                  &mut result), -1);
+            assert_eq!(numconv_convert_to_base_10(b"XYZ\0".as_ptr() as *const c_char, 10, &mut result), -1);
+            assert_eq!(result, 0xDEAD_BEEF);
+            assert_eq!(numconv_convert_to_base_10(ptr::null(), 10, &mut result), -1);
+            assert_eq!(numconv_convert_to_base_10(b"1\0".as_ptr() as *const c_char, 10, ptr::null_mut()), -1);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversions that don't allocate, for `no_std` builds.
+//!
+//! These only need `core`, so they are there with or without the `std`
+//! feature. Parsing needs nothing extra: `u128::from_str_radix` is in `core`.
+
+use core::{fmt, ops::Deref, str};
+use crate::ErrorCode;
+
+/// A string of at most `N` bytes, kept inline
+#[derive(Clone, Copy)]
+pub struct ArrayString<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayString<N> {
+    pub fn new() -> Self {
+        ArrayString { buf: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits are ever pushed
+        str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+
+    /// Add `byte` to the end. Fails when the string is full.
+    fn push(&mut self, byte: u8) -> Result<(), ErrorCode> {
+        match self.buf.get_mut(self.len) {
+            Some(slot) => *slot = byte,
+            None       => return Err(ErrorCode::OverflowErr),
+        }
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for ArrayString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for ArrayString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for ArrayString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> fmt::Debug for ArrayString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Display for ArrayString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Format `num` in `base` without allocating, like `as_string_base`.
+///
+/// A u128 needs up to 128 digits in base 2 but only 39 in base 10, so `N`
+/// can be picked for the bases in use. Digits that don't fit in `N` are an
+/// `OverflowErr`, and bases outside 2 to 36 a `TargetBaseErr`.
+pub fn as_string_base_fixed<const N: usize>(num: u128, base: u32) -> Result<ArrayString<N>, ErrorCode> {
+    if !(2..=36).contains(&base) {
+        return Err(ErrorCode::TargetBaseErr);
+    }
+    // Digits come out least significant first
+    let mut reversed = [0u8; 128];
+    let mut count = 0;
+    let mut tmp = num;
+    loop {
+        let digit = (tmp % base as u128) as u8;
+        reversed[count] = if digit >= 10 { b'A' + digit - 10 } else { b'0' + digit };
+        count += 1;
+        tmp /= base as u128;
+        if tmp == 0 {
+            break;
+        }
+    }
+    let mut digits = ArrayString::new();
+    for &digit in reversed[..count].iter().rev() {
+        digits.push(digit)?;
+    }
+    Ok(digits)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_string_base_fixed() {
+        assert_eq!(as_string_base_fixed::<8>(255, 16).unwrap(), "FF");
+        assert_eq!(as_string_base_fixed::<8>(0, 2).unwrap(), "0");
+        assert_eq!(as_string_base_fixed::<8>(255, 2).unwrap(), "11111111");
+        assert_eq!(as_string_base_fixed::<128>(u128::MAX, 2).unwrap().len(), 128);
+        assert_eq!(as_string_base_fixed::<39>(u128::MAX, 10).unwrap(), "340282366920938463463374607431768211455");
+        assert_eq!(as_string_base_fixed::<4>(35, 36).unwrap(), "Z");
+    }
+
+    #[test]
+    fn test_as_string_base_fixed_errors() {
+        assert_eq!(as_string_base_fixed::<7>(255, 2).map(|s| s.len()), Err(ErrorCode::OverflowErr));
+        assert_eq!(as_string_base_fixed::<0>(0, 10).map(|s| s.len()), Err(ErrorCode::OverflowErr));
+        assert_eq!(as_string_base_fixed::<8>(1, 1).map(|s| s.len()), Err(ErrorCode::TargetBaseErr));
+        assert_eq!(as_string_base_fixed::<8>(1, 37).map(|s| s.len()), Err(ErrorCode::TargetBaseErr));
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Q-format fixed-point numbers.
+//!
+//! A format has `integer_bits` integer bits and `frac_bits` fractional bits.
+//! For the signed (Q) formats the sign bit is one of the integer bits, so
+//! Q8.8 and UQ8.8 are both 16 bits wide.
+
+use std::{fmt, str::FromStr};
+use crate::bits::mask;
+
+/// Largest supported number of fractional bits
+pub const MAX_FRAC_BITS: u32 = 124;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedPointFormat {
+    pub signed: bool,
+    pub integer_bits: u32,
+    pub frac_bits: u32,
+}
+
+impl FixedPointFormat {
+    pub fn width(&self) -> u32 {
+        self.integer_bits + self.frac_bits
+    }
+
+    /// Display the raw bit pattern `raw` as a decimal fraction
+    pub fn format(&self, raw: u128) -> String {
+        if self.signed {
+            to_signed_fixed_point_str(raw, self.integer_bits, self.frac_bits)
+        } else {
+            to_fixed_point_str(raw, self.integer_bits, self.frac_bits)
+        }
+    }
+
+    /// Parse a decimal fraction into the raw bit pattern for this format
+    pub fn parse(&self, s: &str) -> Result<u128, String> {
+        from_fixed_point_str(s, self.integer_bits, self.frac_bits, self.signed)
+    }
+}
+
+impl FromStr for FixedPointFormat {
+    type Err = String;
+
+    /// Parse `Q8.8` (signed), `UQ8.8` or `8.8` (unsigned)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_ascii_uppercase();
+        let (signed, bits) = if let Some(rest) = upper.strip_prefix("UQ") {
+            (false, rest)
+        } else if let Some(rest) = upper.strip_prefix('Q') {
+            (true, rest)
+        } else {
+            (false, upper.as_str())
+        };
+        let invalid = || format!("Invalid fixed-point format {}, expected e.g. Q8.8 or UQ4.12", s);
+        let mut parts = bits.splitn(2, '.');
+        let integer_bits: u32 = parts.next().and_then(|v| v.parse().ok()).ok_or_else(invalid)?;
+        let frac_bits:    u32 = parts.next().and_then(|v| v.parse().ok()).ok_or_else(invalid)?;
+
+        let format = FixedPointFormat { signed, integer_bits, frac_bits };
+        if format.width() == 0 || format.width() > 128 || frac_bits > MAX_FRAC_BITS
+            || (signed && integer_bits == 0) {
+            return Err(format!("Unsupported fixed-point format {}: width must be 1 to 128 bits, \
+                                with at most {} fractional bits", s, MAX_FRAC_BITS));
+        }
+        Ok(format)
+    }
+}
+
+impl fmt::Display for FixedPointFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}Q{}.{}", if self.signed { "" } else { "U" }, self.integer_bits, self.frac_bits)
+    }
+}
+
+/// Exact decimal expansion of `frac / 2^frac_bits`, without the leading "0."
+fn frac_digits(frac: u128, frac_bits: u32) -> String {
+    let mut digits = String::new();
+    let mut rem = frac;
+    while rem != 0 {
+        rem *= 10;
+        digits.push((b'0' + (rem >> frac_bits) as u8) as char);
+        rem &= mask(frac_bits);
+    }
+    digits
+}
+
+fn unsigned_str(raw: u128, frac_bits: u32) -> String {
+    let int_part  = if frac_bits >= 128 { 0 } else { raw >> frac_bits };
+    let frac_part = raw & mask(frac_bits);
+    let digits = frac_digits(frac_part, frac_bits);
+    if digits.is_empty() {
+        int_part.to_string()
+    } else {
+        format!("{}.{}", int_part, digits)
+    }
+}
+
+/// Interpret `raw` as an unsigned (UQ) fixed-point number and display it as
+/// a decimal fraction. Bits above the format width are ignored.
+pub fn to_fixed_point_str(raw: u128, integer_bits: u32, frac_bits: u32) -> String {
+    unsigned_str(raw & mask(integer_bits + frac_bits), frac_bits)
+}
+
+/// Interpret `raw` as a two's complement (Q) fixed-point number and display
+/// it as a decimal fraction. Bits above the format width are ignored.
+pub fn to_signed_fixed_point_str(raw: u128, integer_bits: u32, frac_bits: u32) -> String {
+    let width = integer_bits + frac_bits;
+    let raw = raw & mask(width);
+    if width > 0 && (raw >> (width - 1)) & 1 == 1 {
+        let magnitude = (!raw).wrapping_add(1) & mask(width);
+        format!("-{}", unsigned_str(magnitude, frac_bits))
+    } else {
+        unsigned_str(raw, frac_bits)
+    }
+}
+
+/// Parse a decimal fraction such as `1.5` or `-0.25` into the raw bit pattern
+/// of the given fixed-point format, rounding to the nearest representable
+/// value.
+pub fn from_fixed_point_str(s: &str, integer_bits: u32, frac_bits: u32, signed: bool) -> Result<u128, String> {
+    let width = integer_bits + frac_bits;
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None       => (false, s),
+    };
+    if negative && !signed {
+        return Err(format!("{} is negative, which needs a signed (Q) format", s));
+    }
+    let mut parts = digits.splitn(2, '.');
+    let int_str  = parts.next().unwrap_or("");
+    let frac_str = parts.next().unwrap_or("");
+    let invalid = || format!("Could not read {} as a decimal fraction", s);
+    if (int_str.is_empty() && frac_str.is_empty())
+        || !int_str.chars().chain(frac_str.chars()).all(|c| c.is_ascii_digit())
+        || frac_str.len() > 38 {
+        return Err(invalid());
+    }
+
+    let int_part: u128 = if int_str.is_empty() { 0 } else { int_str.parse().map_err(|_| invalid())? };
+
+    // Binary long division of the decimal fraction, one bit at a time
+    let denom = 10u128.pow(frac_str.len() as u32);
+    let mut num: u128 = if frac_str.is_empty() { 0 } else { frac_str.parse().map_err(|_| invalid())? };
+    let mut frac: u128 = 0;
+    for _ in 0..frac_bits {
+        num *= 2;
+        frac <<= 1;
+        if num >= denom {
+            frac |= 1;
+            num -= denom;
+        }
+    }
+    let mut magnitude = int_part.checked_mul(1 << frac_bits)
+        .and_then(|v| v.checked_add(frac))
+        .ok_or_else(|| format!("{} is out of range for {}-bit fixed point", s, width))?;
+    if num * 2 >= denom {
+        magnitude = magnitude.checked_add(1)
+            .ok_or_else(|| format!("{} is out of range for {}-bit fixed point", s, width))?;
+    }
+
+    let limit = if signed { 1u128 << (width - 1) } else { mask(width) };
+    // Two's complement reaches one further below zero than above it
+    let fits = if signed && !negative { magnitude < limit } else { magnitude <= limit };
+    if !fits {
+        return Err(format!("{} is out of range for {}", s,
+                           FixedPointFormat { signed, integer_bits, frac_bits }));
+    }
+    if negative {
+        Ok((!magnitude).wrapping_add(1) & mask(width))
+    } else {
+        Ok(magnitude)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_point_format_parse() {
+        assert_eq!("Q8.8".parse(),    Ok(FixedPointFormat { signed: true,  integer_bits: 8, frac_bits: 8 }));
+        assert_eq!("uq4.12".parse(),  Ok(FixedPointFormat { signed: false, integer_bits: 4, frac_bits: 12 }));
+        assert_eq!("16.16".parse(),   Ok(FixedPointFormat { signed: false, integer_bits: 16, frac_bits: 16 }));
+        assert!("Q8".parse::<FixedPointFormat>().is_err());
+        assert!("Q100.100".parse::<FixedPointFormat>().is_err());
+        assert!("Q0.8".parse::<FixedPointFormat>().is_err());
+    }
+
+    #[test]
+    fn test_q8_8() {
+        assert_eq!(to_fixed_point_str(0x0180, 8, 8), "1.5");
+        assert_eq!(to_signed_fixed_point_str(0x0180, 8, 8), "1.5");
+        assert_eq!(to_signed_fixed_point_str(0xFF80, 8, 8), "-0.5");
+        assert_eq!(to_signed_fixed_point_str(0x8000, 8, 8), "-128");
+        assert_eq!(to_fixed_point_str(0xFF80, 8, 8), "255.5");
+        assert_eq!(from_fixed_point_str("1.5",  8, 8, true), Ok(0x0180));
+        assert_eq!(from_fixed_point_str("-0.5", 8, 8, true), Ok(0xFF80));
+        assert_eq!(from_fixed_point_str("-128", 8, 8, true), Ok(0x8000));
+        assert!(from_fixed_point_str("128", 8, 8, true).is_err());
+    }
+
+    #[test]
+    fn test_q16_16() {
+        assert_eq!(to_signed_fixed_point_str(0x0003_243F, 16, 16), "3.1415863037109375");
+        assert_eq!(to_signed_fixed_point_str(0xFFFF_0000, 16, 16), "-1");
+        assert_eq!(from_fixed_point_str("3.14159", 16, 16, true), Ok(0x0003_243F));
+        assert_eq!(from_fixed_point_str("-1", 16, 16, true), Ok(0xFFFF_0000));
+    }
+
+    #[test]
+    fn test_uq4_12() {
+        assert_eq!(to_fixed_point_str(0x1000, 4, 12), "1");
+        assert_eq!(to_fixed_point_str(0xF001, 4, 12), "15.000244140625");
+        assert_eq!(to_fixed_point_str(0x0800, 4, 12), "0.5");
+        assert_eq!(from_fixed_point_str(".5", 4, 12, false), Ok(0x0800));
+        assert!(from_fixed_point_str("-0.5", 4, 12, false).is_err());
+        assert!(from_fixed_point_str("16", 4, 12, false).is_err());
+    }
+}
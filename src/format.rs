@@ -0,0 +1,82 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The output formats picked with `--format`.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// The labelled lines
+    Text,
+    /// One JSON object, or an array of them for a batch
+    Json,
+    /// One compact JSON object per line, written as each number converts
+    Jsonl,
+    Markdown,
+    /// `Markdown` with the base 16 row in bold
+    MarkdownBold,
+    /// An HTML table for each number
+    Html,
+    /// A LaTeX `tabular` for each number
+    Latex,
+    /// An XML document for each number
+    Xml,
+}
+
+impl OutputFormat {
+    /// The names `FromStr` accepts, for `possible_values`
+    pub const NAMES: &'static [&'static str] = &["text", "json", "jsonl", "markdown", "markdown-bold", "html", "latex", "xml"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text"          => Ok(OutputFormat::Text),
+            "json"          => Ok(OutputFormat::Json),
+            "jsonl"         => Ok(OutputFormat::Jsonl),
+            "markdown"      => Ok(OutputFormat::Markdown),
+            "markdown-bold" => Ok(OutputFormat::MarkdownBold),
+            "html"          => Ok(OutputFormat::Html),
+            "latex"         => Ok(OutputFormat::Latex),
+            "xml"           => Ok(OutputFormat::Xml),
+            _               => Err(format!("Invalid format {}, expected one of {}", s, OutputFormat::NAMES.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Text         => "text",
+            OutputFormat::Json         => "json",
+            OutputFormat::Jsonl        => "jsonl",
+            OutputFormat::Markdown     => "markdown",
+            OutputFormat::MarkdownBold => "markdown-bold",
+            OutputFormat::Html         => "html",
+            OutputFormat::Latex        => "latex",
+            OutputFormat::Xml          => "xml",
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_names() {
+        for name in OutputFormat::NAMES {
+            assert_eq!(name.parse::<OutputFormat>().map(|format| format.to_string()).as_deref(), Ok(*name));
+        }
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
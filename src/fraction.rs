@@ -0,0 +1,496 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Fractional parts of numbers, the digits after the radix point.
+//!
+//! A fractional part is kept as an exact fraction `numerator / denominator`
+//! with the numerator less than the denominator.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+use crate::{convert_to_base_10, ErrorCode};
+
+/// How the last fractional digit is rounded when the expansion is longer
+/// than the precision. Values are never negative, so `Down` is the same as
+/// `Trunc` and `Nearest` breaking ties away from zero means rounding up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rounding {
+    /// Drop the rest of the digits
+    Trunc,
+    /// Round to the closer of the two, ties away from zero
+    Nearest,
+    /// Toward positive infinity
+    Up,
+    /// Toward negative infinity
+    Down,
+}
+
+impl FromStr for Rounding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trunc"   => Ok(Rounding::Trunc),
+            "nearest" => Ok(Rounding::Nearest),
+            "up"      => Ok(Rounding::Up),
+            "down"    => Ok(Rounding::Down),
+            _ => Err(format!("Invalid rounding {}, expected trunc, nearest, up or down", s)),
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Rounding::Trunc   => "trunc",
+            Rounding::Nearest => "nearest",
+            Rounding::Up      => "up",
+            Rounding::Down    => "down",
+        })
+    }
+}
+
+/// Split `s` at the first `point`, e.g. `3,14159` at `,` gives `("3",
+/// Some("14159"))`. Without a radix point the fractional part is `None`.
+pub fn split_at_radix_point(s: &str, point: char) -> (String, Option<String>) {
+    match s.find(point) {
+        Some(i) => (s[..i].to_string(), Some(s[i + point.len_utf8()..].to_string())),
+        None    => (s.to_string(), None),
+    }
+}
+
+/// The greatest common divisor of `a` and `b`, `b` if `a` is 0
+pub fn gcd(a: u128, b: u128) -> u128 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// The least common multiple of `a` and `b`, 0 if either is 0, or `None`
+/// if it's too large for a u128
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Read `numerator/denominator`, both in `base` with `sep_char`
+/// separators, reduced to lowest terms. A zero denominator is an error, as
+/// is one too large to multiply by any base like `parse_fraction`'s.
+pub fn parse_rational(s: &str, base: u32, sep_char: char) -> Result<(u128, u128), ErrorCode> {
+    let (numerator, denominator) = match s.split_once('/') {
+        Some(v) => v,
+        None    => {
+            eprintln!("Expected NUMERATOR/DENOMINATOR, got {}", s);
+            return Err(ErrorCode::BaseConversionErr);
+        },
+    };
+    let numerator = convert_to_base_10(Some(numerator.to_string()), base, sep_char)?;
+    let denominator = convert_to_base_10(Some(denominator.to_string()), base, sep_char)?;
+    if denominator == 0 {
+        eprintln!("The denominator of {} is zero", s);
+        return Err(ErrorCode::BaseConversionErr);
+    }
+    let divisor = gcd(numerator, denominator);
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+    if denominator.checked_mul(36).is_none() {
+        eprintln!("The denominator of {} is too large", s);
+        return Err(ErrorCode::OverflowErr);
+    }
+    Ok((numerator, denominator))
+}
+
+/// Read decimal scientific notation like `1.5e3` or `2E-4` as an exact
+/// fraction in lowest terms, `point` being the radix point and `sep_char`
+/// separators ignored. `1.5e3` is `(1500, 1)`.
+///
+/// Malformed input is an error naming the column of the problem, as is a
+/// value or denominator too large like `parse_rational`'s.
+pub fn parse_scientific(s: &str, point: char, sep_char: char) -> Result<(u128, u128), ErrorCode> {
+    let invalid = |column: usize, problem: &str| {
+        eprintln!("{} at column {} of {}", problem, column, s);
+        ErrorCode::BaseConversionErr
+    };
+    let chars: Vec<char> = s.chars().collect();
+    let e = match chars.iter().position(|&c| c == 'e' || c == 'E') {
+        Some(v) => v,
+        None    => return Err(invalid(1, "Expected an exponent with e")),
+    };
+
+    let mut digits = String::new();
+    let mut frac_len: i64 = 0;
+    let mut seen_point = false;
+    for (i, &c) in chars[..e].iter().enumerate() {
+        match c {
+            '0'..='9' => {
+                digits.push(c);
+                frac_len += seen_point as i64;
+            },
+            c if c == sep_char => (),
+            c if c == point && !seen_point => seen_point = true,
+            c => return Err(invalid(i + 1, &format!("Invalid digit {}", c))),
+        }
+    }
+    if digits.is_empty() {
+        return Err(invalid(e + 1, "No digits before the exponent"));
+    }
+
+    let exponent = &chars[e + 1..];
+    let (negative, exponent_digits) = match exponent.first() {
+        Some('-') => (true, &exponent[1..]),
+        Some('+') => (false, &exponent[1..]),
+        _         => (false, exponent),
+    };
+    let first_digit = chars.len() - exponent_digits.len();
+    if exponent_digits.is_empty() {
+        return Err(invalid(first_digit + 1, "Missing exponent digits"));
+    }
+    let mut exponent: i64 = 0;
+    for (i, &c) in exponent_digits.iter().enumerate() {
+        match c.to_digit(10) {
+            // Past this any nonzero mantissa overflows anyway
+            Some(d) => exponent = (exponent * 10 + d as i64).min(1_000_000),
+            None    => return Err(invalid(first_digit + i + 1, &format!("Invalid exponent digit {}", c))),
+        }
+    }
+    let exponent = if negative { -exponent } else { exponent } - frac_len;
+
+    let mantissa = convert_to_base_10(Some(digits), 10, sep_char)?;
+    if mantissa == 0 {
+        return Ok((0, 1));
+    }
+    let power = 10u128.checked_pow(exponent.unsigned_abs().min(u32::MAX as u64) as u32);
+    let (numerator, denominator) = match power {
+        Some(p) if exponent >= 0 => match mantissa.checked_mul(p) {
+            Some(n) => (n, 1),
+            None    => {
+                eprintln!("{}", ErrorCode::OverflowErr);
+                return Err(ErrorCode::OverflowErr);
+            },
+        },
+        Some(p) => (mantissa, p),
+        None if exponent >= 0 => {
+            eprintln!("{}", ErrorCode::OverflowErr);
+            return Err(ErrorCode::OverflowErr);
+        },
+        None => {
+            eprintln!("The exponent of {} is too small", s);
+            return Err(ErrorCode::OverflowErr);
+        },
+    };
+    let divisor = gcd(numerator, denominator);
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+    if denominator.checked_mul(36).is_none() {
+        eprintln!("The exponent of {} is too small", s);
+        return Err(ErrorCode::OverflowErr);
+    }
+    Ok((numerator, denominator))
+}
+
+/// Read the digits after the radix point in `base` as an exact fraction.
+///
+/// `digits` in base B is `digits / B^len`. The denominator is kept small
+/// enough to multiply by any base, which allows 36 digits in base 10 or 24
+/// in base 36.
+pub fn parse_fraction(digits: &str, base: u32) -> Result<(u128, u128), ErrorCode> {
+    let mut denominator: u128 = 1;
+    for _ in digits.chars() {
+        denominator = match denominator.checked_mul(base as u128).filter(|d| d.checked_mul(36).is_some()) {
+            Some(v) => v,
+            None    => {
+                eprintln!("Too many fractional digits in {}", digits);
+                return Err(ErrorCode::OverflowErr);
+            },
+        };
+    }
+    match u128::from_str_radix(digits, base) {
+        Ok(numerator) if !digits.starts_with('+') => Ok((numerator, denominator)),
+        _ => {
+            eprintln!("Could not convert fractional digits {} from base {}", digits, base);
+            Err(ErrorCode::BaseConversionErr)
+        },
+    }
+}
+
+/// Up to `precision` digits of `numerator / denominator` in `base`,
+/// truncated. Stops early when the expansion ends, and is `0` for a zero
+/// fraction.
+pub fn fraction_digits(numerator: u128, denominator: u128, base: u32, precision: usize) -> String {
+    round_fraction_digits(numerator, denominator, base, precision, Rounding::Trunc).0
+}
+
+/// Up to `precision` digits of `numerator / denominator` in `base`, with
+/// the last one rounded by `rounding`. Also returns whether rounding
+/// carried into the integer part, e.g. 0.96 rounded to one decimal digit is
+/// 1.0, which gives `("0", true)`.
+pub fn round_fraction_digits(numerator: u128, denominator: u128, base: u32, precision: usize,
+                             rounding: Rounding) -> (String, bool) {
+    let mut digits = Vec::new();
+    let mut remainder = numerator % denominator;
+    while remainder != 0 && digits.len() < precision {
+        // remainder < denominator, which leaves room to multiply by the base
+        remainder *= base as u128;
+        digits.push((remainder / denominator) as u32);
+        remainder %= denominator;
+    }
+
+    // What's left is remainder / denominator of the last digit
+    let round_up = match rounding {
+        Rounding::Trunc | Rounding::Down => false,
+        Rounding::Up                     => remainder != 0,
+        Rounding::Nearest                => remainder != 0 && remainder * 2 >= denominator,
+    };
+    let mut carry = round_up;
+    for digit in digits.iter_mut().rev() {
+        if !carry {
+            break;
+        }
+        *digit += 1;
+        carry = *digit == base;
+        if carry {
+            *digit = 0;
+        }
+    }
+
+    let mut digits: String = digits.iter()
+        .map(|&d| std::char::from_digit(d, base).unwrap().to_ascii_uppercase())
+        .collect();
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    (digits, carry)
+}
+
+/// `numerator / denominator` in `base` as the digits before the part that
+/// repeats and the part that repeats, which is empty when the expansion
+/// ends. 1/10 in base 2 is `("0", "0011")` for 0.0(0011). `None` if neither
+/// happens within `max_digits` digits.
+pub fn repeating_fraction_digits(numerator: u128, denominator: u128, base: u32,
+                                 max_digits: usize) -> Option<(String, String)> {
+    let mut digits = String::new();
+    // Where each remainder was first seen, a remainder seen again starts the cycle
+    let mut seen: HashMap<u128, usize> = HashMap::new();
+    let mut remainder = numerator % denominator;
+    while remainder != 0 {
+        if let Some(&start) = seen.get(&remainder) {
+            let cycle = digits.split_off(start);
+            return Some((digits, cycle));
+        }
+        if digits.len() == max_digits {
+            return None;
+        }
+        seen.insert(remainder, digits.len());
+        remainder *= base as u128;
+        digits.push(std::char::from_digit((remainder / denominator) as u32, base).unwrap().to_ascii_uppercase());
+        remainder %= denominator;
+    }
+    Some((digits, String::new()))
+}
+
+/// `n` as a percentage of `total` to `precision` decimal places, rounded to
+/// nearest, like `49.80%` for 127 of 255. `total` must not be 0.
+pub fn as_percent(n: u128, total: u128, precision: u32) -> String {
+    scaled_ratio(n, total, 100, precision) + "%"
+}
+
+/// `n` in parts per thousand of `total`, like `as_percent`
+pub fn as_permille(n: u128, total: u128, precision: u32) -> String {
+    scaled_ratio(n, total, 1000, precision) + "\u{2030}"
+}
+
+/// `n / total * scale` to `precision` decimal places. Exact, unless the
+/// values are too near `u128::MAX` to scale, when it falls back to floating
+/// point.
+fn scaled_ratio(n: u128, total: u128, scale: u128, precision: u32) -> String {
+    assert!(total != 0, "the total of a ratio can't be 0");
+    let exact = || {
+        let (q, r) = (n / total, n % total);
+        let scaled = r.checked_mul(scale)?;
+        let whole = q.checked_mul(scale)?.checked_add(scaled / total)?;
+        // round_fraction_digits multiplies the remainder by 10
+        total.checked_mul(10)?;
+        let (digits, carry) = round_fraction_digits(scaled % total, total, 10, precision as usize, Rounding::Nearest);
+        let whole = whole.checked_add(carry as u128)?;
+        Some(match precision {
+            0 => whole.to_string(),
+            _ => format!("{}.{:0<width$}", whole, digits, width = precision as usize),
+        })
+    };
+    exact().unwrap_or_else(|| format!("{:.*}", precision as usize, n as f64 / total as f64 * scale as f64))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_at_radix_point() {
+        assert_eq!(split_at_radix_point("3.14159", '.'), ("3".to_owned(), Some("14159".to_owned())));
+        assert_eq!(split_at_radix_point("3,14159", ','), ("3".to_owned(), Some("14159".to_owned())));
+        assert_eq!(split_at_radix_point("3,14159", '.'), ("3,14159".to_owned(), None));
+        assert_eq!(split_at_radix_point("1011", '.'), ("1011".to_owned(), None));
+        assert_eq!(split_at_radix_point(".5", '.'), ("".to_owned(), Some("5".to_owned())));
+        assert_eq!(split_at_radix_point("2.", '.'), ("2".to_owned(), Some("".to_owned())));
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(7, 7), Some(7));
+        assert_eq!(lcm(0, 5), Some(0));
+        assert_eq!(lcm(1, u128::MAX), Some(u128::MAX));
+        assert_eq!(lcm(1 << 64, 1 << 100), Some(1 << 100));
+        assert_eq!(lcm(u128::MAX, u128::MAX - 1), None);
+        assert_eq!(lcm(1 << 127, 3), None);
+    }
+
+    #[test]
+    fn test_parse_rational() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(parse_rational("3/7", 10, '_'), Ok((3, 7)));
+        assert_eq!(parse_rational("6/8", 10, '_'), Ok((3, 4)));
+        assert_eq!(parse_rational("0/5", 10, '_'), Ok((0, 1)));
+        assert_eq!(parse_rational("1_0/1_00", 10, '_'), Ok((1, 10)));
+        assert_eq!(parse_rational("F/1E", 16, '_'), Ok((1, 2)));
+        assert_eq!(parse_rational("1/0", 10, '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_rational("1/", 10, '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_rational("12/3", 2, '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_rational(&format!("1/{}", u128::MAX / 2), 10, '_'), Err(ErrorCode::OverflowErr));
+    }
+
+    #[test]
+    fn test_parse_scientific() {
+        assert_eq!(parse_scientific("1.5e3", '.', '_'), Ok((1500, 1)));
+        assert_eq!(parse_scientific("1E3", '.', '_'), Ok((1000, 1)));
+        assert_eq!(parse_scientific("6.02e23", '.', '_'), Ok((602_000_000_000_000_000_000_000, 1)));
+        assert_eq!(parse_scientific("1_000e+0", '.', '_'), Ok((1000, 1)));
+        assert_eq!(parse_scientific("2E-4", '.', '_'), Ok((1, 5000)));
+        assert_eq!(parse_scientific("1.25e1", '.', '_'), Ok((25, 2)));
+        assert_eq!(parse_scientific("1,5e1", ',', '_'), Ok((15, 1)));
+        assert_eq!(parse_scientific("0e99999999", '.', '_'), Ok((0, 1)));
+        assert_eq!(parse_scientific(".5e1", '.', '_'), Ok((5, 1)));
+        assert_eq!(parse_scientific("1e39", '.', '_'), Err(ErrorCode::OverflowErr));
+        assert_eq!(parse_scientific("1e99999999999", '.', '_'), Err(ErrorCode::OverflowErr));
+        assert_eq!(parse_scientific("1e-40", '.', '_'), Err(ErrorCode::OverflowErr));
+        assert_eq!(parse_scientific("1.5e", '.', '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_scientific("1.5e-", '.', '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_scientific("e5", '.', '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_scientific("1.2.3e5", '.', '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_scientific("1e5e2", '.', '_'), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_scientific("15", '.', '_'), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_parse_fraction() {
+        assert_eq!(parse_fraction("5", 10), Ok((5, 10)));
+        assert_eq!(parse_fraction("14159", 10), Ok((14159, 100_000)));
+        assert_eq!(parse_fraction("8", 16), Ok((8, 16)));
+        assert_eq!(parse_fraction("01", 2), Ok((1, 4)));
+        assert_eq!(parse_fraction(&"9".repeat(36), 10).map(|f| f.1), Ok(10u128.pow(36)));
+        assert_eq!(parse_fraction(&"9".repeat(37), 10), Err(ErrorCode::OverflowErr));
+        assert_eq!(parse_fraction("G", 16), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse_fraction("+1", 10), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_fraction_digits() {
+        assert_eq!(fraction_digits(5, 10, 2, 10), "1");
+        assert_eq!(fraction_digits(1, 10, 2, 10), "0001100110");
+        assert_eq!(fraction_digits(8, 16, 10, 10), "5");
+        assert_eq!(fraction_digits(1, 3, 10, 4), "3333");
+        assert_eq!(fraction_digits(14159, 100_000, 10, 10), "14159");
+        assert_eq!(fraction_digits(0, 10, 16, 10), "0");
+        assert_eq!(fraction_digits(1, 4, 16, 10), "4");
+        assert_eq!(fraction_digits(35, 36, 36, 10), "Z");
+    }
+
+    #[test]
+    fn test_rounding() {
+        let round = |n, d, base, precision, rounding| round_fraction_digits(n, d, base, precision, rounding);
+        // 0.999999 at four decimal digits
+        assert_eq!(round(999_999, 1_000_000, 10, 4, Rounding::Trunc), ("9999".to_string(), false));
+        assert_eq!(round(999_999, 1_000_000, 10, 4, Rounding::Nearest), ("0000".to_string(), true));
+        assert_eq!(round(999_999, 1_000_000, 10, 4, Rounding::Up), ("0000".to_string(), true));
+        assert_eq!(round(999_999, 1_000_000, 10, 4, Rounding::Down), ("9999".to_string(), false));
+
+        // 0.1 is 0.000110011... in base 2
+        assert_eq!(round(1, 10, 2, 4, Rounding::Trunc), ("0001".to_string(), false));
+        assert_eq!(round(1, 10, 2, 4, Rounding::Nearest), ("0010".to_string(), false));
+        assert_eq!(round(1, 10, 2, 4, Rounding::Up), ("0010".to_string(), false));
+        assert_eq!(round(1, 10, 2, 4, Rounding::Down), ("0001".to_string(), false));
+        assert_eq!(round(1, 10, 2, 2, Rounding::Nearest), ("00".to_string(), false));
+        assert_eq!(round(1, 10, 2, 2, Rounding::Up), ("01".to_string(), false));
+
+        // 0.1 is 0.1999... in base 16, and a tie rounds away from zero
+        assert_eq!(round(1, 10, 16, 2, Rounding::Trunc), ("19".to_string(), false));
+        assert_eq!(round(1, 10, 16, 2, Rounding::Nearest), ("1A".to_string(), false));
+        assert_eq!(round(1, 10, 16, 2, Rounding::Up), ("1A".to_string(), false));
+        assert_eq!(round(1, 10, 16, 2, Rounding::Down), ("19".to_string(), false));
+        assert_eq!(round(0x18, 0x100, 16, 1, Rounding::Nearest), ("2".to_string(), false));
+        assert_eq!(round(0xF8, 0x100, 16, 1, Rounding::Nearest), ("0".to_string(), true));
+
+        // A terminating expansion is exact whatever the rounding
+        assert_eq!(round(1, 4, 2, 10, Rounding::Up), ("01".to_string(), false));
+        assert_eq!("nearest".parse::<Rounding>(), Ok(Rounding::Nearest));
+        assert!("half-even".parse::<Rounding>().is_err());
+    }
+
+    #[test]
+    fn test_repeating_fraction_digits() {
+        let repeating = |n, d, base, max| repeating_fraction_digits(n, d, base, max)
+            .map(|(prefix, cycle)| prefix + "(" + &cycle + ")");
+        // Terminating
+        assert_eq!(repeating(1, 4, 2, 100), Some("01()".to_string()));
+        assert_eq!(repeating(0, 10, 16, 100), Some("()".to_string()));
+        assert_eq!(repeating(5, 10, 10, 1), Some("5()".to_string()));
+        // Short cycles
+        assert_eq!(repeating(1, 10, 2, 100), Some("0(0011)".to_string()));
+        assert_eq!(repeating(1, 3, 10, 100), Some("(3)".to_string()));
+        assert_eq!(repeating(1, 6, 10, 100), Some("1(6)".to_string()));
+        assert_eq!(repeating(1, 10, 16, 100), Some("1(9)".to_string()));
+        // Long cycles, 1/97 repeats every 96 digits
+        assert_eq!(repeating(1, 7, 10, 100), Some("(142857)".to_string()));
+        assert_eq!(repeating_fraction_digits(1, 97, 10, 100).map(|(p, c)| (p.len(), c.len())), Some((0, 96)));
+        assert_eq!(repeating(1, 97, 10, 95), None);
+    }
+
+    #[test]
+    fn test_as_percent() {
+        assert_eq!(as_percent(128, 256, 3), "50.000%");
+        assert_eq!(as_percent(127, 255, 2), "49.80%");
+        assert_eq!(as_percent(1, 3, 4), "33.3333%");
+        assert_eq!(as_percent(2, 3, 2), "66.67%");
+        assert_eq!(as_percent(2, 3, 0), "67%");
+        assert_eq!(as_percent(0, 7, 1), "0.0%");
+        assert_eq!(as_percent(255, 255, 2), "100.00%");
+        assert_eq!(as_percent(999, 1000, 0), "100%");
+        assert_eq!(as_percent(510, 255, 1), "200.0%");
+        assert_eq!(as_percent(u128::MAX, u128::MAX, 2), "100.00%");
+        assert_eq!(as_percent(u128::MAX / 2, u128::MAX, 2), "50.00%");
+    }
+
+    #[test]
+    fn test_as_permille() {
+        assert_eq!(as_permille(128, 256, 3), "500.000\u{2030}");
+        assert_eq!(as_permille(127, 255, 2), "498.04\u{2030}");
+        assert_eq!(as_permille(1, 7, 5), "142.85714\u{2030}");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ratio_of_zero_total() {
+        as_percent(1, 0, 2);
+    }
+}
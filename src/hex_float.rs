@@ -0,0 +1,255 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Hex float literals like `0x1.8p3`, as C's `%a` and Rust's float
+//! formatting crates print them: hex digits scaled by a power of two.
+
+use crate::{fraction::gcd, ErrorCode};
+
+/// An exact hex float value, `mantissa * 2^exponent`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexFloat {
+    pub negative: bool,
+    pub mantissa: u128,
+    pub exponent: i32,
+}
+
+/// Exponents are clamped to this, far past where any nonzero value
+/// overflows or rounds to zero
+const MAX_EXPONENT: i32 = 1_000_000;
+
+/// Whether `s` looks like a hex float, `0x` digits with a `p` exponent
+pub fn is_hex_float(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    (digits.starts_with("0x") || digits.starts_with("0X")) && digits.contains(['p', 'P'])
+}
+
+impl HexFloat {
+    /// Read `[-+]0xH.HHHp[-+]D`, ignoring `sep_char` separators in the hex
+    /// digits. The fractional digits and radix point are optional, the `p`
+    /// exponent isn't. Malformed input is an error naming the column of the
+    /// problem, and more than 32 significant hex digits are an overflow.
+    pub fn parse(s: &str, sep_char: char) -> Result<Self, ErrorCode> {
+        let invalid = |column: usize, problem: &str| {
+            eprintln!("{} at column {} of {}", problem, column, s);
+            ErrorCode::BaseConversionErr
+        };
+        let chars: Vec<char> = s.chars().collect();
+        let (negative, start) = match chars.first() {
+            Some('-') => (true, 1),
+            Some('+') => (false, 1),
+            _         => (false, 0),
+        };
+        if !matches!(chars.get(start..start + 2), Some(['0', 'x']) | Some(['0', 'X'])) {
+            return Err(invalid(start + 1, "Expected 0x"));
+        }
+        let p = match chars.iter().position(|&c| c == 'p' || c == 'P') {
+            Some(v) => v,
+            None    => return Err(invalid(chars.len() + 1, "Expected a binary exponent with p")),
+        };
+
+        let mut mantissa: u128 = 0;
+        let mut exponent: i64 = 0;
+        let mut any_digits = false;
+        let mut seen_point = false;
+        for (i, &c) in chars.iter().enumerate().take(p).skip(start + 2) {
+            match c.to_digit(16) {
+                Some(d) => {
+                    mantissa = match mantissa.checked_mul(16) {
+                        Some(v) => v + d as u128,
+                        None    => {
+                            eprintln!("Too many hex digits in {}, the most is 32", s);
+                            return Err(ErrorCode::OverflowErr);
+                        },
+                    };
+                    exponent -= 4 * seen_point as i64;
+                    any_digits = true;
+                },
+                None if c == sep_char => (),
+                None if c == '.' && !seen_point => seen_point = true,
+                None => return Err(invalid(i + 1, &format!("Invalid hex digit {}", c))),
+            }
+        }
+        if !any_digits {
+            return Err(invalid(p + 1, "No digits before the exponent"));
+        }
+
+        let (exponent_negative, first_digit) = match chars.get(p + 1) {
+            Some('-') => (true, p + 2),
+            Some('+') => (false, p + 2),
+            _         => (false, p + 1),
+        };
+        if first_digit >= chars.len() {
+            return Err(invalid(first_digit + 1, "Missing exponent digits"));
+        }
+        let mut binary_exponent: i64 = 0;
+        for (i, &c) in chars.iter().enumerate().skip(first_digit) {
+            match c.to_digit(10) {
+                Some(d) => binary_exponent = (binary_exponent * 10 + d as i64).min(MAX_EXPONENT as i64),
+                None    => return Err(invalid(i + 1, &format!("Invalid exponent digit {}", c))),
+            }
+        }
+        if exponent_negative {
+            binary_exponent = -binary_exponent;
+        }
+        let exponent = (exponent + binary_exponent).clamp(-MAX_EXPONENT as i64, MAX_EXPONENT as i64) as i32;
+        Ok(HexFloat { negative, mantissa, exponent })
+    }
+
+    /// The magnitude as `numerator / denominator` in lowest terms, or `None`
+    /// if the numerator doesn't fit in a u128 or the denominator is too
+    /// large to multiply by any base like `parse_fraction`'s
+    pub fn to_fraction(&self) -> Option<(u128, u128)> {
+        if self.mantissa == 0 {
+            return Some((0, 1));
+        }
+        if self.exponent >= 0 {
+            if self.exponent as u32 > self.mantissa.leading_zeros() {
+                return None;
+            }
+            return Some((self.mantissa << self.exponent, 1));
+        }
+        let denominator = 1u128.checked_shl(self.exponent.unsigned_abs())
+            .filter(|d| d.checked_mul(36).is_some());
+        match denominator {
+            Some(d) => {
+                let divisor = gcd(self.mantissa, d);
+                Some((self.mantissa / divisor, d / divisor))
+            },
+            // Trailing zero bits of the mantissa may bring it back in range
+            None => {
+                let shift = self.mantissa.trailing_zeros().min(self.exponent.unsigned_abs());
+                let reduced = HexFloat { mantissa: self.mantissa >> shift, exponent: self.exponent + shift as i32, ..*self };
+                if shift == 0 { None } else { reduced.to_fraction() }
+            },
+        }
+    }
+
+    /// The nearest f64, rounding ties to even
+    pub fn to_f64(&self) -> f64 {
+        f64::from_bits(self.nearest_bits(52, 11))
+    }
+
+    /// The nearest f32, rounding ties to even
+    pub fn to_f32(&self) -> f32 {
+        f32::from_bits(self.nearest_bits(23, 8) as u32)
+    }
+
+    /// The IEEE 754 bits of the nearest binary float with `fraction_bits`
+    /// stored mantissa bits and `exponent_bits` exponent bits. Values too
+    /// large are infinity and values too small round to zero through the
+    /// subnormals.
+    fn nearest_bits(&self, fraction_bits: u32, exponent_bits: u32) -> u64 {
+        let sign = (self.negative as u64) << (fraction_bits + exponent_bits);
+        if self.mantissa == 0 {
+            return sign;
+        }
+        let bias = (1i64 << (exponent_bits - 1)) - 1;
+        let infinity = ((1u64 << exponent_bits) - 1) << fraction_bits;
+        // The value is in [2^top, 2^(top + 1))
+        let top = 127 - self.mantissa.leading_zeros() as i64 + self.exponent as i64;
+        if top > bias {
+            return sign | infinity;
+        }
+        // The power of two of the last mantissa bit kept, fixed for subnormals
+        let last = top.max(1 - bias) - fraction_bits as i64;
+        let shift = last - self.exponent as i64;
+        let kept = if shift <= 0 {
+            self.mantissa << -shift
+        } else if shift > 128 {
+            0
+        } else {
+            let kept = self.mantissa.checked_shr(shift as u32).unwrap_or(0);
+            let rest = self.mantissa - kept.checked_shl(shift as u32).unwrap_or(0);
+            let half = 1u128 << (shift - 1);
+            if rest > half || (rest == half && kept & 1 == 1) { kept + 1 } else { kept }
+        } as u64;
+        // Rounding up into the next power of two carries into the exponent,
+        // and for the largest finite values on to infinity
+        if top < 1 - bias {
+            sign | kept
+        } else {
+            sign | ((((top + bias) as u64) << fraction_bits) + kept - (1u64 << fraction_bits)).min(infinity)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<HexFloat, ErrorCode> {
+        HexFloat::parse(s, '_')
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("0x1.8p3"), Ok(HexFloat { negative: false, mantissa: 0x18, exponent: -1 }));
+        assert_eq!(parse("0x1p10"), Ok(HexFloat { negative: false, mantissa: 1, exponent: 10 }));
+        assert_eq!(parse("-0X1.8P-1"), Ok(HexFloat { negative: true, mantissa: 0x18, exponent: -5 }));
+        assert_eq!(parse("+0x.8p+1"), Ok(HexFloat { negative: false, mantissa: 8, exponent: -3 }));
+        assert_eq!(parse("0x1.p0"), Ok(HexFloat { negative: false, mantissa: 1, exponent: 0 }));
+        assert_eq!(parse("0xFF_FFp0"), Ok(HexFloat { negative: false, mantissa: 0xFFFF, exponent: 0 }));
+        assert_eq!(parse("0x1p999999999999").unwrap().exponent, MAX_EXPONENT);
+        assert_eq!(parse("0x1.8"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0x1.8p"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0x1.8p-"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0xp3"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0x1.8.1p3"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0x1Gp3"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("0x1p3.5"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse("1.8p3"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(parse(&format!("0x{}p0", "F".repeat(33))), Err(ErrorCode::OverflowErr));
+        assert!(is_hex_float("0x1.8p3"));
+        assert!(is_hex_float("-0X1P3"));
+        assert!(!is_hex_float("0x18"));
+        assert!(!is_hex_float("1p3"));
+    }
+
+    #[test]
+    fn test_to_fraction() {
+        assert_eq!(parse("0x1.8p3").unwrap().to_fraction(), Some((12, 1)));
+        assert_eq!(parse("0x1p10").unwrap().to_fraction(), Some((1024, 1)));
+        assert_eq!(parse("0x1.8p-1").unwrap().to_fraction(), Some((3, 4)));
+        assert_eq!(parse("0x0p-99999").unwrap().to_fraction(), Some((0, 1)));
+        assert_eq!(parse("0x1p127").unwrap().to_fraction(), Some((1 << 127, 1)));
+        assert_eq!(parse("0x1p128").unwrap().to_fraction(), None);
+        assert_eq!(parse("0x1p-123").unwrap().to_fraction(), None);
+        assert_eq!(parse("0x100p-130").unwrap().to_fraction(), Some((1, 1 << 122)));
+    }
+
+    #[test]
+    fn test_nearest_floats() {
+        let f64_of = |s: &str| parse(s).unwrap().to_f64();
+        let f32_of = |s: &str| parse(s).unwrap().to_f32();
+        assert_eq!(f64_of("0x1.8p3"), 12.0);
+        assert_eq!(f32_of("0x1.8p3").to_bits(), 0x4140_0000);
+        assert_eq!(f64_of("-0x1.8p-1"), -0.75);
+        assert_eq!(f64_of("0x1.999999999999ap-4"), 0.1);
+        assert_eq!(f32_of("0x1.99999ap-4"), 0.1);
+        assert_eq!(f64_of("-0x0p0").to_bits(), (-0.0f64).to_bits());
+        // Ties go to the even mantissa
+        assert_eq!(f32_of("0x1.000001p0"), 1.0);
+        assert_eq!(f32_of("0x1.000003p0").to_bits(), 0x3F80_0002);
+        // Subnormals, and rounding to zero below them
+        assert_eq!(f64_of("0x1p-1074").to_bits(), 1);
+        assert_eq!(f64_of("0x1p-1075").to_bits(), 0);
+        assert_eq!(f64_of("0x1.8p-1075").to_bits(), 1);
+        assert_eq!(f64_of("0x1p-1022"), f64::MIN_POSITIVE);
+        assert_eq!(f64_of("0x0.fffffffffffff8p-1022"), f64::MIN_POSITIVE);
+        assert_eq!(f32_of("0x1p-149").to_bits(), 1);
+        assert_eq!(f64_of("0x1p-99999"), 0.0);
+        // Up to infinity
+        assert_eq!(f64_of("0x1.fffffffffffffp1023"), f64::MAX);
+        assert_eq!(f64_of("0x1.fffffffffffff8p1023"), f64::INFINITY);
+        assert_eq!(f32_of("0x1p128"), f32::INFINITY);
+        assert_eq!(f32_of("-0x1p999999"), f32::NEG_INFINITY);
+        assert_eq!(f64_of(&format!("0x{}p0", "F".repeat(32))), u128::MAX as f64);
+    }
+}
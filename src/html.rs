@@ -0,0 +1,122 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! HTML tables for `--format html` output.
+
+/// Escape `s` for HTML text or a quoted attribute value
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&'  => escaped.push_str("&amp;"),
+            '<'  => escaped.push_str("&lt;"),
+            '>'  => escaped.push_str("&gt;"),
+            '"'  => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c    => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// An HTML5 table fragment of one number, `input`, with a row for each
+/// `(base, value)` in `results`. Each row has a `data-base` attribute for
+/// scripts to find it by.
+pub fn format_as_html_table(results: &[(u32, String)], input: &str, class: Option<&str>) -> String {
+    let mut html = match class {
+        Some(class) => format!("<table class=\"{}\">\n", escape(class)),
+        None        => String::from("<table>\n"),
+    };
+    html.push_str(&format!("  <caption>{}</caption>\n", escape(input)));
+    html.push_str("  <thead>\n    <tr><th>Base</th><th>Value</th></tr>\n  </thead>\n  <tbody>\n");
+    for (base, value) in results {
+        html.push_str(&format!("    <tr data-base=\"{0}\"><td>{0}</td><td>{1}</td></tr>\n", base, escape(value)));
+    }
+    html.push_str("  </tbody>\n</table>");
+    html
+}
+
+/// Wrap `body` in a whole HTML5 document titled `title`
+pub fn full_document(body: &str, title: &str) -> String {
+    format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n\
+             <body>\n{}\n</body>\n</html>", escape(title), body)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tags in `html` in order, without attributes, `/` first for closing tags
+    fn tags(html: &str) -> Vec<String> {
+        html.split('<').skip(1)
+            .map(|tag| tag[..tag.find('>').expect("unclosed tag")].split(' ').next().unwrap().to_string())
+            .collect()
+    }
+
+    /// Whether every tag in `html` is closed in order, ignoring void elements
+    fn balanced(html: &str) -> bool {
+        let mut open = Vec::new();
+        for tag in tags(html) {
+            if tag.starts_with('!') || tag == "meta" {
+                continue;
+            }
+            match tag.strip_prefix('/') {
+                Some(name) => if open.pop().as_deref() != Some(name) { return false },
+                None       => open.push(tag),
+            }
+        }
+        open.is_empty()
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("1_000"), "1_000");
+        assert_eq!(escape("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+    }
+
+    #[test]
+    fn test_format_as_html_table() {
+        let results = vec![(2, "1111_1111".to_string()), (16, "FF".to_string())];
+        let table = format_as_html_table(&results, "255", None);
+        assert_eq!(table, "<table>\n  <caption>255</caption>\n  \
+                           <thead>\n    <tr><th>Base</th><th>Value</th></tr>\n  </thead>\n  <tbody>\n    \
+                           <tr data-base=\"2\"><td>2</td><td>1111_1111</td></tr>\n    \
+                           <tr data-base=\"16\"><td>16</td><td>FF</td></tr>\n  </tbody>\n</table>");
+        assert!(balanced(&table));
+        assert!(!balanced("<table><tr></table>"));
+    }
+
+    #[test]
+    fn test_format_as_html_table_escapes() {
+        let table = format_as_html_table(&[(36, "<b>".to_string())], "a&b", Some("x\" onclick=\"y"));
+        assert!(table.starts_with("<table class=\"x&quot; onclick=&quot;y\">\n  <caption>a&amp;b</caption>"));
+        assert!(table.contains("<td>&lt;b&gt;</td>"));
+        assert!(balanced(&table));
+    }
+
+    #[test]
+    fn test_format_as_html_table_attributes() {
+        let table = format_as_html_table(&[(8, "377".to_string()), (16, "FF".to_string())], "255", Some("nums"));
+        assert!(table.starts_with("<table class=\"nums\">"));
+        // Every attribute is name="value"
+        for tag in table.split('<').skip(1) {
+            let tag = &tag[..tag.find('>').unwrap()];
+            assert!(tag.split(' ').skip(1).all(|attr| attr.contains("=\"") && attr.ends_with('"')), "{}", tag);
+        }
+    }
+
+    #[test]
+    fn test_full_document() {
+        let doc = full_document(&format_as_html_table(&[(10, "5".to_string())], "5", Some("numbers")), "5 <x>");
+        assert!(doc.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+        assert!(doc.contains("<title>5 &lt;x&gt;</title>"));
+        assert!(doc.ends_with("</body>\n</html>"));
+        assert!(balanced(&doc));
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Just enough JSON to write `--json` output.
+
+use std::fmt;
+
+use crate::ConversionResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i128),
+    UInt(u128),
+    Str(String),
+    Array(Vec<Json>),
+    /// Keys are written in insertion order
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object() -> Self {
+        Json::Object(Vec::new())
+    }
+
+    /// Add `key` to an object. Does nothing for other values.
+    pub fn with<T: Into<Json>>(mut self, key: &str, value: T) -> Self {
+        if let Json::Object(fields) = &mut self {
+            fields.push((key.to_string(), value.into()));
+        }
+        self
+    }
+}
+
+impl From<bool>   for Json { fn from(v: bool)   -> Self { Json::Bool(v) } }
+impl From<u32>    for Json { fn from(v: u32)    -> Self { Json::UInt(v as u128) } }
+impl From<usize>  for Json { fn from(v: usize)  -> Self { Json::UInt(v as u128) } }
+impl From<u128>   for Json { fn from(v: u128)   -> Self { Json::UInt(v) } }
+impl From<i128>   for Json { fn from(v: i128)   -> Self { Json::Int(v) } }
+impl From<&str>   for Json { fn from(v: &str)   -> Self { Json::Str(v.to_string()) } }
+impl From<String> for Json { fn from(v: String) -> Self { Json::Str(v) } }
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<&ConversionResult> for Json {
+    fn from(conversion: &ConversionResult) -> Self {
+        conversion_json(conversion, |_, result| result)
+    }
+}
+
+/// `conversion` as `--json` writes it, `{input, from_base, ok, decimal,
+/// results: [{base, value}]}`, with `extra` adding fields to the result for
+/// each base
+pub fn conversion_json<F: FnMut(u32, Json) -> Json>(conversion: &ConversionResult, mut extra: F) -> Json {
+    let results: Vec<Json> = conversion.results.iter()
+        .map(|(base, digits)| extra(*base, Json::object().with("base", *base).with("value", digits.as_str())))
+        .collect();
+    Json::object()
+        .with("input", conversion.input.as_str())
+        .with("from_base", conversion.from_base)
+        .with("ok", true)
+        .with("decimal", conversion.decimal)
+        .with("results", results)
+}
+
+/// Quote and escape `s` as a JSON string
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"'  => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl fmt::Display for Json {
+    /// Compact JSON, with no whitespace between tokens
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null     => write!(f, "null"),
+            Json::Bool(v)  => write!(f, "{}", v),
+            Json::Int(v)   => write!(f, "{}", v),
+            Json::UInt(v)  => write!(f, "{}", v),
+            Json::Str(v)   => write!(f, "{}", escape(v)),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", escape(key), value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_json() {
+        let conversion = ConversionResult::new("FF", 16, &[2]).unwrap();
+        assert_eq!(Json::from(&conversion).to_string(),
+                   r#"{"input":"FF","from_base":16,"ok":true,"decimal":255,"results":[{"base":2,"value":"11111111"}]}"#);
+        let tagged = conversion_json(&conversion, |base, result| result.with("even", base % 2 == 0));
+        assert!(tagged.to_string().ends_with(r#""results":[{"base":2,"value":"11111111","even":true}]}"#));
+    }
+
+    #[test]
+    fn test_json_display() {
+        let json = Json::object()
+            .with("input", "FF")
+            .with("from_base", 16u32)
+            .with("ok", true)
+            .with("missing", None::<u32>)
+            .with("results", vec![Json::object().with("base", 2u32).with("value", "11111111")]);
+        assert_eq!(json.to_string(),
+                   r#"{"input":"FF","from_base":16,"ok":true,"missing":null,"results":[{"base":2,"value":"11111111"}]}"#);
+        assert_eq!(Json::Int(-2).to_string(), "-2");
+        assert_eq!(Json::UInt(u128::MAX).to_string(), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(escape("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(escape("line\nbreak\u{1}"), r#""line\nbreak\u0001""#);
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! LaTeX tables for `--format latex` output.
+
+/// Escape the characters of `s` that are special in LaTeX text, such as the
+/// `_` separators
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            '~'  => escaped.push_str("\\textasciitilde{}"),
+            '^'  => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c    => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A `tabular` of one number with a row for each `(base, value)` in
+/// `results`, in a floating `table` with `caption` when there is one
+pub fn format_as_latex_table(results: &[(u32, String)], caption: Option<&str>) -> String {
+    format_as_latex_table_with_label(results, caption, None)
+}
+
+/// `format_as_latex_table`, floating with a `\label` as well when there is
+/// a `label`
+pub fn format_as_latex_table_with_label(results: &[(u32, String)], caption: Option<&str>,
+                                        label: Option<&str>) -> String {
+    let mut tabular = String::from("\\begin{tabular}{rl}\n\\hline\nBase & Value \\\\\n\\hline\n");
+    for (base, value) in results {
+        tabular.push_str(&format!("{} & {} \\\\\n", base, escape(value)));
+    }
+    tabular.push_str("\\hline\n\\end{tabular}");
+    if caption.is_none() && label.is_none() {
+        return tabular;
+    }
+    let mut table = String::from("\\begin{table}\n\\centering\n");
+    if let Some(caption) = caption {
+        table.push_str(&format!("\\caption{{{}}}\n", escape(caption)));
+    }
+    if let Some(label) = label {
+        table.push_str(&format!("\\label{{{}}}\n", label));
+    }
+    table + &tabular + "\n\\end{table}"
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether every `\begin{..}` in `latex` has a matching `\end{..}`, in order
+    fn balanced(latex: &str) -> bool {
+        let mut open = Vec::new();
+        for (i, _) in latex.match_indices('\\') {
+            let rest = &latex[i + 1..];
+            let name = |rest: &str| rest[rest.find('{').unwrap() + 1..rest.find('}').unwrap()].to_string();
+            if rest.starts_with("begin{") {
+                open.push(name(rest));
+            } else if rest.starts_with("end{") && open.pop() != Some(name(rest)) {
+                return false;
+            }
+        }
+        open.is_empty()
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("1111_1111"), "1111\\_1111");
+        assert_eq!(escape("FF"), "FF");
+        assert_eq!(escape("50% & $5 #1 {x}"), "50\\% \\& \\$5 \\#1 \\{x\\}");
+        assert_eq!(escape("~^\\"), "\\textasciitilde{}\\textasciicircum{}\\textbackslash{}");
+    }
+
+    #[test]
+    fn test_format_as_latex_table() {
+        let results = vec![(2, "1111_1111".to_string()), (16, "FF".to_string())];
+        let table = format_as_latex_table(&results, None);
+        assert_eq!(table, "\\begin{tabular}{rl}\n\\hline\nBase & Value \\\\\n\\hline\n\
+                           2 & 1111\\_1111 \\\\\n16 & FF \\\\\n\\hline\n\\end{tabular}");
+        assert!(balanced(&table));
+        assert!(!balanced("\\begin{table}\\begin{tabular}\\end{table}"));
+    }
+
+    #[test]
+    fn test_format_as_latex_table_floating() {
+        let results = vec![(10, "255".to_string())];
+        let table = format_as_latex_table_with_label(&results, Some("255_10"), Some("tab:255"));
+        assert!(table.starts_with("\\begin{table}\n\\centering\n\\caption{255\\_10}\n\\label{tab:255}\n\\begin{tabular}{rl}\n"));
+        assert!(table.ends_with("\\end{tabular}\n\\end{table}"));
+        assert!(balanced(&table));
+        let labelled = format_as_latex_table_with_label(&results, None, Some("tab:x"));
+        assert!(labelled.starts_with("\\begin{table}\n\\centering\n\\label{tab:x}\n"));
+        assert!(balanced(&labelled));
+    }
+}
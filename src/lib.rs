@@ -0,0 +1,833 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Number base conversion, plus the number properties and alternative
+//! notations the `numconverter` CLI can print alongside a conversion.
+//!
+//! Everything but `ErrorCode` and the `fixed` module needs the default `std`
+//! feature. Without it the crate is `no_std` and doesn't allocate.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+use std::{num::IntErrorKind, convert::TryInto, string::ToString, collections::HashMap, io};
+use core::cmp::PartialEq;
+
+#[cfg(feature = "std")]
+pub mod abbrev;
+#[cfg(feature = "std")]
+pub mod base_info;
+#[cfg(feature = "std")]
+pub mod base_num;
+#[cfg(feature = "std")]
+pub mod bits;
+#[cfg(feature = "std")]
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod constants;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+#[cfg(feature = "std")]
+pub mod factor;
+#[cfg(feature = "std")]
+pub mod fixed_point;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod fraction;
+#[cfg(feature = "std")]
+pub mod hex_float;
+#[cfg(feature = "std")]
+pub mod html;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod latex;
+#[cfg(feature = "std")]
+pub mod literal;
+#[cfg(feature = "std")]
+pub mod markdown;
+#[cfg(feature = "std")]
+pub mod notation;
+#[cfg(feature = "std")]
+pub mod options;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod random;
+#[cfg(feature = "std")]
+pub mod range;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod xml;
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum ErrorCode {
+    BaseConversionErr,
+    TargetBaseErr,
+    InputBaseErr,
+    IoErr,
+    WidthErr,
+    OverflowErr,
+    /// Some lines of a batch failed to convert
+    PartialErr,
+    /// The input failed a check it was asked for, like `--luhn check`
+    CheckErr,
+    /// A yes or no question like `--test-bit` was answered no, which is a
+    /// result rather than a failure
+    TestFalse,
+}
+
+impl core::fmt::Debug for ErrorCode {
+    fn fmt(&self, f:&mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", match *self {
+            ErrorCode::BaseConversionErr => "Base Conversion Error",
+            ErrorCode::TargetBaseErr     => "Target Base Error",
+            ErrorCode::InputBaseErr      => "Input Base Error",
+            ErrorCode::IoErr             => "I/O Error",
+            ErrorCode::WidthErr          => "Width Error",
+            ErrorCode::OverflowErr       => "Overflow Error",
+            ErrorCode::PartialErr        => "Partial Batch Error",
+            ErrorCode::CheckErr          => "Check Failed",
+            ErrorCode::TestFalse         => "Test False",
+        })
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f:&mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", match *self {
+            ErrorCode::BaseConversionErr => "Could not convert the input number",
+            ErrorCode::TargetBaseErr     => "Invalid target base",
+            ErrorCode::InputBaseErr      => "Invalid input base or missing input number",
+            ErrorCode::IoErr             => "Could not write the output",
+            ErrorCode::WidthErr          => "Input value does not fit in the given width",
+            ErrorCode::OverflowErr       => "Input value exceeds maximum representable value (2^128 - 1)",
+            ErrorCode::PartialErr        => "Some lines of the input file failed to convert",
+            ErrorCode::CheckErr          => "The input failed the check",
+            ErrorCode::TestFalse         => "The answer to the test was no",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ErrorCode {
+    fn from(_: io::Error) -> Self {
+        ErrorCode::IoErr
+    }
+}
+
+/// Look up the base for a base_char (b, o, d, h or x), a word alias (bin,
+/// oct, dec, hex, b32 or b36) or a `baseN` name. Word aliases and `baseN`
+/// names are case-insensitive.
+#[cfg(feature = "std")]
+pub fn get_from_base(from_base: &str) -> Option<u32>
+{
+    match from_base {
+        "b" => return Some(2),
+        "o" => return Some(8),
+        "d" => return Some(10),
+        "h" | "x" => return Some(16),
+        _   => (),
+    }
+    let lower = from_base.to_ascii_lowercase();
+    match lower.as_str() {
+        "bin" => Some(2),
+        "oct" => Some(8),
+        "dec" => Some(10),
+        "hex" => Some(16),
+        "b32" => Some(32),
+        "b36" => Some(36),
+        _ => lower.strip_prefix("base")
+                  .and_then(|n| n.parse::<u32>().ok())
+                  .filter(|n| (2..=36).contains(n)),
+    }
+}
+
+/// Parse a base given as a number or any name `get_from_base` knows
+#[cfg(feature = "std")]
+pub fn parse_base(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>().ok().or_else(|| get_from_base(s)) {
+        Some(v) => Ok(v),
+        None    => Err(format!("Unknown base {}", s)),
+    }
+}
+
+/// Look up a base by name: a custom alias from `aliases`, then a base_char
+#[cfg(feature = "std")]
+pub fn resolve_base(name: &str, aliases: &HashMap<String, u32>) -> Option<u32> {
+    aliases.get(name).copied().or_else(|| get_from_base(name))
+}
+
+/// Parse a `NAME=BASE` base alias definition.
+///
+/// Names can't be numbers or shadow a built-in base_char, and the base must
+/// be between 2 and 36.
+#[cfg(feature = "std")]
+pub fn parse_base_alias(spec: &str) -> Result<(String, u32), String> {
+    let mut parts = spec.splitn(2, '=');
+    let name = parts.next().unwrap_or("").trim();
+    let base = parts.next().unwrap_or("").trim();
+    if name.is_empty() || base.is_empty() {
+        return Err(format!("Invalid base alias {}, expected NAME=BASE", spec));
+    }
+    if name.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Base alias name {} can't be a number", name));
+    }
+    if get_from_base(name).is_some() {
+        return Err(format!("Base alias {} conflicts with a built-in base", name));
+    }
+    match base.parse::<u32>() {
+        Ok(v) if (2..=36).contains(&v) => Ok((name.to_string(), v)),
+        _ => Err(format!("Invalid base {} for alias {}, base must be between 2 and 36", base, name)),
+    }
+}
+
+/// Parse `from_num` in `from_base`, ignoring any `sep_char` separators
+#[cfg(feature = "std")]
+pub fn convert_to_base_10(from_num: Option<String>, from_base: u32, sep_char: char) -> Result<u128, ErrorCode> {
+    let from_num = if let Some(num) = from_num {
+        num.replace(sep_char, "")
+    } else {
+        eprintln!("no number to convert was provided");
+        return Err(ErrorCode::InputBaseErr);
+    };
+
+    match u128::from_str_radix(&from_num, from_base) {
+        Ok(v)  => Ok(v),
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => {
+            eprintln!("{}", ErrorCode::OverflowErr);
+            Err(ErrorCode::OverflowErr)
+        },
+        Err(_e) => {
+            match from_num.chars().enumerate().find(|&(i, c)| !(c.is_digit(from_base) || i == 0 && c == '+')) {
+                Some((i, c)) => eprintln!("Could not convert {} from base {}, {} at column {} is not a digit",
+                                          from_num, from_base, c, i + 1),
+                None => eprintln!("Could not convert {} from base {}", from_num, from_base),
+            }
+            Err(ErrorCode::BaseConversionErr)
+        },
+    }
+}
+
+/// Every base from 2 to 36 that can read `s`, with the value it reads.
+///
+/// Bases where the value doesn't fit in a u128 are left out, as are signs.
+#[cfg(feature = "std")]
+pub fn valid_bases_for_string(s: &str) -> Vec<(u32, u128)> {
+    find_valid_bases(s, 2, 36)
+}
+
+/// `valid_bases_for_string` for the bases from `min_base` to `max_base`,
+/// which must be within 2 to 36
+#[cfg(feature = "std")]
+pub fn find_valid_bases(s: &str, min_base: u32, max_base: u32) -> Vec<(u32, u128)> {
+    assert!(2 <= min_base && max_base <= 36, "bases to find must be from 2 to 36");
+    if s.starts_with('+') || s.starts_with('-') {
+        return Vec::new();
+    }
+    (min_base..=max_base)
+        .filter_map(|base| u128::from_str_radix(s, base).ok().map(|v| (base, v)))
+        .collect()
+}
+
+/// Split a base prefix off one line of a batch file: `0x` for hex, `0b` for
+/// binary, `0o` for octal or `N:` for base N (e.g. `7:123`). Lines without a
+/// prefix are in `default_base`. A leading `-` is kept on the digits.
+#[cfg(feature = "std")]
+pub fn split_base_prefix(line: &str, default_base: u32) -> Result<(String, u32), ErrorCode> {
+    let (sign, rest) = match line.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None       => ("", line),
+    };
+    let (digits, base) = match rest.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => (&rest[2..], 16),
+        Some("0b") => (&rest[2..], 2),
+        Some("0o") => (&rest[2..], 8),
+        _ => match rest.find(':') {
+            Some(colon) => match rest[..colon].parse::<u32>() {
+                Ok(base) if (2..=36).contains(&base) => (&rest[colon + 1..], base),
+                _ => {
+                    eprintln!("Invalid base prefix {}: in {}, expected a base from 2 to 36", &rest[..colon], line);
+                    return Err(ErrorCode::InputBaseErr);
+                },
+            },
+            None => (rest, default_base),
+        },
+    };
+    Ok((format!("{}{}", sign, digits), base))
+}
+
+/// Read one line of a batch file, returning its value and the base it was
+/// written in. See `split_base_prefix` for the prefixes.
+#[cfg(feature = "std")]
+pub fn parse_line_with_prefix(line: &str, default_base: u32) -> Result<(u128, u32), ErrorCode> {
+    let (digits, base) = split_base_prefix(line.trim(), default_base)?;
+    Ok((convert_to_base_10(Some(digits), base, '_')?, base))
+}
+
+/// Format `num` in `base`
+#[cfg(feature = "std")]
+pub fn as_string_base(num: &u128, base: u32) -> Result<String, String>
+{
+    if !(2..=36).contains(&base) {
+        Err(String::from("Invalid Base.  Base must be between 2 and 36 inclusive"))
+    }
+    else {
+        let mut str_num = String::new();
+
+        let mut tmp: u128 = *num;
+        let mut count: u32 = 0;
+
+        if tmp == 0 {
+            return Ok(String::from("0"));
+        }
+
+        while tmp > 0 {
+            let radix_mask: u128 = (base as u128).pow(count);
+            let digit: u8 = match ((tmp / radix_mask) % u128::from(base)).try_into() {
+                Ok(v)  => v,
+                Err(_) => {
+                    return Err(format!("Error while trying to convert to radix {}", base));
+                },
+            };
+
+            let ch = if digit >= 10 {
+                (b'A' + (digit-10)) as char
+            }
+            else {
+                (b'0' + digit) as char
+            };
+
+            str_num = ch.to_string() + str_num.as_str();
+
+            count += 1;
+            tmp -= u128::from(digit) * radix_mask;
+        }
+
+        Ok(str_num)
+    }
+}
+
+/// The longest unary string `as_string_base_unary` will make
+#[cfg(feature = "std")]
+pub const UNARY_MAX: u128 = 1000;
+
+/// Format `num` in unary (base 1): `num` copies of `1`, so 0 is empty.
+/// Values past `UNARY_MAX` are an error.
+#[cfg(feature = "std")]
+pub fn as_string_base_unary(num: u128) -> Result<String, String> {
+    if num > UNARY_MAX {
+        return Err(format!("{} is too long for unary, the most is {}", num, UNARY_MAX));
+    }
+    Ok("1".repeat(num as usize))
+}
+
+
+/// One input converted to several bases: what was read and in which base,
+/// its value, and its digits in each target base in order. `--json` writes
+/// one of these for each number.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionResult {
+    pub input:     String,
+    pub from_base: u32,
+    pub decimal:   u128,
+    /// `(base, digits)` for each target base
+    pub results:   Vec<(u32, String)>,
+}
+
+#[cfg(feature = "std")]
+impl ConversionResult {
+    /// Convert `input` from `from_base` to each of `to_bases`. `_`
+    /// separators in `input` are ignored.
+    pub fn new(input: &str, from_base: u32, to_bases: &[u32]) -> Result<Self, ErrorCode> {
+        let decimal = convert_to_base_10(Some(input.to_string()), from_base, '_')?;
+        let results = to_bases.iter()
+            .map(|&base| as_string_base(&decimal, base).map(|digits| (base, digits)).map_err(|_| ErrorCode::TargetBaseErr))
+            .collect::<Result<_, _>>()?;
+        Ok(ConversionResult { input: input.to_string(), from_base, decimal, results })
+    }
+
+    /// The digits in each target base, without the bases
+    pub fn digits(&self) -> Vec<String> {
+        self.results.iter().map(|(_, digits)| digits.clone()).collect()
+    }
+}
+
+/// Convert `input` from `from_base` to `to_base` in one call, for embedding
+/// the conversion elsewhere. `_` separators in `input` are ignored.
+#[cfg(feature = "std")]
+pub fn convert(input: &str, from_base: u32, to_base: u32) -> Result<String, ErrorCode> {
+    let mut conversion = ConversionResult::new(input, from_base, &[to_base])?;
+    Ok(conversion.results.remove(0).1)
+}
+
+/// `convert` each of `inputs` to each of `to_bases`, a row of results per
+/// input. Stops at the first input that fails.
+#[cfg(feature = "std")]
+pub fn convert_batch<S: AsRef<str>>(inputs: &[S], from_base: u32, to_bases: &[u32]) -> Result<Vec<Vec<String>>, ErrorCode> {
+    inputs.iter()
+        .map(|input| ConversionResult::new(input.as_ref(), from_base, to_bases).map(|conversion| conversion.digits()))
+        .collect()
+}
+
+/// How many digits `n` has in `base`, for sizing a buffer, counted by
+/// division so it is exact. 0 has one digit.
+pub fn digit_count(n: u128, base: u32) -> Result<usize, ErrorCode> {
+    if !(2..=36).contains(&base) {
+        return Err(ErrorCode::TargetBaseErr);
+    }
+    let mut n = n / base as u128;
+    let mut count = 1;
+    while n > 0 {
+        n /= base as u128;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The digits of `n` in `base` as numbers, most significant first. Works for
+/// any base from 2 up, including bases with no digit symbols. 0 is `[0]`.
+#[cfg(feature = "std")]
+pub fn to_digits(n: u128, base: u32) -> Result<Vec<u32>, ErrorCode> {
+    if base < 2 {
+        return Err(ErrorCode::TargetBaseErr);
+    }
+    let mut digits = Vec::new();
+    let mut tmp = n;
+    loop {
+        digits.push((tmp % u128::from(base)) as u32);
+        tmp /= u128::from(base);
+        if tmp == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    Ok(digits)
+}
+
+/// Put `sep_char` between every `sep_length` digits, counting from the
+/// right. A `sep_length` of 0 leaves the digits as they are.
+#[cfg(feature = "std")]
+pub fn insert_separators(digits: &str, sep_char: char, sep_length: u32) -> String {
+    if sep_length == 0 {
+        return digits.to_string();
+    }
+    let sep_length = sep_length as usize;
+    let chars: Vec<char> = digits.chars().collect();
+    let mut separated = String::with_capacity(digits.len() + digits.len() / sep_length);
+    for (i, ch) in chars.iter().enumerate() {
+        if i > 0 && (chars.len() - i).is_multiple_of(sep_length) {
+            separated.push(sep_char);
+        }
+        separated.push(*ch);
+    }
+    separated
+}
+
+/// Parse `--group-by` groupings like `2:8,10:3,16:4`, a separator every
+/// LENGTH digits for each BASE
+#[cfg(feature = "std")]
+pub fn parse_group_by(spec: &str) -> Result<HashMap<u32, u32>, String> {
+    let mut overrides = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts = entry.splitn(2, ':');
+        let base = parts.next().unwrap_or("").trim();
+        let length = parts.next().unwrap_or("").trim();
+        let base = match base.parse::<u32>() {
+            Ok(v) if (2..=36).contains(&v) => v,
+            _ => return Err(format!("Invalid base {} in {}, base must be between 2 and 36", base, entry)),
+        };
+        let length = length.parse::<u32>()
+            .map_err(|_| format!("Invalid group length in {}, expected BASE:LENGTH", entry))?;
+        overrides.insert(base, length);
+    }
+    Ok(overrides)
+}
+
+/// How many digits go between separators in `base`: its `--group-by`
+/// override, or `default`
+#[cfg(feature = "std")]
+pub fn get_sep_length_for_base(base: u32, overrides: &HashMap<u32, u32>, default: u32) -> u32 {
+    overrides.get(&base).copied().unwrap_or(default)
+}
+
+/// The first `n` digits of `s` followed by `...` and how many were left
+/// out, e.g. `1111... (+124 digits)`. Separators (ASCII punctuation or
+/// whitespace) are kept between the digits but not counted. `s` is returned
+/// as-is when it has no more than `n` digits.
+#[cfg(feature = "std")]
+pub fn truncate_repr(s: &str, n: usize) -> String {
+    let is_digit = |c: &char| !c.is_ascii_punctuation() && !c.is_ascii_whitespace();
+    let total = s.chars().filter(is_digit).count();
+    if total <= n {
+        return s.to_string();
+    }
+    let mut kept = String::new();
+    let mut count = 0;
+    for c in s.chars() {
+        if is_digit(&c) {
+            if count == n {
+                break;
+            }
+            count += 1;
+        }
+        kept.push(c);
+    }
+    let hidden = total - n;
+    format!("{}... (+{} digit{})", kept.trim_end_matches(|c| !is_digit(&c)), hidden,
+            if hidden == 1 { "" } else { "s" })
+}
+
+/// Convert `start` back and forth between `from` and `to` `n` times,
+/// returning the value after each conversion. Odd steps are in `to`, even
+/// steps back in `from`.
+///
+/// The chain stops early if a step fails to convert.
+#[cfg(feature = "std")]
+pub fn repeat_conversion(start: &str, from: u32, to: u32, n: u32, sep_char: char) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current = start.to_string();
+    for step in 0..n {
+        let (src, dst) = if step.is_multiple_of(2) { (from, to) } else { (to, from) };
+        let next = match convert_to_base_10(Some(current), src, sep_char) {
+            Ok(num) => as_string_base(&num, dst),
+            Err(_)  => break,
+        };
+        current = match next {
+            Ok(v)  => v,
+            Err(_) => break,
+        };
+        values.push(current.clone());
+    }
+    values
+}
+
+/// Check a chain from `repeat_conversion`. Every value should match the one
+/// two steps earlier, and with `from == to` every value should match
+/// `start`. Returns the first step that doesn't, with the expected and
+/// actual values.
+#[cfg(feature = "std")]
+pub fn repeat_mismatch(start: &str, from: u32, to: u32, values: &[String]) -> Option<(usize, String, String)> {
+    for (i, value) in values.iter().enumerate() {
+        let expected = if i == 0 {
+            if from != to {
+                continue;
+            }
+            start
+        } else if i == 1 {
+            start
+        } else {
+            values[i - 2].as_str()
+        };
+        if value != expected {
+            return Some((i + 1, expected.to_string(), value.clone()));
+        }
+    }
+    None
+}
+
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bin() {
+        assert_eq!(as_string_base(&4,   2).unwrap(), "100");
+        assert_eq!(as_string_base(&12,  2).unwrap(), "1100");
+        assert_eq!(as_string_base(&187, 2).unwrap(), "10111011");
+        assert_eq!(as_string_base(&69,  2).unwrap(), "1000101");
+    }
+
+    #[test]
+    fn test_oct() {
+        assert_eq!(as_string_base(&4,   8).unwrap(), "4");
+        assert_eq!(as_string_base(&12,  8).unwrap(), "14");
+        assert_eq!(as_string_base(&187, 8).unwrap(), "273");
+        assert_eq!(as_string_base(&69,  8).unwrap(), "105");
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(as_string_base(&4,   16).unwrap(), "4");
+        assert_eq!(as_string_base(&12,  16).unwrap(), "C");
+        assert_eq!(as_string_base(&187, 16).unwrap(), "BB");
+        assert_eq!(as_string_base(&69,  16).unwrap(), "45");
+    }
+
+    #[test]
+    fn test_zero() {
+        assert_eq!(as_string_base(&0, 2).unwrap(),  "0");
+        assert_eq!(as_string_base(&0, 16).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_base_36() {
+        assert_eq!(as_string_base(&35,   36).unwrap(), "Z");
+        assert_eq!(as_string_base(&1295, 36).unwrap(), "ZZ");
+        assert!(as_string_base(&1, 37).is_err());
+    }
+
+    #[test]
+    fn test_unary() {
+        assert_eq!(as_string_base_unary(0), Ok(String::new()));
+        assert_eq!(as_string_base_unary(1), Ok(String::from("1")));
+        assert_eq!(as_string_base_unary(5), Ok(String::from("11111")));
+        assert_eq!(as_string_base_unary(1000).map(|s| s.len()), Ok(1000));
+        assert!(as_string_base_unary(1001).is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert("FF", 16, 2), Ok("11111111".to_string()));
+        assert_eq!(convert("1111_1111", 2, 10), Ok("255".to_string()));
+        assert_eq!(convert("GG", 16, 2), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(convert("FF", 16, 37), Err(ErrorCode::TargetBaseErr));
+    }
+
+    #[test]
+    fn test_conversion_result() {
+        let conversion = ConversionResult::new("F_F", 16, &[2, 10]).unwrap();
+        assert_eq!(conversion, ConversionResult {
+            input:     "F_F".to_string(),
+            from_base: 16,
+            decimal:   255,
+            results:   vec![(2, "11111111".to_string()), (10, "255".to_string())],
+        });
+        assert_eq!(conversion.digits(), vec!["11111111".to_string(), "255".to_string()]);
+        assert_eq!(ConversionResult::new("FF", 16, &[]).unwrap().results, vec![]);
+        assert_eq!(ConversionResult::new("GG", 16, &[2]), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(ConversionResult::new("FF", 16, &[2, 37]), Err(ErrorCode::TargetBaseErr));
+    }
+
+    #[test]
+    fn test_convert_batch() {
+        assert_eq!(convert_batch(&["10", "255"], 10, &[2, 16]),
+                   Ok(vec![vec!["1010".to_string(), "A".to_string()], vec!["11111111".to_string(), "FF".to_string()]]));
+        assert_eq!(convert_batch::<&str>(&[], 10, &[2]), Ok(vec![]));
+        assert_eq!(convert_batch(&["10", "x"], 10, &[2]), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_to_digits() {
+        assert_eq!(to_digits(0, 10), Ok(vec![0]));
+        assert_eq!(to_digits(7, 60), Ok(vec![7]));
+        assert_eq!(to_digits(113_097, 60), Ok(vec![31, 24, 57]));
+        assert_eq!(to_digits(255, 16), Ok(vec![15, 15]));
+        assert_eq!(to_digits(1 << 64, 65536), Ok(vec![1, 0, 0, 0, 0]));
+        assert_eq!(to_digits(u128::MAX, 2).map(|d| d.len()), Ok(128));
+        assert_eq!(to_digits(1, 1), Err(ErrorCode::TargetBaseErr));
+    }
+
+    #[test]
+    fn test_digit_count() {
+        let values = [0, 1, 255, 65535, u64::MAX as u128];
+        let expected: [(u32, [usize; 5]); 4] = [
+            (2,  [1, 1, 8, 16, 64]),
+            (8,  [1, 1, 3, 6, 22]),
+            (10, [1, 1, 3, 5, 20]),
+            (16, [1, 1, 2, 4, 16]),
+        ];
+        for (base, counts) in &expected {
+            for (value, count) in values.iter().zip(counts) {
+                assert_eq!(digit_count(*value, *base), Ok(*count), "{} in base {}", value, base);
+            }
+        }
+        assert_eq!(digit_count(u128::MAX, 2), Ok(128));
+        assert_eq!(digit_count(35, 36), Ok(1));
+        assert_eq!(digit_count(1, 37), Err(ErrorCode::TargetBaseErr));
+        assert_eq!(digit_count(1, 1), Err(ErrorCode::TargetBaseErr));
+    }
+
+    #[test]
+    fn test_convert_to_base_10() {
+        assert_eq!(convert_to_base_10(Some("10111011".to_owned()), 2, '_'), Ok(187));
+        assert_eq!(convert_to_base_10(Some("273".to_owned()), 8, '_'), Ok(187));
+        assert_eq!(convert_to_base_10(Some("187".to_owned()), 10, '_'), Ok(187));
+        assert_eq!(convert_to_base_10(Some("BB".to_owned()), 16, '_'), Ok(187));
+        assert_eq!(convert_to_base_10(None, 10, '_'), Err(ErrorCode::InputBaseErr));
+    }
+
+    #[test]
+    fn test_convert_to_base_10_overflow() {
+        let forty_digits = "1234567890123456789012345678901234567890".to_owned();
+        assert_eq!(convert_to_base_10(Some(forty_digits), 10, '_'), Err(ErrorCode::OverflowErr));
+        assert_eq!(convert_to_base_10(Some(u128::MAX.to_string()), 10, '_'), Ok(u128::MAX));
+        assert_eq!(convert_to_base_10(Some("1".repeat(129)), 2, '_'), Err(ErrorCode::OverflowErr));
+        assert_eq!(convert_to_base_10(Some("12G".to_owned()), 16, '_'), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_split_base_prefix() {
+        assert_eq!(split_base_prefix("0x1F", 10), Ok(("1F".to_owned(), 16)));
+        assert_eq!(split_base_prefix("0B101", 10), Ok(("101".to_owned(), 2)));
+        assert_eq!(split_base_prefix("0o17", 10), Ok(("17".to_owned(), 8)));
+        assert_eq!(split_base_prefix("7:123", 10), Ok(("123".to_owned(), 7)));
+        assert_eq!(split_base_prefix("-0xFF", 10), Ok(("-FF".to_owned(), 16)));
+        assert_eq!(split_base_prefix("123", 10), Ok(("123".to_owned(), 10)));
+        assert_eq!(split_base_prefix("123", 16), Ok(("123".to_owned(), 16)));
+        assert_eq!(split_base_prefix("37:1", 10), Err(ErrorCode::InputBaseErr));
+        assert_eq!(split_base_prefix("x:1", 10), Err(ErrorCode::InputBaseErr));
+    }
+
+    #[test]
+    fn test_parse_line_with_prefix() {
+        assert_eq!(parse_line_with_prefix("0xBB", 10), Ok((187, 16)));
+        assert_eq!(parse_line_with_prefix("0b1011_1011", 10), Ok((187, 2)));
+        assert_eq!(parse_line_with_prefix("0o273", 10), Ok((187, 8)));
+        assert_eq!(parse_line_with_prefix("7:355", 10), Ok((187, 7)));
+        assert_eq!(parse_line_with_prefix(" 187 ", 10), Ok((187, 10)));
+        assert_eq!(parse_line_with_prefix("BB", 16), Ok((187, 16)));
+        assert_eq!(parse_line_with_prefix("0x", 10), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_valid_bases_for_string() {
+        let bases = valid_bases_for_string("10");
+        assert_eq!(bases.len(), 35);
+        assert_eq!(bases[0], (2, 2));
+        assert_eq!(bases[34], (36, 36));
+        let bases = valid_bases_for_string("FF");
+        assert_eq!(bases.first(), Some(&(16, 255)));
+        assert_eq!(bases.len(), 21);
+        assert_eq!(valid_bases_for_string("Z"), vec![(36, 35)]);
+        assert_eq!(valid_bases_for_string("z"), vec![(36, 35)]);
+        assert_eq!(valid_bases_for_string("-1"), vec![]);
+    }
+
+    #[test]
+    fn test_find_valid_bases() {
+        assert_eq!(find_valid_bases("10", 2, 36).len(), 35);
+        assert_eq!(find_valid_bases("10", 8, 10), vec![(8, 8), (9, 9), (10, 10)]);
+        assert_eq!(find_valid_bases("ff", 2, 16), vec![(16, 255)]);
+        assert_eq!(find_valid_bases("ff", 2, 36).len(), 21);
+        assert_eq!(find_valid_bases("zz", 2, 36), vec![(36, 1295)]);
+        assert_eq!(find_valid_bases("zz", 2, 35), vec![]);
+        assert_eq!(find_valid_bases("cafebabe", 2, 16), vec![(16, 3_405_691_582)]);
+        assert_eq!(find_valid_bases("cafebabe", 2, 36).first(), Some(&(16, 3_405_691_582)));
+        assert_eq!(find_valid_bases("", 2, 36), vec![]);
+        assert_eq!(valid_bases_for_string(""), vec![]);
+        assert_eq!(valid_bases_for_string("a.b"), vec![]);
+    }
+
+    #[test]
+    fn test_insert_separators() {
+        assert_eq!(insert_separators("10111011", '_', 4), "1011_1011");
+        assert_eq!(insert_separators("111011", '_', 4), "11_1011");
+        assert_eq!(insert_separators("1000000", ',', 3), "1,000,000");
+        assert_eq!(insert_separators("1011", '_', 4), "1011");
+        assert_eq!(insert_separators("10111011", '_', 0), "10111011");
+        assert_eq!(insert_separators("", '_', 1), "");
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        let overrides = parse_group_by("2:8,10:3,16:4").unwrap();
+        assert_eq!(overrides.len(), 3);
+        assert_eq!(overrides[&10], 3);
+        assert_eq!(parse_group_by(" 2 : 8 ,").unwrap()[&2], 8);
+        assert!(parse_group_by("1:3").is_err());
+        assert!(parse_group_by("10").is_err());
+        assert!(parse_group_by("10:x").is_err());
+    }
+
+    #[test]
+    fn test_get_sep_length_for_base() {
+        let overrides = parse_group_by("2:8,10:3").unwrap();
+        assert_eq!(get_sep_length_for_base(10, &overrides, 4), 3);
+        assert_eq!(insert_separators("1000000", ',', get_sep_length_for_base(10, &overrides, 4)), "1,000,000");
+        assert_eq!(get_sep_length_for_base(2, &overrides, 4), 8);
+        assert_eq!(insert_separators("1011101110111011", '_', get_sep_length_for_base(2, &overrides, 4)),
+                   "10111011_10111011");
+        assert_eq!(get_sep_length_for_base(16, &overrides, 4), 4);
+        assert_eq!(get_sep_length_for_base(16, &HashMap::new(), 2), 2);
+    }
+
+    #[test]
+    fn test_truncate_repr() {
+        let digits = "1".repeat(128);
+        assert_eq!(truncate_repr(&digits, 4), "1111... (+124 digits)");
+        assert_eq!(truncate_repr(&digits, 127), "1".repeat(127) + "... (+1 digit)");
+        assert_eq!(truncate_repr(&digits, 128), digits);
+        assert_eq!(truncate_repr("FF", 10), "FF");
+        assert_eq!(truncate_repr("DEADBEEF", 1), "D... (+7 digits)");
+        assert_eq!(truncate_repr("DEADBEEF", 0), "... (+8 digits)");
+        assert_eq!(truncate_repr("1010_1010_1010", 6), "1010_10... (+6 digits)");
+        assert_eq!(truncate_repr("1010_1010_1010", 4), "1010... (+8 digits)");
+        assert_eq!(truncate_repr("1,000,000", 4), "1,000... (+3 digits)");
+    }
+
+    #[test]
+    fn test_repeat_conversion() {
+        assert_eq!(repeat_conversion("BB", 16, 16, 3, '_'), vec!["BB", "BB", "BB"]);
+        assert_eq!(repeat_conversion("187", 10, 2, 4, '_'), vec!["10111011", "187", "10111011", "187"]);
+        assert_eq!(repeat_conversion("XYZ", 10, 2, 4, '_'), Vec::<String>::new());
+
+        let values = repeat_conversion("187", 10, 16, 5, '_');
+        assert_eq!(repeat_mismatch("187", 10, 16, &values), None);
+        let values = repeat_conversion("BB", 16, 16, 3, '_');
+        assert_eq!(repeat_mismatch("BB", 16, 16, &values), None);
+    }
+
+    #[test]
+    fn test_repeat_detects_changes() {
+        // Lowercase digits come back uppercase
+        let values = repeat_conversion("bb", 16, 16, 2, '_');
+        assert_eq!(repeat_mismatch("bb", 16, 16, &values), Some((1, "bb".to_owned(), "BB".to_owned())));
+
+        // Leading zeros and separators are dropped on the way back
+        let values = repeat_conversion("00FF", 16, 2, 2, '_');
+        assert_eq!(repeat_mismatch("00FF", 16, 2, &values), Some((2, "00FF".to_owned(), "FF".to_owned())));
+        let values = repeat_conversion("1_000", 10, 16, 2, '_');
+        assert_eq!(repeat_mismatch("1_000", 10, 16, &values), Some((2, "1_000".to_owned(), "1000".to_owned())));
+    }
+
+    #[test]
+    fn test_get_from_base() {
+        assert_eq!(get_from_base("b"), Some(2));
+        assert_eq!(get_from_base("h"), Some(16));
+        for (name, base) in &[("bin", 2), ("oct", 8), ("dec", 10), ("hex", 16), ("b32", 32), ("b36", 36),
+                              ("base2", 2), ("base10", 10), ("base36", 36)] {
+            assert_eq!(get_from_base(name), Some(*base));
+            assert_eq!(get_from_base(&name.to_uppercase()), Some(*base));
+        }
+        assert_eq!(get_from_base("base1"), None);
+        assert_eq!(get_from_base("base37"), None);
+        assert_eq!(get_from_base("base"), None);
+        assert_eq!(get_from_base("hexadecimal"), None);
+        assert_eq!(parse_base("12"), Ok(12));
+        assert_eq!(parse_base("HEX"), Ok(16));
+        assert!(parse_base("nope").is_err());
+    }
+
+    #[test]
+    fn test_base_alias() {
+        let mut aliases = HashMap::new();
+        let (name, base) = parse_base_alias("phone=36").unwrap();
+        aliases.insert(name, base);
+        assert_eq!(resolve_base("phone", &aliases), Some(36));
+        assert_eq!(resolve_base("h", &aliases), Some(16));
+        assert_eq!(resolve_base("nope", &aliases), None);
+
+        assert!(parse_base_alias("h=12").is_err());
+        assert!(parse_base_alias("x=16").is_err());
+        assert!(parse_base_alias("12=12").is_err());
+        assert!(parse_base_alias("phone").is_err());
+        assert!(parse_base_alias("phone=1").is_err());
+        assert!(parse_base_alias("phone=37").is_err());
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Line editing for the `--interactive` prompt, with the interactive
+//! feature.
+//!
+//! The terminal is put in raw mode while a line is read and the keys are
+//! handled here, readline style: arrows and Home/End move, Ctrl-A/E jump to
+//! the ends, Ctrl-W deletes a word, Up/Down step through the history, Ctrl-C
+//! drops the line and Ctrl-D on an empty line exits.
+
+use std::{io::{self, Read, Write}, str};
+use crate::repl::{History, ReadLine};
+
+/// The line being edited, as chars so the cursor never splits one
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Replace the line with `text`, with the cursor at the end
+    pub fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Delete the char before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    /// Delete the char under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    /// Delete back to the start of the word before the cursor, as Ctrl-W
+    pub fn delete_word(&mut self) {
+        let before = &self.chars[..self.cursor];
+        let word_end = before.iter().rposition(|c| !c.is_whitespace()).map_or(0, |i| i + 1);
+        let word_start = before[..word_end].iter().rposition(|c| c.is_whitespace()).map_or(0, |i| i + 1);
+        self.chars.drain(word_start..self.cursor);
+        self.cursor = word_start;
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+}
+
+/// A key press, decoded from the bytes the terminal sends
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    /// Ctrl-W
+    DeleteWord,
+    /// Ctrl-C
+    Cancel,
+    /// Ctrl-D
+    Eof,
+    /// Anything else, e.g. function keys
+    Ignored,
+}
+
+/// The first key in `bytes` and how many bytes it took, or `None` if
+/// `bytes` ends partway through one
+pub fn decode(bytes: &[u8]) -> Option<(Key, usize)> {
+    let key = match *bytes.first()? {
+        b'\r' | b'\n' => Key::Enter,
+        0x7F | 0x08   => Key::Backspace,
+        0x01          => Key::Home,
+        0x05          => Key::End,
+        0x02          => Key::Left,
+        0x06          => Key::Right,
+        0x10          => Key::Up,
+        0x0E          => Key::Down,
+        0x17          => Key::DeleteWord,
+        0x03          => Key::Cancel,
+        0x04          => Key::Eof,
+        0x1B          => return decode_escape(bytes),
+        0x00..=0x1F   => Key::Ignored,
+        first => {
+            let len = match first {
+                0x00..=0x7F => 1,
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _           => return Some((Key::Ignored, 1)),
+            };
+            let c = str::from_utf8(bytes.get(..len)?).ok().and_then(|s| s.chars().next());
+            return Some(c.map_or((Key::Ignored, 1), |c| (Key::Char(c), len)));
+        },
+    };
+    Some((key, 1))
+}
+
+/// A `CSI` or `SS3` sequence, e.g. `ESC [ A` for Up or `ESC [ 3 ~` for Delete
+fn decode_escape(bytes: &[u8]) -> Option<(Key, usize)> {
+    match bytes.get(1)? {
+        b'[' | b'O' => (),
+        _           => return Some((Key::Ignored, 1)),
+    }
+    let end = bytes.get(2..)?.iter().position(|b| (0x40..=0x7E).contains(b))? + 2;
+    let key = match &bytes[2..=end] {
+        b"A"                  => Key::Up,
+        b"B"                  => Key::Down,
+        b"C"                  => Key::Right,
+        b"D"                  => Key::Left,
+        b"H" | b"1~" | b"7~"  => Key::Home,
+        b"F" | b"4~" | b"8~"  => Key::End,
+        b"3~"                 => Key::Delete,
+        _                     => Key::Ignored,
+    };
+    Some((key, end + 1))
+}
+
+/// How a key ended the line
+#[derive(Debug, PartialEq)]
+pub enum Done {
+    Submit,
+    Cancel,
+    Eof,
+}
+
+/// Where Up and Down have got to in the history
+#[derive(Debug, Default)]
+pub struct HistoryPosition {
+    /// How many entries back from the latest, 0 for the line being typed
+    back: usize,
+    /// The line being typed, kept while looking at the history
+    typed: String,
+}
+
+/// Apply `key` to `line`, returning how the line ended if it did
+pub fn edit(line: &mut LineBuffer, key: Key, history: &History, position: &mut HistoryPosition) -> Option<Done> {
+    match key {
+        Key::Char(c)                    => line.insert(c),
+        Key::Enter                      => return Some(Done::Submit),
+        Key::Cancel                     => return Some(Done::Cancel),
+        Key::Eof if line.is_empty()     => return Some(Done::Eof),
+        Key::Eof | Key::Delete          => line.delete(),
+        Key::Backspace                  => line.backspace(),
+        Key::DeleteWord                 => line.delete_word(),
+        Key::Left                       => line.left(),
+        Key::Right                      => line.right(),
+        Key::Home                       => line.home(),
+        Key::End                        => line.end(),
+        Key::Up => if let Some(entry) = history.latest(position.back + 1) {
+            if position.back == 0 {
+                position.typed = line.text();
+            }
+            position.back += 1;
+            line.set(entry);
+        },
+        Key::Down => match position.back {
+            0 => (),
+            1 => {
+                position.back = 0;
+                line.set(&position.typed);
+            },
+            _ => {
+                position.back -= 1;
+                line.set(history.latest(position.back).unwrap_or_default());
+            },
+        },
+        Key::Ignored => (),
+    }
+    None
+}
+
+/// The terminal settings from before raw mode, put back when dropped
+struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        raw.c_iflag &= !(libc::ICRNL | libc::IXON | libc::BRKINT | libc::INPCK | libc::ISTRIP);
+        raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG | libc::IEXTEN);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &self.original);
+        }
+    }
+}
+
+/// Reads lines from the terminal with editing
+pub struct LineEditor {
+    prompt: &'static str,
+    /// Bytes read that aren't a whole key yet
+    pending: Vec<u8>,
+}
+
+impl LineEditor {
+    pub fn new(prompt: &'static str) -> Self {
+        LineEditor { prompt, pending: Vec::new() }
+    }
+
+    /// The next key press, or `None` at the end of the input
+    fn next_key(&mut self) -> io::Result<Option<Key>> {
+        loop {
+            if let Some((key, len)) = decode(&self.pending) {
+                self.pending.drain(..len);
+                return Ok(Some(key));
+            }
+            let mut buf = [0; 64];
+            match io::stdin().read(&mut buf)? {
+                0 => return Ok(None),
+                n => self.pending.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    fn redraw(&self, out: &mut dyn Write, line: &LineBuffer) -> io::Result<()> {
+        write!(out, "\r{}{}\x1b[K", self.prompt, line.text())?;
+        let after = line.chars.len() - line.cursor;
+        if after > 0 {
+            write!(out, "\x1b[{}D", after)?;
+        }
+        out.flush()
+    }
+}
+
+impl ReadLine for LineEditor {
+    fn read_line(&mut self, history: &History) -> io::Result<Option<String>> {
+        let _raw = RawMode::enable()?;
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let mut line = LineBuffer::default();
+        let mut position = HistoryPosition::default();
+        self.redraw(&mut out, &line)?;
+        loop {
+            let key = match self.next_key()? {
+                Some(v) => v,
+                None    => return Ok(None),
+            };
+            match edit(&mut line, key, history, &mut position) {
+                Some(Done::Submit) => {
+                    write!(out, "\r\n")?;
+                    return Ok(Some(line.text()));
+                },
+                Some(Done::Cancel) => {
+                    write!(out, "^C\r\n")?;
+                    return Ok(Some(String::new()));
+                },
+                Some(Done::Eof) => {
+                    write!(out, "\r\n")?;
+                    return Ok(None);
+                },
+                None => self.redraw(&mut out, &line)?,
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(keys: &[u8]) -> (LineBuffer, Option<Done>) {
+        let mut line = LineBuffer::default();
+        let mut position = HistoryPosition::default();
+        let history = History::new(10);
+        let mut keys = keys;
+        while let Some((key, len)) = decode(keys) {
+            keys = &keys[len..];
+            if let Some(done) = edit(&mut line, key, &history, &mut position) {
+                return (line, Some(done));
+            }
+        }
+        (line, None)
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"a"), Some((Key::Char('a'), 1)));
+        assert_eq!(decode("é".as_bytes()), Some((Key::Char('é'), 2)));
+        assert_eq!(decode(&"€".as_bytes()[..2]), None);
+        assert_eq!(decode(b"\x1b[A"), Some((Key::Up, 3)));
+        assert_eq!(decode(b"\x1bOH"), Some((Key::Home, 3)));
+        assert_eq!(decode(b"\x1b[3~x"), Some((Key::Delete, 4)));
+        assert_eq!(decode(b"\x1b[3"), None);
+        assert_eq!(decode(b"\x1b[15~"), Some((Key::Ignored, 5)));
+        assert_eq!(decode(b"\x17"), Some((Key::DeleteWord, 1)));
+        assert_eq!(decode(b"\r"), Some((Key::Enter, 1)));
+        assert_eq!(decode(b""), None);
+    }
+
+    #[test]
+    fn test_editing() {
+        let (line, done) = typed(b"h DEADBEEF\x01\x1b[C\x1b[C\x7f\x05 16\r");
+        assert_eq!((line.text(), done), ("hDEADBEEF 16".to_string(), Some(Done::Submit)));
+        // Backspace takes a whole char, however many bytes it is
+        assert_eq!(typed("d 1€\x7f2".as_bytes()).0.text(), "d 12");
+        assert_eq!(typed(b"h FF  2 \x17").0.text(), "h FF  ");
+        assert_eq!(typed(b"h FF\x1b[D\x1b[D\x17").0.text(), "FF");
+        assert_eq!(typed(b"h F\x1b[D\x04").0.text(), "h ");
+        assert_eq!(typed(b"h FF\x03").1, Some(Done::Cancel));
+        assert_eq!(typed(b"\x04").1, Some(Done::Eof));
+    }
+
+    #[test]
+    fn test_history_keys() {
+        let mut history = History::new(10);
+        history.add("b 101");
+        history.add("h FF");
+        let mut line = LineBuffer::default();
+        let mut position = HistoryPosition::default();
+        line.set("d 1");
+        let mut press = |key| {
+            edit(&mut line, key, &history, &mut position);
+            line.text()
+        };
+        assert_eq!(press(Key::Up), "h FF");
+        assert_eq!(press(Key::Up), "b 101");
+        assert_eq!(press(Key::Up), "b 101");
+        assert_eq!(press(Key::Down), "h FF");
+        assert_eq!(press(Key::Down), "d 1");
+        assert_eq!(press(Key::Down), "d 1");
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! How lines of output end, for `-n` and `-z`.
+
+use std::io::{self, Write};
+
+/// What ends each line of output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineEnd {
+    Newline,
+    /// Newlines, except after the last line
+    NoFinalNewline,
+    /// NUL instead of newline, for `xargs -0`
+    Nul,
+}
+
+/// Rewrites the newlines written through it to `line_end`.
+///
+/// For `NoFinalNewline` a newline is held back until more output follows
+/// it, so the one after the last line is never written.
+pub struct LineEndWriter<'a> {
+    inner: &'a mut dyn Write,
+    line_end: LineEnd,
+    pending_newline: bool,
+}
+
+impl<'a> LineEndWriter<'a> {
+    pub fn new(inner: &'a mut dyn Write, line_end: LineEnd) -> Self {
+        LineEndWriter { inner, line_end, pending_newline: false }
+    }
+}
+
+impl<'a> Write for LineEndWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.line_end {
+            LineEnd::Newline => return self.inner.write(buf),
+            LineEnd::Nul => {
+                let replaced: Vec<u8> = buf.iter().map(|&b| if b == b'\n' { b'\0' } else { b }).collect();
+                self.inner.write_all(&replaced)?;
+            },
+            LineEnd::NoFinalNewline => {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+                if self.pending_newline {
+                    self.inner.write_all(b"\n")?;
+                }
+                self.pending_newline = buf.ends_with(b"\n");
+                let body = if self.pending_newline { &buf[..buf.len() - 1] } else { buf };
+                self.inner.write_all(body)?;
+            },
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_lines(line_end: LineEnd, lines: &[&str]) -> Vec<u8> {
+        let mut out = Vec::new();
+        {
+            let mut writer = LineEndWriter::new(&mut out, line_end);
+            for line in lines {
+                write!(writer, "{}", line).unwrap();
+                writeln!(writer).unwrap();
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_line_ends() {
+        let lines = ["Base 02: 1111_1111", "Base 16: FF"];
+        assert_eq!(write_lines(LineEnd::Newline, &lines), b"Base 02: 1111_1111\nBase 16: FF\n");
+        assert_eq!(write_lines(LineEnd::NoFinalNewline, &lines), b"Base 02: 1111_1111\nBase 16: FF");
+        assert_eq!(write_lines(LineEnd::Nul, &lines), b"Base 02: 1111_1111\0Base 16: FF\0");
+        assert_eq!(write_lines(LineEnd::NoFinalNewline, &["", ""]), b"\n");
+    }
+}
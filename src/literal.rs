@@ -0,0 +1,250 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Integer literals copied out of source code, with the type suffix that
+//! gives their width and signedness, like Rust's `0xFF_u32`.
+
+use std::fmt;
+
+/// The integer type a literal's suffix names
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntType {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl IntType {
+    /// Whether rustc would accept a literal of this type with this value.
+    /// Unsigned types can't be negative, and signed ones only reach their
+    /// minimum with a `-`.
+    pub fn fits(&self, negative: bool, magnitude: u128) -> bool {
+        let max = u128::MAX >> (128 - self.bits + self.signed as u32);
+        match (negative, self.signed) {
+            (true, false) => magnitude == 0,
+            (true, true)  => magnitude <= max + 1,
+            (false, _)    => magnitude <= max,
+        }
+    }
+}
+
+impl fmt::Display for IntType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+/// Rust's integer suffixes, `size` being 64 bits as on 64 bit targets
+const RUST_SUFFIXES: &[(&str, u32)] = &[("8", 8), ("16", 16), ("32", 32), ("64", 64), ("128", 128), ("size", 64)];
+
+/// The `<stdint.h>` macros for integer constants and the width of their type
+const C_MACROS: &[(&str, u32)] = &[
+    ("INT8_C", 8), ("INT16_C", 16), ("INT32_C", 32), ("INT64_C", 64), ("INTMAX_C", 64),
+    ("UINT8_C", 8), ("UINT16_C", 16), ("UINT32_C", 32), ("UINT64_C", 64), ("UINTMAX_C", 64),
+];
+
+/// An integer literal from source code, like `42i64`, `0xFF_u32` or
+/// `0xFFFFFFFFUL`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Literal {
+    /// The digits with a leading `-` if there was one, without the prefix,
+    /// suffix or separators
+    pub digits: String,
+    /// 16, 8 or 2 for a `0x`, `0o` or `0b` prefix (or C's leading `0`),
+    /// otherwise `None`
+    pub base: Option<u32>,
+    /// The types the literal can have, in the order the compiler tries them
+    pub types: Vec<IntType>,
+    /// Whether a `-` is applied after the type is picked, wrapping an
+    /// unsigned value as in C, instead of being part of the literal as in
+    /// Rust
+    pub wrapping_minus: bool,
+}
+
+impl Literal {
+    /// The first of `types` the value fits in, and whether it fits. A
+    /// value too large for all of them gets the last.
+    pub fn int_type(&self, negative: bool, magnitude: u128) -> (IntType, bool) {
+        let negative = negative && !self.wrapping_minus;
+        match self.types.iter().find(|t| t.fits(negative, magnitude)) {
+            Some(&t) => (t, true),
+            None     => (*self.types.last().unwrap(), false),
+        }
+    }
+}
+
+/// Split a leading `-` and a `0x`, `0o` or `0b` base prefix off `s`, and
+/// remove the `separator`s. `None` if there are no digits left.
+fn split_prefix(s: &str, separator: char, leading_zero_octal: bool) -> Option<(String, Option<u32>)> {
+    let (sign, body) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None       => ("", s),
+    };
+    let (base, digits) = match body.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => (Some(16), &body[2..]),
+        Some("0o") if !leading_zero_octal => (Some(8), &body[2..]),
+        Some("0b") => (Some(2), &body[2..]),
+        _ if leading_zero_octal && body.len() > 1 && body.starts_with('0') => (Some(8), &body[1..]),
+        _ => (None, body),
+    };
+    let digits = digits.replace(separator, "");
+    if digits.is_empty() {
+        return None;
+    }
+    Some((format!("{}{}", sign, digits), base))
+}
+
+/// Split the type suffix and any base prefix off a Rust integer literal.
+/// `None` if `s` doesn't end in one of the integer suffixes, or there are
+/// no digits in front of it.
+pub fn split_rust_literal(s: &str) -> Option<Literal> {
+    let (int_type, body) = RUST_SUFFIXES.iter()
+        .flat_map(|&(size, bits)| [("u", false), ("i", true)].iter().map(move |&(sign, signed)| (sign, size, bits, signed)))
+        .find_map(|(sign, size, bits, signed)| {
+            let body = s.strip_suffix(size)?.strip_suffix(sign)?;
+            Some((IntType { bits, signed }, body))
+        })?;
+    let (digits, base) = split_prefix(body, '_', false)?;
+    Some(Literal { digits, base, types: vec![int_type], wrapping_minus: false })
+}
+
+/// Split the `u`, `l` or `ll` suffix (in any case and order) and any base
+/// prefix off a C integer literal, or unwrap a `UINT64_C(...)` style macro.
+/// `'` separators are removed and a leading `0` is octal, as in C.
+///
+/// The types are C's for the suffix on LP64 targets, where `long` and `long
+/// long` are both 64 bits: decimal literals without `u` stay signed, others
+/// go from signed to unsigned at each width. `None` if `s` has neither a
+/// suffix nor a macro, or there are no digits.
+pub fn split_c_literal(s: &str) -> Option<Literal> {
+    for &(name, bits) in C_MACROS {
+        if let Some(inner) = s.strip_prefix(name).and_then(|r| r.strip_prefix('(')).and_then(|r| r.strip_suffix(')')) {
+            let inner = inner.trim();
+            let mut literal = split_c_literal(inner).or_else(|| {
+                split_prefix(inner, '\'', true).map(|(digits, base)| Literal { digits, base, types: Vec::new(), wrapping_minus: true })
+            })?;
+            literal.types = vec![IntType { bits, signed: !name.starts_with('U') }];
+            return Some(literal);
+        }
+    }
+
+    let suffix_len = s.chars().rev().take_while(|c| matches!(c, 'u' | 'U' | 'l' | 'L')).count();
+    let (body, suffix) = s.split_at(s.len() - suffix_len);
+    let suffix = suffix.to_ascii_lowercase();
+    let (unsigned, longs) = match suffix.as_str() {
+        "u"          => (true, 0),
+        "l"          => (false, 1),
+        "ul" | "lu"  => (true, 1),
+        "ll"         => (false, 2),
+        "ull" | "llu" => (true, 2),
+        _            => return None,
+    };
+    let (digits, base) = split_prefix(body, '\'', true)?;
+    let widths: &[u32] = if longs == 0 { &[32, 64] } else { &[64] };
+    let types = widths.iter()
+        .flat_map(|&bits| [true, false].iter().map(move |&signed| IntType { bits, signed }))
+        .filter(|t| if t.signed { !unsigned } else { unsigned || base.is_some() })
+        .collect();
+    Some(Literal { digits, base, types, wrapping_minus: true })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(digits: &str, base: Option<u32>, bits: u32, signed: bool) -> Option<Literal> {
+        Some(Literal { digits: digits.to_string(), base, types: vec![IntType { bits, signed }], wrapping_minus: false })
+    }
+
+    fn types(s: &str) -> Vec<String> {
+        split_c_literal(s).unwrap().types.iter().map(IntType::to_string).collect()
+    }
+
+    #[test]
+    fn test_split_rust_literal() {
+        assert_eq!(split_rust_literal("42i64"), literal("42", None, 64, true));
+        assert_eq!(split_rust_literal("0xFF_u32"), literal("FF", Some(16), 32, false));
+        assert_eq!(split_rust_literal("0b1010_1010u8"), literal("10101010", Some(2), 8, false));
+        assert_eq!(split_rust_literal("0o777_u16"), literal("777", Some(8), 16, false));
+        assert_eq!(split_rust_literal("-128i8"), literal("-128", None, 8, true));
+        assert_eq!(split_rust_literal("1i16"), literal("1", None, 16, true));
+        assert_eq!(split_rust_literal("1i32"), literal("1", None, 32, true));
+        assert_eq!(split_rust_literal("1u64"), literal("1", None, 64, false));
+        assert_eq!(split_rust_literal("1u128"), literal("1", None, 128, false));
+        assert_eq!(split_rust_literal("1_i128"), literal("1", None, 128, true));
+        assert_eq!(split_rust_literal("7usize"), literal("7", None, 64, false));
+        assert_eq!(split_rust_literal("-7isize"), literal("-7", None, 64, true));
+        assert_eq!(split_rust_literal("42"), None);
+        assert_eq!(split_rust_literal("u8"), None);
+        assert_eq!(split_rust_literal("0x_u8"), None);
+        assert_eq!(split_rust_literal("42u7"), None);
+        assert_eq!(split_rust_literal("42f32"), None);
+    }
+
+    #[test]
+    fn test_split_c_literal() {
+        let c_literal = |s: &str| split_c_literal(s).map(|l| (l.digits, l.base));
+        assert_eq!(c_literal("0xFFFFFFFFUL"), Some(("FFFFFFFF".to_string(), Some(16))));
+        assert_eq!(c_literal("123456789LL"), Some(("123456789".to_string(), None)));
+        assert_eq!(c_literal("0755u"), Some(("755".to_string(), Some(8))));
+        assert_eq!(c_literal("0u"), Some(("0".to_string(), None)));
+        assert_eq!(c_literal("0b1010lu"), Some(("1010".to_string(), Some(2))));
+        assert_eq!(c_literal("1'000'000uLL"), Some(("1000000".to_string(), None)));
+        assert_eq!(c_literal("UINT64_C(0x123)"), Some(("123".to_string(), Some(16))));
+        assert_eq!(c_literal("INT8_C(-5)"), Some(("-5".to_string(), None)));
+        assert_eq!(c_literal("UINT32_C(10U)"), Some(("10".to_string(), None)));
+        assert_eq!(c_literal("123"), None);
+        assert_eq!(c_literal("0xFFUZ"), None);
+        assert_eq!(c_literal("1uul"), None);
+        assert_eq!(c_literal("UL"), None);
+        assert_eq!(c_literal("UINT64_C()"), None);
+        assert_eq!(c_literal("UINT64_C(0x12"), None);
+
+        assert_eq!(types("1u"), ["u32", "u64"]);
+        assert_eq!(types("1l"), ["i64"]);
+        assert_eq!(types("0x1l"), ["i64", "u64"]);
+        assert_eq!(types("1ULL"), ["u64"]);
+        assert_eq!(types("0x1LL"), ["i64", "u64"]);
+        assert_eq!(types("UINT64_C(1)"), ["u64"]);
+        assert_eq!(types("INT16_C(1)"), ["i16"]);
+        assert_eq!(types("INTMAX_C(1)"), ["i64"]);
+    }
+
+    #[test]
+    fn test_literal_int_type() {
+        let int = |bits, signed| IntType { bits, signed };
+        // C's types grow to fit the value
+        assert_eq!(split_c_literal("0xFFFFFFFFU").unwrap().int_type(false, 0xFFFF_FFFF), (int(32, false), true));
+        assert_eq!(split_c_literal("0x1FFFFFFFFU").unwrap().int_type(false, 0x1_FFFF_FFFF), (int(64, false), true));
+        assert_eq!(split_c_literal("0xFFFFFFFFFFFFFFFFL").unwrap().int_type(false, u64::MAX as u128),
+                   (int(64, false), true));
+        assert_eq!(split_c_literal("1L").unwrap().int_type(false, u64::MAX as u128), (int(64, true), false));
+        // C negates an unsigned value, Rust can't
+        assert_eq!(split_c_literal("1U").unwrap().int_type(true, 1), (int(32, false), true));
+        assert_eq!(split_rust_literal("1u32").unwrap().int_type(true, 1), (int(32, false), false));
+        assert_eq!(split_rust_literal("256u8").unwrap().int_type(false, 256), (int(8, false), false));
+    }
+
+    #[test]
+    fn test_int_type_fits() {
+        let u8_type = IntType { bits: 8, signed: false };
+        let i8_type = IntType { bits: 8, signed: true };
+        assert!(u8_type.fits(false, 255));
+        assert!(!u8_type.fits(false, 256));
+        assert!(!u8_type.fits(true, 1));
+        assert!(i8_type.fits(false, 127));
+        assert!(!i8_type.fits(false, 128));
+        assert!(i8_type.fits(true, 128));
+        assert!(!i8_type.fits(true, 129));
+        assert!(IntType { bits: 128, signed: false }.fits(false, u128::MAX));
+        assert!(IntType { bits: 128, signed: true }.fits(true, 1 << 127));
+        assert!(!IntType { bits: 128, signed: true }.fits(false, 1 << 127));
+        assert_eq!(i8_type.to_string(), "i8");
+        assert_eq!(IntType { bits: 64, signed: false }.to_string(), "u64");
+    }
+}
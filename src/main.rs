@@ -6,41 +6,347 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{convert::TryInto, string::ToString, cmp::PartialEq};
-use structopt::StructOpt;
+use std::{string::ToString, collections::HashMap, env, ffi::OsString, fmt, fs::{self, OpenOptions}, io::{self, BufWriter, Read, Write},
+          path::{Path, PathBuf}, process::ExitCode, thread, time::{Duration, Instant}};
+use numconverter::{
+    ErrorCode, ConversionResult,
+    abbrev::{abbreviate_to, format_metric_prefix, AbbrevStyle},
+    encoding::{Encoding, OverflowMode}, convert_to_base_10, as_string_base, as_string_base_unary, digit_count, to_digits, repeat_conversion, repeat_mismatch,
+    resolve_base, parse_base, parse_base_alias, insert_separators, truncate_repr, parse_group_by, get_sep_length_for_base, split_base_prefix,
+    parse_line_with_prefix, valid_bases_for_string, find_valid_bases,
+    base_info::base_info, bits::{self, BitOp}, checksum, encoding, factor,
+    constants::{constant, constant_names},
+    explain::{explain_to_base_10, explain_from_base_10, elide_steps, decompose, format_decomposition},
+    fixed_point::FixedPointFormat,
+    format::OutputFormat,
+    html::{format_as_html_table, full_document},
+    latex::format_as_latex_table_with_label,
+    xml::format_as_xml,
+    hex_float::{is_hex_float, HexFloat},
+    fraction::{as_percent, as_permille, split_at_radix_point, gcd, lcm, parse_fraction, parse_rational, parse_scientific, round_fraction_digits, repeating_fraction_digits, Rounding},
+    json::{self, Json},
+    literal::{split_c_literal, split_rust_literal, IntType},
+    markdown::{format_as_markdown_table, format_as_markdown_table_bold, markdown_header, markdown_row},
+    random::{random_number, Rng, SplitMix64},
+    range::{RangeSpec, Step},
+    notation::{base_symbol, to_subscript_str, to_superscript_str, number_to_morse, morse_to_number},
+    stats,
+};
+use structopt::{StructOpt, clap::AppSettings};
 
-#[derive(PartialEq)]
-enum ErrorCode {
-    BaseConversionErr,
-    TargetBaseErr,
-    InputBaseErr,
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod line_end;
+#[cfg(all(feature = "interactive", unix))]
+mod line_editor;
+mod osc52;
+mod progress;
+mod repl;
+use line_end::{LineEnd, LineEndWriter};
+use progress::{ProgressReporter, PROGRESS_INTERVAL};
+use repl::{Command, History, ReadLine, Variables};
+
+/// Print a diagnostic to stderr when the verbosity is at least `$level`
+macro_rules! verbose {
+    ($opt:expr, $level:expr, $($arg:tt)*) => {
+        if $opt.verbosity >= $level {
+            eprintln!($($arg)*);
+        }
+    };
 }
 
-impl std::fmt::Debug for ErrorCode {
-    fn fmt(&self, f:&mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", match *self {
-            ErrorCode::BaseConversionErr => "Base Conversion Error",
-            ErrorCode::TargetBaseErr     => "Target Base Error",
-            ErrorCode::InputBaseErr      => "Input Base Error",
-        })
+fn main() -> ExitCode {
+    // Get args
+    let opt = parse_opt(escape_after_double_dash(env::args_os()));
+    match try_main(&opt) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if e != ErrorCode::TestFalse {
+                eprintln!("Error: {:?}", e);
+            }
+            ExitCode::from(exit_code(&opt, &e))
+        },
     }
 }
 
-fn main() -> Result<(), ErrorCode> {
-    // Get args
-    let opt = Opt::from_args();
+/// Parse `args` as `Opt::from_iter` does, noting where the bit operations
+/// were so `transform` can run them in that order
+fn parse_opt<I>(args: I) -> Opt where I: IntoIterator, I::Item: Into<OsString> + Clone {
+    let matches = Opt::clap().get_matches_from(args);
+    let mut opt = Opt::from_clap(&matches);
+    opt.bit_op_indices = BIT_OPS.iter()
+        .flat_map(|&name| matches.indices_of(name).into_iter().flatten().map(move |i| (name, i)))
+        .collect();
+    opt
+}
+
+/// Arguments after `--` are literal, so escape the ones that would read as
+/// an `@FILE`
+fn escape_after_double_dash(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut literal = false;
+    args.map(|arg| {
+        if literal && arg.to_string_lossy().starts_with('@') {
+            let mut escaped = OsString::from("@");
+            escaped.push(arg);
+            return escaped;
+        }
+        literal |= arg == "--";
+        arg
+    }).collect()
+}
+
+/// Whether `opt` converts a batch of numbers from `--input-file` or `@FILE`
+fn is_batch(opt: &Opt) -> bool {
+    opt.input_file.is_some() || [&opt.from_base_char, &opt.from_num].iter()
+        .any(|arg| arg.as_deref().and_then(at_file).is_some())
+}
+
+/// The file named by an `@FILE` argument, `@-` for stdin. `@@` escapes a
+/// number starting with `@`.
+fn at_file(arg: &str) -> Option<PathBuf> {
+    match arg.strip_prefix('@') {
+        Some(rest) if !rest.starts_with('@') => Some(PathBuf::from(rest)),
+        _ => None,
+    }
+}
+
+/// The exit code for a failed run. Batch runs exit 1 when only some lines
+/// failed and 2 when none converted or the batch stopped at a failure, a
+/// single conversion exits 1. `--test-bit` and `--aligned-to` keep 1 for
+/// false, like `grep` does for no match, so their errors exit 2.
+fn exit_code(opt: &Opt, e: &ErrorCode) -> u8 {
+    match (is_batch(opt), e) {
+        (_, ErrorCode::TestFalse)     => 1,
+        _ if is_predicate(opt)        => 2,
+        (false, _)                    => 1,
+        (true, ErrorCode::PartialErr) => 1,
+        (true, _)                     => 2,
+    }
+}
+
+fn try_main(opt: &Opt) -> Result<(), ErrorCode> {
+    let mut aliases: HashMap<String, u32> = load_saved_aliases();
+    add_base_aliases(opt, &mut aliases)?;
+    if opt.save_alias {
+        save_aliases(opt)?;
+    }
+    if opt.from_base_char.is_none() && opt.save_alias {
+        // Only saving aliases, there's nothing to convert
+        return Ok(());
+    }
+    if opt.interactive {
+        let stdout = io::stdout();
+        return run_interactive(opt, &aliases, &mut *repl::stdin_lines(), &mut stdout.lock());
+    }
+
+    match &opt.output {
+        Some(path) if path != Path::new("-") => run_to_file(opt, &aliases, path),
+        _ => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            run(opt, &aliases, &mut out)
+        },
+    }
+}
+
+/// Do the conversion, writing the results to `--output`.
+///
+/// With `--append` the results are added to the end of the file. Otherwise
+/// they go to a temporary file next to it that only replaces it once every
+/// conversion has succeeded, so a failed run leaves the old file alone.
+fn run_to_file(opt: &Opt, aliases: &HashMap<String, u32>, path: &Path) -> Result<(), ErrorCode> {
+    let open_err = |path: &Path, e: io::Error| {
+        eprintln!("Could not write to {}: {}", path.display(), e);
+        ErrorCode::IoErr
+    };
+
+    if opt.append {
+        let file = OpenOptions::new().append(true).create(true).open(path).map_err(|e| open_err(path, e))?;
+        let mut out = BufWriter::new(file);
+        let result = run(opt, aliases, &mut out).and_then(|_| out.flush().map_err(|e| open_err(path, e)));
+        if result.is_err() {
+            eprintln!("Warning: {} may end with the output of a failed conversion", path.display());
+        }
+        return result;
+    }
 
-    if opt.verbosity > 0 {
-        println!("{:?}", opt);
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.numconverter-tmp", name));
+    let file = fs::File::create(&temp).map_err(|e| open_err(path, e))?;
+    let mut out = BufWriter::new(file);
+    let result = run(opt, aliases, &mut out)
+        .and_then(|_| out.flush().map_err(|e| open_err(path, e)))
+        .and_then(|_| fs::rename(&temp, path).map_err(|e| open_err(path, e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        verbose!(opt, 1, "Left {} unchanged", path.display());
+    }
+    result
+}
+
+/// Where `--save-alias` keeps aliases, one `NAME=BASE` per line
+fn alias_file() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("numconverter").join("aliases"))
+}
+
+fn load_saved_aliases() -> HashMap<String, u32> {
+    let mut aliases = HashMap::new();
+    let contents = match alias_file().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(v) => v,
+        None    => return aliases,
+    };
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        match parse_base_alias(line) {
+            Ok((name, base)) => { aliases.insert(name, base); },
+            Err(e)           => eprintln!("Warning: ignoring saved alias: {}", e),
+        }
+    }
+    aliases
+}
+
+/// Add the `--base-alias` definitions to `aliases`, overriding saved ones
+fn add_base_aliases(opt: &Opt, aliases: &mut HashMap<String, u32>) -> Result<(), ErrorCode> {
+    for spec in &opt.base_alias {
+        match parse_base_alias(spec) {
+            Ok((name, base)) => { aliases.insert(name, base); },
+            Err(e) => {
+                eprintln!("{}", e);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Merge the `--base-alias` definitions into the alias file
+fn save_aliases(opt: &Opt) -> Result<(), ErrorCode> {
+    let path = match alias_file() {
+        Some(v) => v,
+        None    => {
+            eprintln!("Can't find a config directory to save aliases in, set XDG_CONFIG_HOME or HOME");
+            return Err(ErrorCode::IoErr);
+        },
+    };
+    let mut saved = load_saved_aliases();
+    add_base_aliases(opt, &mut saved)?;
+    let mut names: Vec<&String> = saved.keys().collect();
+    names.sort();
+    let contents: String = names.iter().map(|name| format!("{}={}\n", name, saved[*name])).collect();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, contents)?;
+    verbose!(opt, 1, "Saved base aliases to {}", path.display());
+    Ok(())
+}
+
+/// Do the conversion described by `opt`, writing the results to `out`.
+///
+/// Only the requested output goes to `out`; diagnostics and verbose output
+/// go to stderr.
+fn run(opt: &Opt, aliases: &HashMap<String, u32>, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    verbose!(opt, 2, "Parsed arguments: {:?}", opt);
+    let only_opt;
+    let opt = match opt.only {
+        Some(_) => {
+            only_opt = just_the_value(opt);
+            &only_opt
+        },
+        None => opt,
+    };
+
+    let format_opt;
+    let opt = if opt.jsonl || opt.format.is_some() {
+        format_opt = with_format(opt);
+        &format_opt
+    } else {
+        opt
+    };
+
+    if let Some(base) = &opt.base_info {
+        return print_base_info(base, aliases, out);
+    }
+    if opt.radix_point == opt.sep_char {
+        eprintln!("--radix-point and --sep-char can't both be '{}'", opt.sep_char);
+        return Err(ErrorCode::InputBaseErr);
     }
 
     //
     // Sort out the optional indexed argument
     //
     let mut to_bases: Vec<String>    = opt.to_bases.clone();
-    let bases = get_bases(&opt, &mut to_bases);
-    let from_base: u32 = bases.0;
-    let from_num = bases.1;
+    let bases = get_bases(opt, aliases, &mut to_bases);
+    let mut from_base: u32 = bases.0;
+    let mut from_num = bases.1;
+    if opt.scan_bases {
+        return print_scan_bases(opt, from_num.as_deref().unwrap_or_default(), out);
+    }
+    if let Some(max) = opt.find_base {
+        return print_found_bases(opt, aliases, from_num.as_deref().unwrap_or_default(), max, &to_bases, out);
+    }
+    let mut batch_file = opt.input_file.clone();
+    if let Some(path) = from_num.as_deref().and_then(at_file) {
+        if batch_file.is_some() {
+            eprintln!("Can't read numbers from both --input-file and @{}", path.display());
+            return Err(ErrorCode::InputBaseErr);
+        }
+        batch_file = Some(path);
+        from_num = None;
+    } else if batch_file.is_some() {
+        // The numbers come from the file, so everything after the base_char is
+        // a target base
+        if let Some(base) = from_num.take() {
+            to_bases.insert(0, base);
+        }
+    } else if opt.constant.is_some() || !opt.diff.is_empty() || !opt.gcd.is_empty() || !opt.lcm.is_empty()
+        || opt.range.is_some() || opt.random || opt.benchmark
+        || opt.watch_clipboard {
+        // The input comes from a flag, so every positional is a target base
+        if let Some(base) = from_num.take() {
+            to_bases.insert(0, base);
+        }
+        if let Some(base_char) = opt.from_base_char.as_deref().filter(|c| resolve_base(c, aliases).is_some()) {
+            to_bases.insert(0, base_char.to_string());
+        }
+        if let Some(name) = &opt.constant {
+            match constant(name) {
+                Some(bits) => from_num = Some(bits.to_string()),
+                None       => {
+                    eprintln!("Unknown constant {}, try one of {}", name, constant_names());
+                    return Err(ErrorCode::InputBaseErr);
+                },
+            }
+            from_base = 10;
+        }
+    } else if let Some(literal) = from_num.as_deref().and_then(|n| n.strip_prefix('@')) {
+        from_num = Some(literal.to_string());
+    }
+
+    if opt.all_bases {
+        if !to_bases.is_empty() {
+            eprintln!("--all-bases can't be used with the target bases {}", to_bases.join(", "));
+            return Err(ErrorCode::TargetBaseErr);
+        }
+        to_bases = (2..=36).map(|base: u32| base.to_string()).collect();
+    }
+
+    if let Some(only) = &opt.only {
+        if !to_bases.is_empty() {
+            eprintln!("--only {} can't be used with the target bases {}", only, to_bases.join(", "));
+            return Err(ErrorCode::TargetBaseErr);
+        }
+        to_bases.push(only.clone());
+    }
+
+    if let Some(base_char) = opt.from_base_char.as_deref().filter(|c| resolve_base(c, aliases).is_some()) {
+        verbose!(opt, 2, "Input base {} taken from base_char '{}'", from_base, base_char);
+    } else {
+        verbose!(opt, 2, "No base_char given, input base {} taken from --from-base", from_base);
+    }
 
     if to_bases.is_empty() {
         to_bases = vec![
@@ -50,243 +356,4582 @@ fn main() -> Result<(), ErrorCode> {
             "16".to_string()
         ]
     }
+    if let Ok(targets) = target_bases(&to_bases, aliases) {
+        warn_excess_padding(opt, &targets);
+    }
 
-    //
-    // Convert input number to base 10
-    //
-    let num = convert_to_base_10(from_num, from_base, opt.sep_char)?;
+    match &batch_file {
+        Some(path) => verbose!(opt, 1, "Input file:        {}", path.display()),
+        None       => verbose!(opt, 1, "Input number:      {}", from_num.as_deref().unwrap_or("<none>")),
+    }
+    verbose!(opt, 1, "Input base:        {}", if opt.from_morse {
+        "Morse".to_string()
+    } else if let Some(format) = opt.from_fixed_point {
+        format!("{} fixed point", format)
+    } else {
+        from_base.to_string()
+    });
+    verbose!(opt, 1, "Target bases:      {}", to_bases.join(", "));
+    if opt.no_sep || opt.sep_length == 0 {
+        verbose!(opt, 1, "Separator:         none");
+    } else {
+        verbose!(opt, 1, "Separator:         '{}' every {} digits", opt.sep_char, opt.sep_length);
+    }
+    verbose!(opt, 1, "Padding:           {}", opt.pad);
+    if let Some(width) = opt.width {
+        verbose!(opt, 1, "Width:             {} bits, {}", width, if opt.signed { "signed" } else { "unsigned" });
+        verbose!(opt, 1, "On overflow:       {}", overflow_mode(opt));
+        verbose!(opt, 1, "Negative values:   {}", encoding(opt).description());
+    }
 
-    // Print conversions
-    for target_base in to_bases {
-        let custom_base = match u32::from_str_radix(&target_base, 10) {
-            Ok (v) => v,
-            Err(_) => {
-                println!("Error with target base {}\nPlease provide target base is base 10.", target_base);
-                return Err(ErrorCode::TargetBaseErr);
+    let line_end = if opt.no_newline {
+        LineEnd::NoFinalNewline
+    } else if opt.null {
+        LineEnd::Nul
+    } else {
+        LineEnd::Newline
+    };
+    let mut out = LineEndWriter::new(out, line_end);
+    if opt.markdown && !opt.silent {
+        let labels: Vec<String> = std::iter::once("Input".to_string())
+            .chain(target_bases(&to_bases, aliases)?.iter().map(|base| format!("Base {}", base)))
+            .collect();
+        writeln!(out, "{}", markdown_header(&labels))?;
+    }
+    if let [a, b] = opt.diff.as_slice() {
+        return print_diff(opt, aliases, a, b, &to_bases, &mut out);
+    }
+    if !opt.gcd.is_empty() {
+        return print_reduced(opt, aliases, &opt.gcd, false, &to_bases, &mut out);
+    }
+    if !opt.lcm.is_empty() {
+        return print_reduced(opt, aliases, &opt.lcm, true, &to_bases, &mut out);
+    }
+    if let Some(spec) = &opt.range {
+        return run_range(opt, aliases, spec, &to_bases, &mut out);
+    }
+    if opt.random {
+        return run_random(opt, aliases, &to_bases, &mut out);
+    }
+    if opt.benchmark {
+        return run_benchmark(opt, aliases, &to_bases, &mut out);
+    }
+    if opt.watch_clipboard {
+        return watch_clipboard(opt, aliases, from_base, &to_bases, &mut out);
+    }
+    match &batch_file {
+        Some(path) => run_batch(opt, aliases, path, from_base, &to_bases, &mut out)?.result(),
+        None       => convert(opt, aliases, from_num, from_base, &to_bases, &mut out),
+    }
+}
+
+/// `--interactive`: convert each line of `lines` as if its words were the
+/// positional arguments, with the rest of `opt` applying to every line, until
+/// the input ends or `:quit`. Lines are kept in the history file unless
+/// `--no-history`.
+fn run_interactive(opt: &Opt, aliases: &HashMap<String, u32>, lines: &mut dyn ReadLine,
+                   out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let history_path = if opt.no_history { None } else { repl::history_file() };
+    let mut history = match &history_path {
+        Some(path) => History::load(path, opt.history_size).unwrap_or_else(|e| {
+            eprintln!("Warning: couldn't read the history in {}: {}", path.display(), e);
+            History::new(opt.history_size)
+        }),
+        None => History::new(opt.history_size),
+    };
+
+    let mut variables = Variables::default();
+    let is_base = |word: &str| resolve_base(word, aliases).is_some();
+    while let Some(mut line) = lines.read_line(&history)? {
+        if let Command::Rerun(index) = Command::parse(&line) {
+            match history.get(index) {
+                Some(entry) => {
+                    writeln!(out, "{}", entry)?;
+                    line = entry.to_string();
+                },
+                None => {
+                    eprintln!("There's no history entry {}", index);
+                    continue;
+                },
+            }
+        }
+        let (name, entry) = match Command::parse(&line) {
+            Command::Convert(entry)      => (None, entry),
+            Command::Assign(name, entry) => (Some(name), entry),
+            Command::History => {
+                for (index, entry) in history.recent() {
+                    writeln!(out, "{:>5}  {}", index, entry)?;
+                }
+                continue;
+            },
+            Command::Vars => {
+                for (name, value) in variables.list() {
+                    writeln!(out, "{} = {}", name, value)?;
+                }
+                continue;
+            },
+            Command::Rerun(_) => {
+                eprintln!("A history entry can't run another one");
+                continue;
+            },
+            Command::Quit => break,
+            Command::Unknown(command) => {
+                eprintln!("Unknown command {}, try :history, :!N, :vars or :quit", command);
+                continue;
             },
         };
-        let mut out_str = match as_string_base(&num, custom_base) {
-            Ok(v)  => v,
+        if entry.is_empty() {
+            continue;
+        }
+        history.add(&line);
+        if let Some(path) = &history_path {
+            if let Err(e) = history.save(path) {
+                eprintln!("Warning: couldn't save the history to {}: {}", path.display(), e);
+            }
+        }
+
+        let words: Vec<&str> = entry.split_whitespace().collect();
+        let checked = name.map_or(Ok(()), |name| Variables::check_name(name, is_base))
+            .and_then(|_| variables.substitute(&words, is_base));
+        let mut words = match checked {
+            Ok(v)  => v.into_iter(),
             Err(e) => {
-                println!("Error with custom base:\n\t{}", e);
-                return Err(ErrorCode::InputBaseErr);
+                eprintln!("{}", e);
+                continue;
             },
         };
-
-        if !opt.silent {
-            if !opt.no_sep && opt.sep_length > 0 {
-                // Pad string every opt.spacer_length characters
-                // Need size-1/spacer_len additional slots in the string
-                let mut insert_idx: i32 = out_str.len() as i32 - opt.sep_length as i32;
-                while insert_idx > 0 {
-                    let left  = String::from(&out_str[..(insert_idx as usize)]);
-                    let right = String::from(&out_str[(insert_idx as usize)..]);
-                    out_str = left;
-                    out_str.push(opt.sep_char);
-                    out_str.push_str(&right);
-                    insert_idx -= opt.sep_length as i32;
+        let line_opt = Opt {
+            interactive: false,
+            from_base_char: words.next(),
+            from_num: words.next(),
+            to_bases: words.collect(),
+            ..opt.clone()
+        };
+        // A line that doesn't convert says why and the prompt carries on
+        match run(&line_opt, aliases, out) {
+            Err(ErrorCode::IoErr) => return Err(ErrorCode::IoErr),
+            Err(e)                => eprintln!("Error: {:?}", e),
+            Ok(()) => if let Some(value) = line_value(&line_opt, aliases) {
+                variables.set_ans(value);
+                if let Some(name) = name {
+                    variables.set(name, value);
                 }
-            }
-            if !opt.bare {
-                print!("Base {:02}: ", &custom_base);
-            }
-            println!("{}", out_str);
+            },
         }
+        out.flush()?;
     }
-    return Ok(());
+    Ok(())
 }
 
-fn get_from_base(from_base: &str) -> Option<u32>
-{
-    match from_base {
-        "b" => Some(2),
-        "o" => Some(8),
-        "d" => Some(10),
-        "h" | "x" => Some(16),
-        _   => None,
-    }
+/// The value a line at the prompt converted, for `$ans` and variables
+fn line_value(opt: &Opt, aliases: &HashMap<String, u32>) -> Option<u128> {
+    let quiet = Opt { verbosity: 0, explain: false, ..opt.clone() };
+    let (from_base, from_num) = get_bases(&quiet, aliases, &mut Vec::new());
+    let (num, _) = parse_input(&quiet, split_input(&quiet, from_num).0, from_base, &mut io::sink()).ok()?;
+    transform(&quiet, num, from_base).ok()
 }
 
-fn get_bases(opt: &Opt, to_bases: &mut Vec<String>) -> (u32, Option<String>) {
-    match get_from_base(opt.from_base_char.as_str()) {
-        Some(v) => (v, opt.from_num.clone()),
-        None => {
-            // No base_char. Push from_num to the bases Vec, push base_char to from_num.
-            if let Some(a_base) = &opt.from_num {
-                to_bases.insert(0, a_base.clone());
+/// `--range`: convert every value in the range, as a table with a column for
+/// each base. With `--json` or `--markdown` the values go through
+/// `convert_each` like a batch file.
+fn run_range(opt: &Opt, aliases: &HashMap<String, u32>, spec: &str, to_bases: &[String],
+             out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let values = RangeSpec::parse(spec, opt.from_base)?.values(opt.step, opt.limit)?;
+    if opt.json || opt.markdown {
+        let inputs: Vec<String> = values.iter().map(u128::to_string).collect();
+        let lines: Vec<(usize, &str)> = inputs.iter().map(String::as_str).enumerate().collect();
+        return convert_each(opt, aliases, &lines, &format!("--range {}", spec), 10, to_bases, out)?.result();
+    }
+
+    let targets = target_bases(to_bases, aliases)?;
+    if opt.silent {
+        return Ok(());
+    }
+    let mut rows = Vec::new();
+    if !opt.bare {
+        rows.push(targets.iter().map(|base| format!("Base {}", base)).collect::<Vec<_>>());
+    }
+    for value in values {
+        let mut row = Vec::new();
+        for &base in &targets {
+            match as_string_base(&value, base) {
+                Ok(v)  => row.push(separate(opt, &pad_digits(opt, v, base), base)),
+                Err(e) => {
+                    eprintln!("Error with custom base:\n\t{}", e);
+                    return Err(ErrorCode::InputBaseErr);
+                },
             }
-            // base_char wasn't provided, use the `-b` flag value as the base.
-            (opt.from_base, Some(opt.from_base_char.clone()))
-        },
+        }
+        rows.push(row);
+    }
+    let widths: Vec<usize> = (0..targets.len())
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    for row in rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:>1$}", cell, width)).collect();
+        writeln!(out, "{}", cells.join("  "))?;
     }
+    Ok(())
 }
 
-fn convert_to_base_10(from_num: Option<String>, from_base: u32, sep_char: char) -> Result<u128, ErrorCode> {
-    let from_num = if let Some(num) = from_num {
-        num.replace(sep_char, "")
-    } else {
-        println!("no number to convert was provided");
+/// `--random`: convert `--random-count` random values, like a batch file
+/// when there's more than one
+fn run_random(opt: &Opt, aliases: &HashMap<String, u32>, to_bases: &[String],
+              out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let (min, max) = match opt.random_range.as_slice() {
+        [min, max] => (parse_line_with_prefix(min, opt.from_base)?.0, parse_line_with_prefix(max, opt.from_base)?.0),
+        _          => (0, u64::MAX as u128),
+    };
+    if min > max {
+        eprintln!("--random-range {} {} ends before it starts", min, max);
         return Err(ErrorCode::InputBaseErr);
+    }
+    let mut rng = match opt.seed {
+        Some(seed) => SplitMix64::seed_from_u64(seed),
+        None       => SplitMix64::from_entropy(),
     };
-
-    match u128::from_str_radix(&from_num, from_base) {
-        Ok(v)  => Ok(v),
-        Err(_e) => {
-            println!("Could not convert {} from base {}", from_num, from_base);
-            return Err(ErrorCode::BaseConversionErr);
-        },
+    let inputs: Vec<String> = (0..opt.random_count).map(|_| random_number(&mut rng, min, max).to_string()).collect();
+    if let [input] = inputs.as_slice() {
+        return convert(opt, aliases, Some(input.clone()), 10, to_bases, out);
     }
+    let lines: Vec<(usize, &str)> = inputs.iter().map(String::as_str).enumerate().collect();
+    convert_each(opt, aliases, &lines, "--random", 10, to_bases, out)?.result()
 }
 
-fn as_string_base(num: &u128, base: u32) -> Result<String, String>
-{
-    if base<2 || base>33 {
-        Err(String::from("Invalid Base.  Base must be between 2 and 32 inclusive"))
-    }
-    else {
-        let mut str_num = String::new();
+/// `--benchmark`: time converting `--count` random u64s to each target base
+/// and print the throughput instead of the results
+fn run_benchmark(opt: &Opt, aliases: &HashMap<String, u32>, to_bases: &[String],
+                 out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let targets = target_bases(to_bases, aliases)?;
+    let mut rng = match opt.seed {
+        Some(seed) => SplitMix64::seed_from_u64(seed),
+        None       => SplitMix64::from_entropy(),
+    };
+    let warmup: Vec<u128> = (0..opt.benchmark_warmup).map(|_| rng.next_u64() as u128).collect();
+    let numbers: Vec<u128> = (0..opt.count).map(|_| rng.next_u64() as u128).collect();
+    let convert_all = |numbers: &[u128]| -> Result<(), ErrorCode> {
+        for num in numbers {
+            for &base in &targets {
+                let digits = as_string_base(num, base).map_err(|e| {
+                    eprintln!("Error with custom base:\n\t{}", e);
+                    ErrorCode::InputBaseErr
+                })?;
+                std::hint::black_box(separate(opt, &digits, base));
+            }
+        }
+        Ok(())
+    };
 
-        let mut tmp: u128 = *num;
-        let mut count: u32 = 0;
+    convert_all(&warmup)?;
+    let start = Instant::now();
+    convert_all(&numbers)?;
+    let elapsed = start.elapsed().as_secs_f64();
+    verbose!(opt, 1, "Benchmarked {} warmup and {} timed numbers to bases {}", warmup.len(), numbers.len(),
+             to_bases.join(", "));
+    if !opt.silent {
+        let rate = if elapsed > 0.0 { numbers.len() as f64 / elapsed } else { f64::INFINITY };
+        writeln!(out, "Converted {} numbers in {:.3}s ({:.0} conversions/sec)", numbers.len(), elapsed, rate)?;
+    }
+    Ok(())
+}
 
-        while tmp > 0 {
-            let radix_mask: u128 = u128::from((base as u128).pow(count));
-            let digit: u8 = match ((tmp / radix_mask) % u128::from(base)).try_into() {
-                Ok(v)  => v,
-                Err(_) => {
-                    return Err(format!("Error while trying to convert to radix {}", base));
-                },
-            };
+/// `--watch-clipboard`: convert each new number copied to the clipboard until
+/// Ctrl-C. Numbers can have a base prefix, and anything else is ignored.
+#[cfg(feature = "clipboard")]
+fn watch_clipboard(opt: &Opt, aliases: &HashMap<String, u32>, from_base: u32, to_bases: &[String],
+                   out: &mut dyn Write) -> Result<(), ErrorCode> {
+    // Checked quietly, since most of what gets copied isn't a number
+    let number = |value: &str| split_base_prefix(value, from_base).ok()
+        .filter(|(digits, base)| u128::from_str_radix(&digits.replace(opt.sep_char, ""), *base).is_ok());
+    verbose!(opt, 1, "Watching the clipboard, Ctrl-C to stop");
+    let mut first = true;
+    clipboard::watch(clipboard::stop_on_ctrl_c(), |value| number(value).is_some(), |value| {
+        let (digits, base) = number(value).unwrap();
+        if !first && !opt.json {
+            print_group_separator(out, &opt.output_separator)?;
+        }
+        first = false;
+        // A value that doesn't convert has already said why on stderr
+        match convert(opt, aliases, Some(digits), base, to_bases, out) {
+            Err(ErrorCode::IoErr) => return Err(ErrorCode::IoErr),
+            _                     => out.flush()?,
+        }
+        Ok(())
+    })
+}
 
-            let ch = if digit >= 10 {
-                (b'A' + (digit-10)) as char
-            }
-            else {
-                (b'0' + digit) as char
-            };
+#[cfg(not(feature = "clipboard"))]
+fn watch_clipboard(_opt: &Opt, _aliases: &HashMap<String, u32>, _from_base: u32, _to_bases: &[String],
+                   _out: &mut dyn Write) -> Result<(), ErrorCode> {
+    eprintln!("--watch-clipboard needs numconverter built with the clipboard feature");
+    Err(ErrorCode::InputBaseErr)
+}
 
-            str_num = ch.to_string() + str_num.as_str();
+/// `--diff`: both values in each target base one above the other with their
+/// XOR under them, then the bit positions that differ
+fn print_diff(opt: &Opt, aliases: &HashMap<String, u32>, a: &str, b: &str, to_bases: &[String],
+              out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let (a_num, a_base) = parse_line_with_prefix(a, opt.from_base)?;
+    let (b_num, b_base) = parse_line_with_prefix(b, opt.from_base)?;
+    let xor = a_num ^ b_num;
+    let bits: Vec<u32> = (0..128).filter(|bit| (xor >> bit) & 1 == 1).collect();
+    let targets = target_bases(to_bases, aliases)?;
+    if opt.silent {
+        return Ok(());
+    }
 
-            count += 1;
-            tmp -= u128::from(digit) * radix_mask;
+    let mut results = Vec::new();
+    for base in targets {
+        let mut values = Vec::new();
+        for num in &[a_num, b_num, xor] {
+            match as_string_base(num, base) {
+                Ok(v)  => values.push(v),
+                Err(e) => {
+                    eprintln!("Error with custom base:\n\t{}", e);
+                    return Err(ErrorCode::InputBaseErr);
+                },
+            }
+        }
+        // Pad all three to the same length so the digits line up
+        let width = values.iter().map(String::len).max().unwrap_or(0).max(opt.pad as usize);
+        let values: Vec<String> = values.iter().map(|v| format!("{:0>1$}", v, width)).collect();
+        if opt.json {
+            results.push(Json::object()
+                .with("base", base)
+                .with("a", values[0].as_str())
+                .with("b", values[1].as_str())
+                .with("xor", values[2].as_str()));
+            continue;
+        }
+        let values: Vec<String> = values.iter().map(|v| separate(opt, v, base)).collect();
+        if opt.bare {
+            writeln!(out, "{}\n{}\n{}", values[0], values[1], values[2])?;
+        } else {
+            writeln!(out, "Base {:02}: {}\n         {}\nXOR:     {}", base, values[0], values[1], values[2])?;
         }
+    }
 
-        Ok(str_num)
+    if opt.json {
+        let input = |raw: &str, base: u32, num: u128| Json::object()
+            .with("input", raw)
+            .with("from_base", base)
+            .with("decimal", num);
+        writeln!(out, "{}", Json::object()
+            .with("a", input(a, a_base, a_num))
+            .with("b", input(b, b_base, b_num))
+            .with("xor", xor)
+            .with("differing_bits", bits)
+            .with("results", results))?;
+    } else {
+        let bits: Vec<String> = bits.iter().map(u32::to_string).collect();
+        if !opt.bare {
+            write!(out, "Bits differ: ")?;
+        }
+        writeln!(out, "{}", if bits.is_empty() { "none".to_string() } else { bits.join(", ") })?;
     }
+    Ok(())
 }
 
+/// `--gcd` or, with `multiple`, `--lcm`: reduce `inputs` to their greatest common
+/// divisor or least common multiple and print it in each target base
+fn print_reduced(opt: &Opt, aliases: &HashMap<String, u32>, inputs: &[String], multiple: bool, to_bases: &[String],
+                 out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let name = if multiple { "LCM" } else { "GCD" };
+    if inputs.len() < 2 {
+        eprintln!("--{} needs at least two numbers, separated by commas", name.to_lowercase());
+        return Err(ErrorCode::InputBaseErr);
+    }
+    let mut nums = Vec::new();
+    for input in inputs {
+        let (num, base) = parse_line_with_prefix(input, opt.from_base)?;
+        verbose!(opt, 1, "Input number:      {} in base {} is {}", input, base, num);
+        nums.push(num);
+    }
+    let reduced = if multiple {
+        nums[1..].iter().try_fold(nums[0], |acc, &num| lcm(acc, num)).ok_or_else(|| {
+            eprintln!("The LCM of {} is too large for 128 bits", inputs.join(", "));
+            ErrorCode::OverflowErr
+        })?
+    } else {
+        nums[1..].iter().fold(nums[0], |acc, &num| gcd(acc, num))
+    };
+    let targets = target_bases(to_bases, aliases)?;
+    if opt.silent {
+        return Ok(());
+    }
 
-#[derive(StructOpt, Debug)]
-#[structopt(name = "numconverter", about = "A CLI number conversion utility written in Rust")]
-struct Opt {
-    /// Pad the output with leading 0s
-    #[structopt(short, long, default_value = "0")]
-    pad: u8,
+    let mut results = Vec::new();
+    for base in targets {
+        let digits = as_string_base(&reduced, base).map_err(|e| {
+            eprintln!("Error with custom base:\n\t{}", e);
+            ErrorCode::InputBaseErr
+        })?;
+        let digits = format!("{:0>1$}", digits, opt.pad as usize);
+        if opt.json {
+            results.push(Json::object().with("base", base).with("value", digits.as_str()));
+        } else if opt.bare {
+            writeln!(out, "{}", separate(opt, &digits, base))?;
+        } else {
+            writeln!(out, "{} base {:02}: {}", name, base, separate(opt, &digits, base))?;
+        }
+    }
+    if opt.json {
+        writeln!(out, "{}", Json::object()
+            .with("inputs", inputs.iter().map(String::as_str).collect::<Vec<_>>())
+            .with(&name.to_lowercase(), reduced)
+            .with("results", results))?;
+    }
+    Ok(())
+}
 
-    /// Put a spacer every N characters
-    #[structopt(short = "-l", long, default_value = "4")]
-    sep_length: u32,
+/// Put separators in the digits of a value in `base`, with `--group-by` or
+/// `--sep-length` digits between them
+fn separate(opt: &Opt, digits: &str, base: u32) -> String {
+    if opt.no_sep {
+        return digits.to_string();
+    }
+    let sep_length = opt.group_by.as_ref()
+        .map_or(opt.sep_length, |overrides| get_sep_length_for_base(base, overrides, opt.sep_length));
+    insert_separators(digits, opt.sep_char, sep_length)
+}
 
-    /// Specify spacer char
-    #[structopt(long, default_value = "_")]
-    sep_char: char,
+/// Describe the base named by `--base-info`
+fn print_base_info(base: &str, aliases: &HashMap<String, u32>, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let info = base.parse::<u32>().ok()
+        .or_else(|| resolve_base(base, aliases))
+        .ok_or_else(|| format!("Unknown base {}", base))
+        .and_then(base_info);
+    match info {
+        Ok(info) => writeln!(out, "{}", info)?,
+        Err(e)   => {
+            eprintln!("{}", e);
+            return Err(ErrorCode::InputBaseErr);
+        },
+    }
+    Ok(())
+}
 
-    /// Do not pad the output
-    #[structopt(long)]
-    no_sep: bool,
+/// The `--scan-bases` table of every base in the scanned range that can read
+/// `input`, with the value in decimal
+fn print_scan_bases(opt: &Opt, input: &str, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let (min, max) = (opt.min_from_base, opt.max_from_base);
+    if !(2..=36).contains(&min) || !(2..=36).contains(&max) || min > max {
+        eprintln!("Bases to scan must be from 2 to 36, with --min-from-base no more than --max-from-base");
+        return Err(ErrorCode::InputBaseErr);
+    }
+    let digits = input.replace(opt.sep_char, "");
+    let bases: Vec<_> = valid_bases_for_string(&digits).into_iter()
+        .filter(|(base, _)| (min..=max).contains(base))
+        .collect();
+    if bases.is_empty() {
+        eprintln!("No base from {} to {} can read {}", min, max, input);
+        return Err(ErrorCode::BaseConversionErr);
+    }
+    if opt.silent {
+        return Ok(());
+    }
+    if opt.json {
+        let bases: Vec<Json> = bases.iter()
+            .map(|&(base, value)| Json::object().with("base", base).with("decimal", value))
+            .collect();
+        writeln!(out, "{}", Json::from(bases))?;
+        return Ok(());
+    }
+    if !opt.bare {
+        writeln!(out, "Base  Decimal")?;
+    }
+    for (base, value) in bases {
+        if opt.bare {
+            writeln!(out, "{} {}", base, value)?;
+        } else {
+            writeln!(out, "{:>4}  {}", base, value)?;
+        }
+    }
+    Ok(())
+}
 
-    /// Input Base
-    ///
-    /// base_char takes precedence over this setting
-    #[structopt(short, long, default_value = "10")]
-    from_base: u32,
+/// `--find-base`: every base from `--min-from-base` to `max` that can read
+/// `input`, with the value in decimal and in each of `to_bases`
+fn print_found_bases(opt: &Opt, aliases: &HashMap<String, u32>, input: &str, max: u32, to_bases: &[String],
+                     out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let min = opt.min_from_base;
+    if !(2..=36).contains(&min) || !(2..=36).contains(&max) || min > max {
+        eprintln!("Bases to find must be from 2 to 36, with --min-from-base no more than --find-base");
+        return Err(ErrorCode::InputBaseErr);
+    }
+    let targets = target_bases(to_bases, aliases)?;
+    let bases = find_valid_bases(&input.replace(opt.sep_char, ""), min, max);
+    if bases.is_empty() {
+        eprintln!("No base from {} to {} can read {}", min, max, input);
+        return Err(ErrorCode::BaseConversionErr);
+    }
+    if opt.silent {
+        return Ok(());
+    }
+    let mut found = Vec::new();
+    for (base, value) in bases {
+        let mut results = Vec::new();
+        for &target in &targets {
+            let digits = as_string_base(&value, target).map_err(|e| {
+                eprintln!("Error with custom base:\n\t{}", e);
+                ErrorCode::InputBaseErr
+            })?;
+            results.push((target, digits));
+        }
+        if opt.json {
+            let results: Vec<Json> = results.iter()
+                .map(|(target, digits)| Json::object().with("base", *target).with("value", digits.as_str()))
+                .collect();
+            found.push(Json::object().with("base", base).with("decimal", value).with("results", results));
+        } else if opt.bare {
+            // A row per base, the base, its decimal value and then the digits in each target
+            let mut row = vec![base.to_string(), value.to_string()];
+            row.extend(results.iter().map(|(target, digits)| separate(opt, digits, *target)));
+            writeln!(out, "{}", row.join(" "))?;
+        } else {
+            writeln!(out, "Base {:02}: {}", base, value)?;
+            for (target, digits) in results {
+                writeln!(out, "  Base {:02}: {}", target, separate(opt, &digits, target))?;
+            }
+        }
+    }
+    if opt.json {
+        writeln!(out, "{}", Json::from(found))?;
+    }
+    Ok(())
+}
 
-    /// Do not print output (for use with clipboard)
-    #[structopt(short, long)]
-    silent: bool,
+/// `opt` with the flags for its `--format` set. `--jsonl` is JSON written a
+/// line at a time, so it sets `json` too. `--format markdown` is a table
+/// with a row per base for one number, and the `--markdown` table for more.
+fn with_format(opt: &Opt) -> Opt {
+    let jsonl = opt.jsonl || opt.format == Some(OutputFormat::Jsonl);
+    let markdown = matches!(opt.format, Some(OutputFormat::Markdown) | Some(OutputFormat::MarkdownBold));
+    Opt {
+        json:     opt.json || jsonl || opt.format == Some(OutputFormat::Json),
+        jsonl,
+        markdown: opt.markdown || (markdown && !converts_one_number(opt)),
+        ..opt.clone()
+    }
+}
 
-    /// Disable Pretty Print
-    #[structopt(long)]
-    bare: bool,
+/// The `--format` that prints one number as a table of its own, with a row
+/// per base
+fn table_format(opt: &Opt) -> Option<OutputFormat> {
+    match opt.format {
+        Some(OutputFormat::Markdown) | Some(OutputFormat::MarkdownBold) if opt.markdown => None,
+        Some(OutputFormat::Text) | Some(OutputFormat::Json) | Some(OutputFormat::Jsonl) | None => None,
+        format => format,
+    }
+}
 
-    /// Verbosity (more v's, more verbose)
-    #[structopt(short, long, parse(from_occurrences))]
-    verbosity: u8,
+/// Whether `opt` converts a single number, rather than a batch, range or
+/// several random values
+fn converts_one_number(opt: &Opt) -> bool {
+    let several_random = opt.random && opt.random_count != 1;
+    !(is_batch(opt) || opt.range.is_some() || several_random || opt.watch_clipboard)
+}
 
-    /// Char representation of input base (b, o, d, or h) [optional]
-    from_base_char: String,
+type TurnOff = fn(&mut Opt);
 
-    /// Number to convert
-    from_num: Option<String>,
+/// The flags that print more than the value in each base, each with the
+/// arguments that turn it on, for `test_only`, and how to turn it off.
+/// `--only` turns them all off, so a new one goes here.
+const EXTRA_OUTPUT: &[(&[&str], TurnOff)] = &[
+    (&["--morse"],               |opt| opt.morse = false),
+    (&["--unary"],               |opt| opt.unary = false),
+    (&["--metric-prefix"],       |opt| opt.metric_prefix = false),
+    (&["--alignment"],           |opt| opt.alignment = false),
+    (&["--percent=1000"],        |opt| opt.percent = None),
+    (&["--permille=1000"],       |opt| opt.permille = None),
+    (&["--explain"],             |opt| opt.explain = false),
+    (&["--entropy"],             |opt| opt.entropy = false),
+    (&["--width-needed"],        |opt| opt.width_needed = false),
+    (&["--hamming", "0"],        |opt| opt.hamming = None),
+    // --check-parity still needs the scheme, and reports through the exit code
+    (&["--parity", "even"],      |opt| if opt.check_parity.is_none() { opt.parity = None }),
+    (&["--abbrev"],              |opt| opt.abbrev = false),
+    (&["--digit-freq"],          |opt| opt.digit_freq = false),
+    (&["--fixed-point", "Q8.8"], |opt| opt.fixed_point = None),
+    (&["--interpret"],           |opt| opt.interpret = false),
+    (&["--stats"],               |opt| opt.stats = false),
+    (&["--pow2"],                |opt| opt.pow2 = false),
+    (&["--factor"],              |opt| opt.factor = false),
+    (&["--decompose"],           |opt| opt.decompose = false),
+    (&["--repeat", "3"],         |opt| opt.repeat = None),
+    (&["--color-blind"],         |opt| opt.color_blind = false),
+];
 
-    /// Bases to convert to
-    to_bases: Vec<String>,
+/// `opt` for `--only`: bare, with every line but the value turned off
+fn just_the_value(opt: &Opt) -> Opt {
+    let mut only = Opt { bare: true, ..opt.clone() };
+    for (_, turn_off) in EXTRA_OUTPUT {
+        turn_off(&mut only);
+    }
+    only
 }
 
+/// Read `path`, or stdin for `-`
+fn read_input_file(path: &Path) -> Result<String, ErrorCode> {
+    let mut contents = String::new();
+    let read = if path == Path::new("-") {
+        io::stdin().read_to_string(&mut contents).map(|_| ())
+    } else {
+        fs::read_to_string(path).map(|v| contents = v)
+    };
+    if let Err(e) = read {
+        eprintln!("Could not read {}: {}", path.display(), e);
+        return Err(ErrorCode::IoErr);
+    }
+    Ok(contents)
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// How the lines of a batch went, for `--summary` and the exit code
+#[derive(Debug, Default, PartialEq)]
+struct BatchSummary {
+    total:     usize,
+    converted: usize,
+    failed:    usize,
+    /// Lines after a failure without `--keep-going`
+    skipped:   usize,
+    last_error: Option<ErrorCode>,
+}
 
-    #[test]
-    fn test_bin() {
-        assert_eq!(as_string_base(&4,   2).unwrap(), "100");
-        assert_eq!(as_string_base(&12,  2).unwrap(), "1100");
-        assert_eq!(as_string_base(&187, 2).unwrap(), "10111011");
-        assert_eq!(as_string_base(&69,  2).unwrap(), "1000101");
+impl BatchSummary {
+    /// `PartialErr` when only some lines failed, the last error when none converted
+    fn result(&self) -> Result<(), ErrorCode> {
+        match self.last_error {
+            None                                               => Ok(()),
+            Some(_) if self.converted > 0 && self.skipped == 0 => Err(ErrorCode::PartialErr),
+            Some(e)                                            => Err(e),
+        }
     }
+}
 
-    #[test]
-    fn test_oct() {
-        assert_eq!(as_string_base(&4,   8).unwrap(), "4");
-        assert_eq!(as_string_base(&12,  8).unwrap(), "14");
-        assert_eq!(as_string_base(&187, 8).unwrap(), "273");
-        assert_eq!(as_string_base(&69,  8).unwrap(), "105");
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Summary: {} of {} lines converted, {} failed, {} skipped",
+               self.converted, self.total, self.failed, self.skipped)
     }
+}
 
-    #[test]
-    fn test_hex() {
-        assert_eq!(as_string_base(&4,   16).unwrap(), "4");
-        assert_eq!(as_string_base(&12,  16).unwrap(), "C");
-        assert_eq!(as_string_base(&187, 16).unwrap(), "BB");
-        assert_eq!(as_string_base(&69,  16).unwrap(), "45");
-    }
+/// How many lines `--parallel` converts at once, so results still stream
+/// out of long batches
+const PARALLEL_BLOCK: usize = 4096;
+
+/// Convert every number in `--input-file`, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped. With
+/// `--from-file-base` each line can pick its own base with a prefix, see
+/// `split_base_prefix`.
+fn run_batch(opt: &Opt, aliases: &HashMap<String, u32>, path: &Path, from_base: u32, to_bases: &[String],
+             out: &mut dyn Write) -> Result<BatchSummary, ErrorCode> {
+    let contents = read_input_file(path)?;
+    let lines: Vec<(usize, &str)> = contents.lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    convert_each(opt, aliases, &lines, &path.display().to_string(), from_base, to_bases, out)
+}
+
+/// Convert each of `lines`, numbered by their index in `source`.
+///
+/// The results for each line are separated by `--output-separator`, or with
+/// `--json` printed as one array. Stops at the first line that fails to
+/// convert unless `--keep-going`. Prints progress to stderr for long
+/// batches unless `--no-progress` or `--silent`, and the counts with
+/// `--summary`.
+fn convert_each(opt: &Opt, aliases: &HashMap<String, u32>, lines: &[(usize, &str)], source: &str, from_base: u32,
+                to_bases: &[String], out: &mut dyn Write) -> Result<BatchSummary, ErrorCode> {
+    let mut progress = ProgressReporter::new(lines.len(), PROGRESS_INTERVAL, !opt.no_progress && !opt.silent);
+    let mut summary = BatchSummary { total: lines.len(), ..BatchSummary::default() };
+    let mut records = Vec::new();
+    let results: Box<dyn Iterator<Item = (Result<(), ErrorCode>, Vec<u8>)>> = if opt.parallel {
+        Box::new(lines.chunks(PARALLEL_BLOCK)
+            .flat_map(|block| process_lines_parallel(opt, aliases, block, from_base, to_bases)))
+    } else {
+        Box::new(lines.iter().map(|&(index, line)| convert_line(opt, aliases, index, line, from_base, to_bases)))
+    };
+    for (&(index, line), (converted, mut record)) in lines.iter().zip(results) {
+        if opt.jsonl {
+            // Every line gets an object, so the output lines up with the input
+            if let (true, false, Err(e)) = (record.is_empty(), opt.silent, converted) {
+                write_json_error(opt, &mut record, json_error(line, from_base, e))?;
+            }
+            out.write_all(&record)?;
+            out.flush()?;
+        } else if opt.json {
+            if !record.is_empty() {
+                records.push(String::from_utf8_lossy(&record).trim_end().to_string());
+            }
+        } else if opt.markdown {
+            // The rows of one table
+            out.write_all(&record)?;
+        } else if !record.is_empty() {
+            // The empty line between results would be an empty record with -z
+            if summary.converted + summary.failed > 0 && !(opt.null && opt.output_separator.is_empty()) {
+                print_group_separator(out, &opt.output_separator)?;
+            }
+            out.write_all(&record)?;
+        }
+
+        match converted {
+            Ok(())  => summary.converted += 1,
+            Err(e) => {
+                summary.failed += 1;
+                summary.last_error = Some(e);
+                if !opt.keep_going {
+                    eprintln!("Stopped at line {} of {}", index + 1, source);
+                    break;
+                }
+                eprintln!("Line {} of {} failed to convert", index + 1, source);
+            },
+        }
+        progress.tick();
+    }
+    progress.finish();
+    summary.skipped = summary.total - summary.converted - summary.failed;
+
+    if opt.json && !opt.jsonl && !opt.silent {
+        writeln!(out, "[{}]", records.join(","))?;
+    }
+    if opt.summary {
+        eprintln!("{}", summary);
+    }
+    Ok(summary)
+}
+
+/// Convert one line of a batch, returning the output for it
+fn convert_line(opt: &Opt, aliases: &HashMap<String, u32>, index: usize, line: &str, from_base: u32,
+                to_bases: &[String]) -> (Result<(), ErrorCode>, Vec<u8>) {
+    let mut record = Vec::new();
+    let converted = if opt.from_file_base {
+        split_base_prefix(line, from_base)
+    } else {
+        Ok((line.to_string(), from_base))
+    };
+    let converted = converted.and_then(|(from_num, base)| {
+        verbose!(opt, 2, "Line {}: {} in base {}", index + 1, from_num, base);
+        convert(opt, aliases, Some(from_num), base, to_bases, &mut record)
+    });
+    (converted, record)
+}
+
+/// `--parallel`: convert `lines` split between a thread for each core,
+/// returning the output for each in the order of `lines`
+fn process_lines_parallel(opt: &Opt, aliases: &HashMap<String, u32>, lines: &[(usize, &str)], from_base: u32,
+                          to_bases: &[String]) -> Vec<(Result<(), ErrorCode>, Vec<u8>)> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_len = lines.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let chunks: Vec<_> = lines.chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|&(index, line)| convert_line(opt, aliases, index, line, from_base, to_bases))
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+        chunks.into_iter().flat_map(|chunk| chunk.join().unwrap()).collect()
+    })
+}
+
+/// Print the line between the results for two inputs
+fn print_group_separator(writer: &mut dyn Write, sep: &str) -> Result<(), ErrorCode> {
+    writeln!(writer, "{}", sep)?;
+    Ok(())
+}
+
+/// The bases named by `to_bases`, as numbers or aliases
+fn target_bases(to_bases: &[String], aliases: &HashMap<String, u32>) -> Result<Vec<u32>, ErrorCode> {
+    let mut targets: Vec<u32> = Vec::new();
+    for target_base in to_bases {
+        match target_base.parse::<u32>().ok().or_else(|| resolve_base(target_base, aliases)) {
+            Some(v) => targets.push(v),
+            None    => {
+                eprintln!("Error with target base {}\nPlease provide target base is base 10.", target_base);
+                return Err(ErrorCode::TargetBaseErr);
+            },
+        }
+    }
+    Ok(targets)
+}
+
+/// Convert one number from `from_base` to each of `to_bases`, writing the
+/// results and any annotations to `out`.
+fn convert(opt: &Opt, aliases: &HashMap<String, u32>, from_num: Option<String>, from_base: u32, to_bases: &[String],
+           out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let raw_input = from_num.clone().unwrap_or_default();
+    let literal_opt;
+    let (opt, from_num, from_base) = match literal_input(opt, &raw_input, from_base) {
+        Some((digits, base, Some(int_type))) => {
+            literal_opt = with_int_type(opt, int_type);
+            (&literal_opt, Some(digits), base)
+        },
+        Some((digits, base, None)) => (opt, Some(digits), base),
+        None => (opt, from_num, from_base),
+    };
+    let input = from_num.clone().unwrap_or_default();
+
+    //
+    // Convert input number to base 10
+    //
+    let parsed = if input.contains('/') || is_scientific(opt, &input, from_base) {
+        parse_rational_input(opt, &input, from_base).map(|(num, frac)| (num, false, frac))
+    } else if is_hex_float_input(opt, &input, from_base) {
+        parse_hex_float_input(opt, &input, out)
+    } else {
+        let (from_num, frac_digits) = split_input(opt, from_num);
+        parse_input(opt, from_num, from_base, out)
+            .and_then(|(num, overflowed)| Ok((num, overflowed, parse_fraction_input(opt, frac_digits, from_base)?)))
+    };
+    let (num, overflowed, frac) = match parsed {
+        Ok(v)  => v,
+        Err(e) => {
+            if opt.json && !opt.silent {
+                write_json_error(opt, out, json_error(&raw_input, from_base, e))?;
+            }
+            return Err(e);
+        },
+    };
+    verbose!(opt, 2, "Parsed value:      {}", num);
+    let (num, luhn_valid) = match &opt.luhn {
+        Some(mode) => luhn(opt, mode, num, from_base, frac.is_some())?,
+        None       => (num, None),
+    };
+    let num = transform(opt, num, from_base)?;
+    let (num, complement) = if opt.complement { complement(opt, num)? } else { (num, None) };
+    let hamming = match &opt.hamming {
+        Some(other) => Some(hamming(opt, num, other, from_base)?),
+        None        => None,
+    };
+    let ratios = ratios(opt, num, from_base)?;
+    let parity_ok = match opt.check_parity {
+        Some(width) => Some(check_parity(opt, num, width)?),
+        None        => None,
+    };
+    if is_predicate(opt) {
+        return predicate(opt, num, out);
+    }
+
+    let targets = target_bases(to_bases, aliases)?;
+
+    if let Some(format) = opt.fixed_point {
+        if format.width() < 128 && num >> format.width() != 0 {
+            eprintln!("{} does not fit in {} ({} bits)", num, format, format.width());
+            return Err(ErrorCode::TargetBaseErr);
+        }
+    }
+
+    if let Some(base) = &opt.osc52 {
+        let base = match base {
+            Some(base) => target_bases(std::slice::from_ref(base), aliases)?[0],
+            None       => targets[0],
+        };
+        let (int, frac_str) = with_fraction(opt, num, frac, base);
+        match as_string_base(&int, base) {
+            Ok(v)  => osc52::copy_to_terminal(&(pad_digits(opt, v, base) + &frac_str)),
+            Err(e) => {
+                eprintln!("Error with custom base:\n\t{}", e);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        }
+    }
+
+    if opt.digit_count_only {
+        if !opt.silent {
+            print_digit_counts(opt, num, &targets, out)?;
+        }
+        return Ok(());
+    }
+
+    if opt.digit_list {
+        if !opt.silent {
+            print_digit_lists(opt, num, &targets, out)?;
+        }
+        return Ok(());
+    }
+
+    if opt.json {
+        if opt.silent {
+            return Ok(());
+        }
+        let mut json = conversion_json(opt, &raw_input, from_base, num, frac, &targets)?;
+        if opt.width.is_some() {
+            let mode = overflow_mode(opt);
+            json = json
+                .with("wrapped", overflowed && mode == OverflowMode::Wrap)
+                .with("clamped", overflowed && mode == OverflowMode::Saturate);
+        }
+        if let (Some(encoding), Some(width)) = (chosen_encoding(opt), final_width(opt)) {
+            let (negative, magnitude) = encoding.decode(num, width);
+            json = json
+                .with("encoding", encoding.to_string())
+                .with("signed_value", encoding::format_signed(negative, magnitude));
+        }
+        match luhn_valid {
+            Some(valid) => json = json.with("luhn_valid", valid),
+            None if opt.luhn.is_some() => json = json.with("luhn_check_digit", (num % 10) as u32),
+            None => (),
+        }
+        let bit_ops: Vec<Json> = bit_op_order(opt).into_iter()
+            .filter_map(|(op, nth)| Some(Json::object().with("op", op).with("bit", single_bits(opt, op)?[nth])))
+            .collect();
+        if !bit_ops.is_empty() {
+            json = json.with("bit_ops", Json::object().with("applied", bit_ops).with("value", num));
+        }
+        for (label, ratio) in &ratios {
+            json = json.with(&label.to_lowercase(), ratio.as_str());
+        }
+        if let Some((of, power)) = complement {
+            json = json.with("complement", Json::object().with("of", of).with("power", power).with("value", num));
+        }
+        if let Some((distance, xor)) = hamming {
+            json = json.with("hamming", Json::object().with("distance", distance).with("xor", xor));
+        }
+        if let Some(parity) = &opt.parity {
+            let mut parity_json = Json::object().with("scheme", parity.as_str()).with("bits_set", num.count_ones());
+            parity_json = match parity_ok {
+                Some(ok) => parity_json.with("ok", ok),
+                None     => parity_json.with("parity_bit", bits::parity_bit(num, odd_parity(opt))),
+            };
+            json = json.with("parity", parity_json);
+        } else if let Some(ok) = parity_ok {
+            json = json.with("parity", Json::object().with("scheme", "even").with("ok", ok));
+        }
+        writeln!(out, "{}", json)?;
+        return passed_checks(&[luhn_valid, parity_ok]);
+    }
+
+    if let Some(format) = table_format(opt) {
+        if !opt.silent {
+            writeln!(out, "{}", table(opt, format, &raw_input, from_base, num, frac, &targets)?)?;
+        }
+        return Ok(());
+    }
+
+    // Print conversions
+    let mut cells = vec![raw_input.clone()];
+    for &custom_base in &targets {
+        let (int, frac_str) = with_fraction(opt, num, frac, custom_base);
+        if opt.abbrev_only {
+            if !opt.silent {
+                if opt.color_blind {
+                    write!(out, "{} ", base_symbol(custom_base))?;
+                }
+                if !opt.bare {
+                    write!(out, "Base {:02}: ", &custom_base)?;
+                }
+                writeln!(out, "{}", abbreviated(opt, int, custom_base))?;
+            }
+            continue;
+        }
+        let mut out_str = match as_string_base(&int, custom_base) {
+            Ok(v)  => v,
+            Err(e) => {
+                eprintln!("Error with custom base:\n\t{}", e);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        };
+        out_str = pad_digits(opt, out_str, custom_base);
+        if opt.verbosity >= 3 {
+            eprintln!("Base {} digits:", custom_base);
+            log_digits(&out_str, custom_base);
+        }
+
+        if !opt.silent {
+            out_str = separate(opt, &out_str, custom_base);
+            if opt.subscript || opt.superscript {
+                let convert = if opt.subscript { to_subscript_str } else { to_superscript_str };
+                let mut groups = Vec::new();
+                for group in out_str.split(opt.sep_char) {
+                    groups.push(match convert(group) {
+                        Ok(v)  => v,
+                        Err(_) if opt.keep_letters => group.chars()
+                            .map(|c| convert(&c.to_string()).unwrap_or_else(|_| c.to_string()))
+                            .collect(),
+                        Err(e) => {
+                            eprintln!("{} (use --keep-letters to print it as-is)", e);
+                            return Err(ErrorCode::TargetBaseErr);
+                        },
+                    });
+                }
+                out_str = groups.join(&opt.sep_char.to_string());
+            }
+            if let Some(n) = opt.truncate_at {
+                out_str = truncate_repr(&out_str, n);
+            }
+            out_str.push_str(&frac_str);
+            if opt.markdown {
+                cells.push(out_str);
+                continue;
+            }
+            if opt.color_blind {
+                write!(out, "{} ", base_symbol(custom_base))?;
+            }
+            if !opt.bare {
+                write!(out, "Base {:02}: ", &custom_base)?;
+            }
+            writeln!(out, "{}", out_str)?;
+            if prints_extras(opt) {
+                print_target_extras(opt, &raw_input, from_base, num, int, custom_base, out)?;
+            }
+        }
+    }
+
+    if opt.markdown {
+        // Only the table, without the extra lines
+        if !opt.silent {
+            writeln!(out, "{}", markdown_row(&cells))?;
+        }
+        return Ok(());
+    }
+
+    if opt.morse && prints_extras(opt) {
+        if !opt.bare {
+            write!(out, "Morse: ")?;
+        }
+        writeln!(out, "{}", number_to_morse(num))?;
+    }
+
+    if opt.unary && prints_extras(opt) {
+        let ones = unary(num)?;
+        if !opt.bare {
+            write!(out, "Unary: ")?;
+        }
+        writeln!(out, "{}", ones)?;
+    }
+
+    if let (Some((of, power)), true, false) = (complement, prints_extras(opt), opt.bare) {
+        writeln!(out, "{} \u{2192} {} (complement: {})", of, power, num)?;
+    }
+
+    if prints_extras(opt) {
+        for (label, ratio) in &ratios {
+            if !opt.bare {
+                write!(out, "{}: ", label)?;
+            }
+            writeln!(out, "{}", ratio)?;
+        }
+    }
+
+    if opt.width_needed && prints_extras(opt) {
+        print_width_needed(opt, num, &targets, out)?;
+    }
+
+    if opt.alignment && prints_extras(opt) {
+        print_alignment(opt, num, out)?;
+    }
+
+    if opt.metric_prefix && prints_extras(opt) {
+        for (si, label) in metric_prefixes(opt) {
+            if !opt.bare {
+                write!(out, "{} prefix: ", label)?;
+            }
+            writeln!(out, "{}", format_metric_prefix(num, si))?;
+        }
+    }
+
+    if let Some(format) = opt.fixed_point {
+        if prints_extras(opt) {
+            if !opt.bare {
+                write!(out, "Fixed-point {}: ", format)?;
+            }
+            writeln!(out, "{}", format.format(num))?;
+        }
+    }
+
+    if let (Some(encoding), Some(width)) = (chosen_encoding(opt), final_width(opt)) {
+        if prints_extras(opt) {
+            let (negative, magnitude) = encoding.decode(num, width);
+            if !opt.bare {
+                write!(out, "{}: ", encoding.description())?;
+            }
+            writeln!(out, "{}", encoding::format_signed(negative, magnitude))?;
+        }
+    }
+
+    if is_hex_float_input(opt, &input, from_base) && prints_extras(opt) {
+        print_nearest_floats(opt, &input, out)?;
+    }
+
+    if opt.interpret && prints_extras(opt) {
+        print_interpretations(opt, num, out)?;
+    }
+
+    if opt.stats && prints_extras(opt) {
+        print_stats(opt, num, out)?;
+    }
+
+    if opt.pow2 && prints_extras(opt) {
+        print_pow2(opt, num, out)?;
+    }
+
+    if opt.factor && prints_extras(opt) {
+        print_factors(opt, num, &targets, out)?;
+    }
+
+    if opt.luhn.is_some() && prints_extras(opt) {
+        let result = match luhn_valid {
+            Some(valid) => (if valid { "valid" } else { "invalid" }).to_string(),
+            None        => (num % 10).to_string(),
+        };
+        if !opt.bare {
+            write!(out, "{}: ", if luhn_valid.is_some() { "Luhn" } else { "Luhn check digit" })?;
+        }
+        writeln!(out, "{}", result)?;
+    }
+
+    if let (Some((distance, xor)), true) = (hamming, prints_extras(opt)) {
+        let mut xors = Vec::new();
+        for &base in &targets {
+            xors.push((base, as_string_base(&xor, base).map_err(|_| ErrorCode::TargetBaseErr)?));
+        }
+        if opt.bare {
+            // The distance, then the XOR in each base on a line of its own
+            writeln!(out, "{}", distance)?;
+            for (_, xor) in xors {
+                writeln!(out, "{}", xor)?;
+            }
+        } else {
+            let xors: Vec<String> = xors.iter().map(|(base, xor)| format!("{}:{}", base, xor)).collect();
+            writeln!(out, "Hamming distance: {} (XOR {})", distance, xors.join(" "))?;
+        }
+    }
+
+    if (opt.parity.is_some() || parity_ok.is_some()) && prints_extras(opt) {
+        print_parity(opt, num, parity_ok, out)?;
+    }
+    passed_checks(&[luhn_valid, parity_ok])
+}
+
+/// Whether to print the lines beyond the values themselves: not with
+/// `--silent`, and not with `--only`, which prints just the one value
+fn prints_extras(opt: &Opt) -> bool {
+    !opt.silent && opt.only.is_none()
+}
+
+/// The extra lines under the value in `base`, for `--decompose`, `--entropy`,
+/// `--abbrev`, `--digit-freq`, `--repeat` and `--explain`. `--bare` drops
+/// their labels and indents.
+fn print_target_extras(opt: &Opt, raw_input: &str, from_base: u32, num: u128, int: u128, base: u32,
+                       out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let indent = if opt.bare { "" } else { "  " };
+    let label = |label: &str| if opt.bare { String::new() } else { format!("  {}: ", label) };
+    if opt.decompose {
+        let values = opt.decompose_style == "values";
+        let decomposition = format_decomposition(&decompose(num, base), base, values, opt.nonzero_only);
+        writeln!(out, "{}{}", if opt.bare { "" } else { "  = " }, decomposition)?;
+    }
+    if opt.entropy {
+        let entropy = stats::shannon_entropy(&as_string_base(&int, base).unwrap_or_default());
+        writeln!(out, "{}{:.2}{}", label("Entropy"), entropy, if opt.bare { "" } else { " bits" })?;
+    }
+    if opt.abbrev {
+        writeln!(out, "{}{}", label("Abbreviated"), abbreviated(opt, int, base))?;
+    }
+    if opt.digit_freq {
+        let digits = as_string_base(&int, base).unwrap_or_default();
+        let counts: Vec<String> = stats::digit_frequencies(&digits, base, opt.all_digits).iter()
+            .map(|(digit, count)| format!("{}:{}", digit, count))
+            .collect();
+        if opt.bare {
+            writeln!(out, "{}", counts.join(" "))?;
+        } else {
+            writeln!(out, "  Digits: {} ({} total)", counts.join(" "), digits.len())?;
+        }
+    }
+    if let Some(n) = opt.repeat {
+        let values = repeat_conversion(raw_input, from_base, base, n, opt.sep_char);
+        let result = match repeat_mismatch(raw_input, from_base, base, &values) {
+            _ if values.len() < n as usize => format!("failed to convert at step {}", values.len() + 1),
+            Some((step, expected, got))    => format!("changed at step {}, expected {} but got {}", step, expected, got),
+            None                           => "stable".to_string(),
+        };
+        writeln!(out, "{}{}", if opt.bare { String::new() } else { format!("  repeat x{}: ", n) }, result)?;
+    }
+    if opt.explain {
+        if !opt.bare {
+            writeln!(out, "{} in base 10 to base {}:", num, base)?;
+        }
+        for line in elide_steps(explain_from_base_10(num, base)) {
+            writeln!(out, "{}{}", indent, line)?;
+        }
+    }
+    Ok(())
+}
+
+/// `--luhn`: `check` leaves the value as it is and says whether its last
+/// digit is the Luhn check digit, `append` adds the check digit to the end
+fn luhn(opt: &Opt, mode: &str, num: u128, from_base: u32, fraction: bool) -> Result<(u128, Option<bool>), ErrorCode> {
+    if from_base != 10 || fraction {
+        eprintln!("--luhn needs a whole number in base 10, not base {}", from_base);
+        return Err(ErrorCode::InputBaseErr);
+    }
+    if mode == "check" {
+        let valid = checksum::luhn_valid(num);
+        verbose!(opt, 2, "Luhn check:        {}", if valid { "valid" } else { "invalid" });
+        return Ok((num, Some(valid)));
+    }
+    let digit = checksum::luhn_check_digit(num);
+    match num.checked_mul(10).and_then(|n| n.checked_add(u128::from(digit))) {
+        Some(v) => {
+            verbose!(opt, 2, "Luhn check digit:  {}", digit);
+            Ok((v, None))
+        },
+        None => {
+            eprintln!("{} with a check digit appended is too large", num);
+            Err(ErrorCode::OverflowErr)
+        },
+    }
+}
+
+/// `--hamming`: the number of bits `num` and `other` differ in, and their
+/// XOR. The shorter value is zero extended, and both must fit in `--width`.
+fn hamming(opt: &Opt, num: u128, other: &str, from_base: u32) -> Result<(u32, u128), ErrorCode> {
+    let (other_num, _) = parse_line_with_prefix(other, from_base)?;
+    if let Some(width) = final_width(opt) {
+        if !bits::fits_in_width(other_num, width) {
+            eprintln!("--hamming {} doesn't fit in {} bits", other, width);
+            return Err(ErrorCode::WidthErr);
+        }
+    }
+    let xor = num ^ other_num;
+    verbose!(opt, 2, "XOR with {}:   0x{:X}", other_num, xor);
+    Ok((xor.count_ones(), xor))
+}
+
+/// The result of a conversion that ran checks, which fails when any did
+fn passed_checks(checks: &[Option<bool>]) -> Result<(), ErrorCode> {
+    if checks.contains(&Some(false)) {
+        Err(ErrorCode::CheckErr)
+    } else {
+        Ok(())
+    }
+}
+
+fn odd_parity(opt: &Opt) -> bool {
+    opt.parity.as_deref() == Some("odd")
+}
+
+/// `--check-parity`: whether the low `width` bits of `num`, the top one the
+/// parity bit, match the `--parity` scheme
+fn check_parity(opt: &Opt, num: u128, width: u32) -> Result<bool, ErrorCode> {
+    if width == 0 || width > 128 || !bits::fits_in_width(num, width) {
+        eprintln!("--check-parity {} needs a width of 1 to 128 bits that holds {}", width, num);
+        return Err(ErrorCode::WidthErr);
+    }
+    Ok(bits::check_parity(num, width, odd_parity(opt)))
+}
+
+/// The parity bit `num` needs, or with `--check-parity` whether it has the
+/// right one
+fn print_parity(opt: &Opt, num: u128, parity_ok: Option<bool>, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let scheme = if odd_parity(opt) { "Odd" } else { "Even" };
+    match parity_ok {
+        Some(ok) => {
+            if !opt.bare {
+                write!(out, "{} parity: ", scheme)?;
+            }
+            writeln!(out, "{}", if ok { "ok" } else { "error" })?;
+        },
+        None => {
+            if !opt.bare {
+                write!(out, "{} parity bit: ", scheme)?;
+            }
+            writeln!(out, "{} ({} bits set)", bits::parity_bit(num, odd_parity(opt)), num.count_ones())?;
+        },
+    }
+    Ok(())
+}
+
+/// The value in each target base as a `table_format` table
+fn table(opt: &Opt, format: OutputFormat, raw_input: &str, from_base: u32, num: u128, frac: Option<(u128, u128)>,
+         targets: &[u32]) -> Result<String, ErrorCode> {
+    let mut results = Vec::new();
+    for &base in targets {
+        let (int, frac_str) = with_fraction(opt, num, frac, base);
+        let digits = as_string_base(&int, base).map_err(|e| {
+            eprintln!("Error with custom base:\n\t{}", e);
+            ErrorCode::InputBaseErr
+        })?;
+        let digits = pad_digits(opt, digits, base);
+        // Like JSON, XML is for programs, so its values have no separators
+        let digits = if format == OutputFormat::Xml { digits } else { separate(opt, &digits, base) };
+        results.push((base, digits + &frac_str));
+    }
+    Ok(match format {
+        OutputFormat::MarkdownBold => format_as_markdown_table_bold(&results, raw_input, 16),
+        OutputFormat::Html => {
+            let table = format_as_html_table(&results, raw_input, opt.css_class.as_deref());
+            if opt.full_document { full_document(&table, raw_input) } else { table }
+        },
+        OutputFormat::Latex => {
+            format_as_latex_table_with_label(&results, opt.latex_caption.as_deref(), opt.latex_label.as_deref())
+        },
+        OutputFormat::Xml => format_as_xml(&results, raw_input, from_base, opt.xml_indent),
+        _ => format_as_markdown_table(&results, raw_input),
+    })
+}
+
+/// Whether `opt` asks a yes or no question, with `--test-bit` or
+/// `--aligned-to`, instead of converting
+fn is_predicate(opt: &Opt) -> bool {
+    !opt.test_bit.is_empty() || opt.aligned_to.is_some()
+}
+
+/// Print `true` or `false` for the `is_predicate` question, failing with
+/// `TestFalse` for false
+fn predicate(opt: &Opt, num: u128, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    if is_batch(opt) {
+        eprintln!("--test-bit and --aligned-to test one number, not a batch");
+        return Err(ErrorCode::InputBaseErr);
+    }
+    let result = match &opt.aligned_to {
+        Some(alignment) => is_aligned_to(num, alignment)?,
+        None            => test_bits(opt, num)?,
+    };
+    if !opt.silent {
+        writeln!(out, "{}", result)?;
+    }
+    if result { Ok(()) } else { Err(ErrorCode::TestFalse) }
+}
+
+/// `--test-bit`: whether all, or with `--any` any, of the bits are set
+fn test_bits(opt: &Opt, num: u128) -> Result<bool, ErrorCode> {
+    let width = final_width(opt).unwrap_or(128);
+    if let Some(bit) = opt.test_bit.iter().find(|&&bit| bit >= width) {
+        eprintln!("--test-bit {} is past the {} bit width", bit, width);
+        return Err(ErrorCode::WidthErr);
+    }
+    let is_set = |&bit: &u32| (num >> bit) & 1 == 1;
+    Ok(if opt.all || !opt.any { opt.test_bit.iter().all(is_set) } else { opt.test_bit.iter().any(is_set) })
+}
+
+/// `--aligned-to`: whether `num` is a multiple of `alignment`, which is in
+/// base 10 unless it has a prefix
+fn is_aligned_to(num: u128, alignment: &str) -> Result<bool, ErrorCode> {
+    match parse_line_with_prefix(alignment, 10)?.0 {
+        0 => {
+            eprintln!("--aligned-to 0 isn't an alignment");
+            Err(ErrorCode::BaseConversionErr)
+        },
+        n => Ok(num.is_multiple_of(n)),
+    }
+}
+
+/// `--alignment`: the trailing zero bits and the power of two they make
+fn print_alignment(opt: &Opt, num: u128, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    if !opt.bare {
+        write!(out, "Alignment: ")?;
+    }
+    match bits::alignment(num) {
+        Some((zeros, align)) => writeln!(out, "{} trailing zero bits, {}-byte aligned", zeros, align)?,
+        None                 => writeln!(out, "0 is aligned to every power of two")?,
+    }
+    Ok(())
+}
+
+/// `--digit-count-only`: the number of whole digits in the one target base,
+/// or `BASE:COUNT` pairs on one line for several
+fn print_digit_counts(opt: &Opt, num: u128, targets: &[u32], out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let mut counts = Vec::new();
+    for &base in targets {
+        let count = match digit_count(num, base) {
+            Ok(v)  => v,
+            Err(e) => {
+                eprintln!("Invalid target base {}, base must be between 2 and 36", base);
+                return Err(e);
+            },
+        };
+        counts.push(match targets.len() {
+            1 if opt.bare => count.to_string(),
+            1             => format!("Base {:02} digits: {}", base, count),
+            _             => format!("{}:{}", base, count),
+        });
+    }
+    writeln!(out, "{}", counts.join(" "))?;
+    Ok(())
+}
+
+/// `--width-needed`: the bits, and the digits in each target base, that hold
+/// the value, at least one of each for 0
+fn print_width_needed(opt: &Opt, num: u128, targets: &[u32], out: &mut dyn Write) -> Result<(), ErrorCode> {
+    if !opt.bare {
+        write!(out, "Bits needed: ")?;
+    }
+    writeln!(out, "{}", bits::min_width(num))?;
+    for &base in targets {
+        if !opt.bare {
+            write!(out, "Base {:02} digits needed: ", base)?;
+        }
+        writeln!(out, "{}", digit_count(num, base)?)?;
+    }
+    Ok(())
+}
+
+/// `--digit-list`: the whole part's digit values in each target base, a line
+/// per base
+fn print_digit_lists(opt: &Opt, num: u128, targets: &[u32], out: &mut dyn Write) -> Result<(), ErrorCode> {
+    for &base in targets {
+        let digits: Vec<String> = match to_digits(num, base) {
+            Ok(v)  => v.iter().map(ToString::to_string).collect(),
+            Err(e) => {
+                eprintln!("Invalid target base {}, base must be at least 2", base);
+                return Err(e);
+            },
+        };
+        let list = match &opt.digit_sep {
+            Some(sep) => digits.join(sep),
+            None      => format!("[{}]", digits.join(", ")),
+        };
+        if opt.bare {
+            writeln!(out, "{}", list)?;
+        } else {
+            writeln!(out, "Base {:02}: {}", base, list)?;
+        }
+    }
+    Ok(())
+}
+
+/// `--percent` and `--permille`, labelled, of their totals or of the largest
+/// value that fits in `--width`
+fn ratios(opt: &Opt, num: u128, from_base: u32) -> Result<Vec<(&'static str, String)>, ErrorCode> {
+    let mut ratios = Vec::new();
+    for &(label, total, permille) in [("Percent", &opt.percent, false), ("Permille", &opt.permille, true)].iter() {
+        let total = match total {
+            Some(Some(total)) => parse_line_with_prefix(total, from_base)?.0,
+            Some(None)        => bits::mask(final_width(opt).unwrap_or(128)),
+            None              => continue,
+        };
+        if total == 0 {
+            eprintln!("The --{} total can't be 0", label.to_lowercase());
+            return Err(ErrorCode::BaseConversionErr);
+        }
+        let ratio = if permille { as_permille } else { as_percent };
+        ratios.push((label, ratio(num, total, opt.ratio_precision)));
+    }
+    Ok(ratios)
+}
+
+/// `--complement`: how far `num` is below the next power of two, or of
+/// `--power2-complement-base`, with the value it was and that power
+fn complement(opt: &Opt, num: u128) -> Result<(u128, Option<(u128, u128)>), ErrorCode> {
+    let base = opt.power2_complement_base.unwrap_or(2);
+    match bits::power_complement(num, base) {
+        Some((power, complement)) => {
+            verbose!(opt, 2, "Next power of {} above {}: {}", base, num, power);
+            Ok((complement, Some((num, power))))
+        },
+        None => {
+            eprintln!("The next power of {} above {} is more than 2^128 - 1", base, num);
+            Err(ErrorCode::OverflowErr)
+        },
+    }
+}
+
+/// The `--metric-prefix` prefixes to print, whether each is SI and its label
+fn metric_prefixes(opt: &Opt) -> Vec<(bool, &'static str)> {
+    [(true, "SI", 10), (false, "Binary", 2)].iter()
+        .filter(|&&(_, _, base)| opt.metric_base.is_none_or(|b| b == base))
+        .map(|&(si, label, _)| (si, label))
+        .collect()
+}
+
+/// `--abbrev`: the whole part of the value shortened for `base`
+fn abbreviated(opt: &Opt, int: u128, base: u32) -> String {
+    abbreviate_to(int, opt.abbrev_style, base, opt.abbrev_precision)
+}
+
+/// Warn when `--pad` is more digits than any value has in the target base
+/// with the most, as it will only add leading zeros
+fn warn_excess_padding(opt: &Opt, targets: &[u32]) {
+    if let Some(&base) = targets.iter().min() {
+        let most = to_digits(u128::MAX, base).map_or(0, |digits| digits.len());
+        if opt.pad as usize > most {
+            eprintln!("Warning: --pad {} is more than the {} digits any value has in base {}", opt.pad, most, base);
+        }
+    }
+}
+
+/// Add leading zeros for `--pad`, `--width-auto` and `--negation`
+fn pad_digits(opt: &Opt, digits: String, base: u32) -> String {
+    let mut width = opt.pad as usize;
+    if opt.width_auto {
+        width = width.max(bits::round_to_natural_width(digits.len(), base));
+    }
+    // A negation is a bit pattern of the whole width, so it shows every digit
+    // of it, ceil(width / log2(base)) of them
+    if let (true, Some(bits)) = (opt.negation, final_width(opt)) {
+        width = width.max(digit_count(bits::mask(bits), base).unwrap_or(0));
+    }
+    if digits.len() >= width {
+        digits
+    } else {
+        "0".repeat(width - digits.len()) + &digits
+    }
+}
+
+/// Split the digits after `--radix-point` off the input number. Morse and
+/// fixed-point input are left alone.
+fn split_input(opt: &Opt, from_num: Option<String>) -> (Option<String>, Option<String>) {
+    match from_num {
+        Some(raw) if !opt.from_morse && opt.from_fixed_point.is_none() => {
+            let (int, frac) = split_at_radix_point(&raw, opt.radix_point);
+            // ".5" is "0.5"
+            let int = if int.is_empty() || int == "-" { int + "0" } else { int };
+            (Some(int), frac.filter(|f| !f.is_empty()))
+        },
+        other => (other, None),
+    }
+}
+
+/// Whether `raw` is decimal scientific notation like `1.5e3`. `e` is never a
+/// decimal digit, so any base 10 input with one is.
+fn is_scientific(opt: &Opt, raw: &str, from_base: u32) -> bool {
+    from_base == 10 && !opt.from_morse && opt.from_fixed_point.is_none() && raw.contains(['e', 'E'])
+}
+
+/// Read a `NUMERATOR/DENOMINATOR` or scientific notation input as its
+/// integer part and the fraction left over, which is `None` for a whole
+/// number
+fn parse_rational_input(opt: &Opt, raw: &str, from_base: u32) -> Result<(u128, Option<(u128, u128)>), ErrorCode> {
+    let (numerator, denominator) = if raw.contains('/') {
+        parse_rational(raw, from_base, opt.sep_char)?
+    } else {
+        parse_scientific(raw, opt.radix_point, opt.sep_char)?
+    };
+    verbose!(opt, 2, "Rational input:    {}/{}", numerator, denominator);
+    let frac = Some((numerator % denominator, denominator)).filter(|_| denominator != 1);
+    Ok((numerator / denominator, frac))
+}
+
+/// An integer literal copied from source code as its digits and base, and
+/// the type its suffix gives it: Rust's like `0xFF_u32`, C's like
+/// `0xFFFFFFFFUL` or `UINT64_C(0x123)`, or just a `0x`, `0o` or `0b` prefix
+/// like `0x1F`. Warns about a value the compiler would reject for the type.
+///
+/// The prefix and suffix letters are digits in larger bases, where the
+/// input is read as a number instead.
+fn literal_input(opt: &Opt, raw: &str, from_base: u32) -> Option<(String, u32, Option<IntType>)> {
+    if from_base > 16 || opt.from_morse || opt.from_fixed_point.is_some() || is_hex_float(raw) {
+        return None;
+    }
+    let literal = match split_rust_literal(raw).or_else(|| split_c_literal(raw)) {
+        Some(v) => v,
+        None    => {
+            let prefix = raw.strip_prefix('-').unwrap_or(raw).get(..2)?.to_ascii_lowercase();
+            let letter = prefix.strip_prefix('0')?.chars().next()?;
+            if !matches!(letter, 'x' | 'o' | 'b') || letter.is_digit(from_base) {
+                return None;
+            }
+            let (digits, base) = split_base_prefix(raw, from_base).ok()?;
+            return Some((digits, base, None));
+        },
+    };
+    let base = literal.base.unwrap_or(from_base);
+    let (negative, digits) = match literal.digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None       => (false, literal.digits.as_str()),
+    };
+    let magnitude = u128::from_str_radix(digits, base).ok();
+    let (int_type, fits) = literal.int_type(negative, magnitude.unwrap_or(0));
+    if magnitude.is_some() && !fits {
+        eprintln!("Warning: {} is out of range for {}", raw, int_type);
+    }
+    Some((literal.digits, base, Some(int_type)))
+}
+
+/// `opt` with the width and signedness of a literal's type, unless
+/// `--width` was given
+fn with_int_type(opt: &Opt, int_type: IntType) -> Opt {
+    let mut literal_opt = opt.clone();
+    if opt.width.is_none() {
+        verbose!(opt, 2, "Width {} from the {} suffix", int_type.bits, int_type);
+        literal_opt.width = Some(int_type.bits);
+        literal_opt.signed = opt.signed || int_type.signed;
+    }
+    literal_opt
+}
+
+/// An input's whole part, whether it overflowed `--width`, and its
+/// fractional part if it has one
+type ParsedInput = (u128, bool, Option<(u128, u128)>);
+
+/// Whether `raw` is a hex float like `0x1.8p3`. `x` and `p` are digits from
+/// base 26 up, where it's read as a number instead.
+fn is_hex_float_input(opt: &Opt, raw: &str, from_base: u32) -> bool {
+    from_base <= 16 && !opt.from_morse && opt.from_fixed_point.is_none() && is_hex_float(raw)
+}
+
+/// Read a hex float input as its exact value. Whole numbers go on through
+/// `parse_input` like decimal input, so negative ones need a `--width`, and
+/// anything else is split into its integer part and fraction.
+fn parse_hex_float_input(opt: &Opt, raw: &str, out: &mut dyn Write) -> Result<ParsedInput, ErrorCode> {
+    let value = HexFloat::parse(raw, opt.sep_char)?;
+    let (numerator, denominator) = match value.to_fraction() {
+        Some(v) => v,
+        None if value.exponent >= 0 => {
+            eprintln!("{}", ErrorCode::OverflowErr);
+            return Err(ErrorCode::OverflowErr);
+        },
+        None => {
+            eprintln!("The exponent of {} is too small", raw);
+            return Err(ErrorCode::OverflowErr);
+        },
+    };
+    verbose!(opt, 2, "Hex float value:   {}/{}", numerator, denominator);
+    let negative = value.negative && numerator != 0;
+    if denominator == 1 {
+        let decimal = format!("{}{}", if negative { "-" } else { "" }, numerator);
+        return parse_input(opt, Some(decimal), 10, out).map(|(num, overflowed)| (num, overflowed, None));
+    }
+    if negative || opt.width.is_some() {
+        eprintln!("Fractional input can't be {}, only whole numbers have a bit pattern",
+                  if negative { "negative" } else { "used with --width" });
+        return Err(ErrorCode::WidthErr);
+    }
+    Ok((numerator / denominator, false, Some((numerator % denominator, denominator))))
+}
+
+/// The f32 and f64 nearest a hex float input, with their bits
+fn print_nearest_floats(opt: &Opt, raw: &str, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let value = HexFloat::parse(raw, opt.sep_char)?;
+    let (single, double) = (value.to_f32(), value.to_f64());
+    if !opt.bare {
+        write!(out, "Nearest f32: ")?;
+    }
+    writeln!(out, "{} (0x{:08X})", single, single.to_bits())?;
+    if !opt.bare {
+        write!(out, "Nearest f64: ")?;
+    }
+    writeln!(out, "{} (0x{:016X})", double, double.to_bits())?;
+    Ok(())
+}
+
+/// Read the digits after the radix point as an exact fraction
+fn parse_fraction_input(opt: &Opt, digits: Option<String>, from_base: u32) -> Result<Option<(u128, u128)>, ErrorCode> {
+    let digits = match digits {
+        Some(v) => v.replace(opt.sep_char, ""),
+        None    => return Ok(None),
+    };
+    if opt.width.is_some() {
+        eprintln!("Fractional input can't be used with --width, only whole numbers have a bit pattern");
+        return Err(ErrorCode::WidthErr);
+    }
+    let frac = parse_fraction(&digits, from_base)?;
+    verbose!(opt, 2, "Fractional part:   {}/{}", frac.0, frac.1);
+    Ok(Some(frac))
+}
+
+/// The integer part to print in `base`, and the radix point and
+/// `--precision` fractional digits of `frac` rounded by `--round`. The
+/// integer part is one more than `num` when the rounding carries into it.
+///
+/// With `--as-fraction` the value is printed as `NUMERATOR/DENOMINATOR`
+/// instead, the integer returned being the numerator. With `--repeating`
+/// the whole expansion is printed, with the part that repeats in brackets,
+/// unless no end or cycle is found in `--max-period` digits.
+fn with_fraction(opt: &Opt, num: u128, frac: Option<(u128, u128)>, base: u32) -> (u128, String) {
+    let (numerator, denominator) = match frac {
+        Some(v) => v,
+        None    => return (num, String::new()),
+    };
+    if opt.as_fraction {
+        // Digits after a radix point are over a power of the input base, 0x0.8 is 8/16
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+        if denominator == 1 {
+            return (num, String::new());
+        }
+        let denominator_digits = as_string_base(&denominator, base).unwrap_or_default();
+        match num.checked_mul(denominator).and_then(|n| n.checked_add(numerator)) {
+            Some(whole) => return (whole, format!("/{}", denominator_digits)),
+            None        => verbose!(opt, 1, "The numerator is too large for --as-fraction, printing digits instead"),
+        }
+    }
+    let point = opt.output_radix_point.unwrap_or(opt.radix_point);
+    if opt.repeating {
+        match repeating_fraction_digits(numerator, denominator, base, opt.max_period) {
+            Some((prefix, cycle)) if cycle.is_empty() => {
+                return (num, format!("{}{}", point, if prefix.is_empty() { "0" } else { &prefix }));
+            },
+            Some((prefix, cycle)) => return (num, format!("{}{}({})", point, prefix, cycle)),
+            None => verbose!(opt, 1, "No repeating cycle in the first {} digits in base {}", opt.max_period, base),
+        }
+    }
+    let (digits, carry) = round_fraction_digits(numerator, denominator, base, opt.precision, opt.round);
+    (num.saturating_add(carry as u128), format!("{}{}", point, digits))
+}
+
+/// Read the input number as Morse, fixed point or digits in `from_base`,
+/// then check it against `--width`. Also returns whether it overflowed the
+/// width and was wrapped or clamped.
+fn parse_input(opt: &Opt, from_num: Option<String>, from_base: u32, out: &mut dyn Write) -> Result<(u128, bool), ErrorCode> {
+    if opt.from_morse {
+        let num = from_morse_input(from_num)?;
+        return fit_width(opt, false, num, false, false);
+    }
+    if let Some(format) = opt.from_fixed_point {
+        let num = from_fixed_point_input(from_num, format)?;
+        return fit_width(opt, false, num, false, true);
+    }
+
+    let (negative, from_num) = match from_num {
+        Some(raw) => match raw.strip_prefix('-') {
+            Some(rest) => (true, Some(rest.to_string())),
+            None       => (false, Some(raw)),
+        },
+        None => (false, None),
+    };
+    if negative && opt.width.is_none() {
+        eprintln!("Negative numbers need a --width to pick their two's complement bit pattern");
+        return Err(ErrorCode::WidthErr);
+    }
+
+    if let Some(raw) = &from_num {
+        let stripped = raw.replace(opt.sep_char, "");
+        if stripped != *raw {
+            verbose!(opt, 2, "Stripped separators: {} -> {}", raw, stripped);
+        }
+        if opt.verbosity >= 3 {
+            log_digits(&stripped, from_base);
+        }
+        if opt.explain && prints_extras(opt) && !opt.json {
+            if !opt.bare {
+                writeln!(out, "{} in base {} to base 10:", stripped, from_base)?;
+            }
+            for line in elide_steps(explain_to_base_10(&stripped, from_base)) {
+                writeln!(out, "{}{}", if opt.bare { "" } else { "  " }, line)?;
+            }
+        }
+    }
+    let magnitude = convert_to_base_10(from_num, from_base, opt.sep_char)?;
+    // Non-decimal input is a bit pattern, decimal input is a value that has
+    // to leave room for a sign bit when --signed
+    let bit_pattern = !negative && from_base != 10;
+    let signed = negative || (opt.signed && !bit_pattern);
+    fit_width(opt, negative, magnitude, signed, bit_pattern)
+}
+
+/// Apply the value operations to the parsed input, in this order:
+///
+/// 1. `--extend-to`: sign extend (with `--signed`) or zero extend from `--width`
+/// 2. `--negation`: negate in the `--encoding`
+/// 3. The `BIT_OPS`, in the order they are on the command line:
+///    rotations within the width, shifts, the masks in `from_base`, and
+///    setting, clearing and toggling single bits
+/// 4. `--reverse-bits`: reverse the order of the bits within the width
+/// 5. `--reverse-bytes`: reverse the order of the bytes within the width, or
+///    as few bytes as hold the value
+/// 6. `--crc32` or `--crc32c`: replace the value with the checksum of its bytes
+/// 7. `--mod`: replace the value with its remainder
+fn transform(opt: &Opt, num: u128, from_base: u32) -> Result<u128, ErrorCode> {
+    let mut num = num;
+    if let Some(to) = opt.extend_to {
+        let from = match opt.width {
+            Some(w) if w <= to && to <= 128 => w,
+            _ => {
+                eprintln!("--extend-to needs a --width no larger than it, and at most 128 bits");
+                return Err(ErrorCode::WidthErr);
+            },
+        };
+        num = if opt.signed { encoding(opt).extend(num, from, to) } else { num };
+        verbose!(opt, 2, "{} extended from {} to {} bits: 0x{:X}",
+                 if opt.signed { "Sign" } else { "Zero" }, from, to, num);
+    }
+    if let (true, Some(width)) = (opt.negation, final_width(opt)) {
+        num = encoding(opt).negate(num, width);
+        verbose!(opt, 2, "Negated in {} bit {}: 0x{:X}", width, encoding(opt).description(), num);
+    }
+    let width = final_width(opt).unwrap_or(128);
+    for (op, nth) in bit_op_order(opt) {
+        num = bit_op(opt, op, nth, num, width, from_base)?;
+    }
+    if let (true, Some(width)) = (opt.reverse_bits, final_width(opt)) {
+        num = bits::reverse_bits(num, width);
+        verbose!(opt, 2, "Reversed {} bits: 0x{:X}", width, num);
+    }
+    if opt.reverse_bytes {
+        let width = final_width(opt).unwrap_or_else(|| bits::min_width(num));
+        num = bits::reverse_bytes(num, width);
+        verbose!(opt, 2, "Reversed {} bytes: 0x{:X}", width.div_ceil(8), num);
+    }
+    if let Some(order) = opt.crc32.as_ref().or(opt.crc32c.as_ref()) {
+        let width = final_width(opt).unwrap_or_else(|| bits::min_width(num));
+        let bytes = bits::to_bytes(num, width, order.as_deref() == Some("be"));
+        let (name, crc) = if opt.crc32.is_some() {
+            ("CRC-32", checksum::crc32(&bytes))
+        } else {
+            ("CRC-32C", checksum::crc32c(&bytes))
+        };
+        verbose!(opt, 2, "{} of {} bytes: 0x{:08X}", name, bytes.len(), crc);
+        num = u128::from(crc);
+    }
+    if let Some(divisor) = &opt.modulo {
+        num = modulo(opt, num, divisor)?;
+    }
+    Ok(num)
+}
+
+/// `--mod`: the remainder of `num` divided by `divisor`, which is in base 10
+/// unless it has a prefix. With `--signed`, a negative value's remainder is
+/// Euclidean, so never negative, unless `--truncated-mod` gives it the sign
+/// of the value as Rust's `%` does.
+fn modulo(opt: &Opt, num: u128, divisor: &str) -> Result<u128, ErrorCode> {
+    let divisor = match parse_line_with_prefix(divisor, 10)?.0 {
+        0 => {
+            eprintln!("--mod 0 would divide by zero");
+            return Err(ErrorCode::BaseConversionErr);
+        },
+        n => n,
+    };
+    let (negative, magnitude) = match final_width(opt) {
+        Some(width) if opt.signed => encoding(opt).decode(num, width),
+        _                         => (false, num),
+    };
+    let (mut quotient, mut remainder) = (magnitude / divisor, magnitude % divisor);
+    let result = match (negative, remainder) {
+        (false, _) | (true, 0) => remainder,
+        (true, _) if opt.truncated_mod => {
+            let width = final_width(opt).unwrap_or(128);
+            encoding(opt).encode(true, remainder, width)
+        },
+        (true, _) => {
+            // -m = -(q + 1) * d + (d - m % d)
+            quotient += 1;
+            remainder = divisor - remainder;
+            remainder
+        },
+    };
+    let sign = if negative { "-" } else { "" };
+    let op = if negative && opt.truncated_mod && remainder != 0 { "-" } else { "+" };
+    verbose!(opt, 1, "{}{} = {}{} * {} {} {}", sign, magnitude, sign, quotient, divisor, op, remainder);
+    Ok(result)
+}
+
+/// The flags of step 3 of `transform`, in the order they run when
+/// `Opt::bit_op_indices` doesn't say, by their argument names
+const BIT_OPS: &[&str] = &["rotate-left", "rotate-right", "rol", "ror", "shift-left", "shift-right",
+                           "mask", "or-mask", "xor-mask", "not", "set-bit", "clear-bit", "toggle-bit"];
+
+/// The `BIT_OPS` to run, each with which of its occurrences it is
+fn bit_op_order(opt: &Opt) -> Vec<(&'static str, usize)> {
+    let mut ops: Vec<_> = BIT_OPS.iter()
+        .flat_map(|&op| (0..single_bits(opt, op).map_or(1, Vec::len)).map(move |nth| (op, nth)))
+        .collect();
+    ops.sort_by_key(|&(op, nth)| {
+        opt.bit_op_indices.iter().filter(|&&(name, _)| name == op).nth(nth).map_or(0, |&(_, i)| i)
+    });
+    ops
+}
+
+/// The bit indices of `--set-bit`, `--clear-bit` or `--toggle-bit`
+fn single_bits<'a>(opt: &'a Opt, op: &str) -> Option<&'a Vec<u32>> {
+    match op {
+        "set-bit"    => Some(&opt.set_bit),
+        "clear-bit"  => Some(&opt.clear_bit),
+        "toggle-bit" => Some(&opt.toggle_bit),
+        _            => None,
+    }
+}
+
+/// Apply the `nth` occurrence of the `BIT_OPS` flag `op`, if it was given,
+/// to `num`
+fn bit_op(opt: &Opt, op: &str, nth: usize, num: u128, width: u32, from_base: u32) -> Result<u128, ErrorCode> {
+    let mut num = num;
+    if let Some(bits) = single_bits(opt, op) {
+        let bit = bits[nth];
+        if bit >= width {
+            eprintln!("--{} {} is past the {} bit width", op, bit, width);
+            return Err(ErrorCode::WidthErr);
+        }
+        let (value, done) = match op {
+            "set-bit"   => (num | 1 << bit, "Set"),
+            "clear-bit" => (num & !(1 << bit), "Cleared"),
+            _           => (num ^ 1 << bit, "Toggled"),
+        };
+        num = value;
+        verbose!(opt, 2, "{} bit {}: 0x{:X}", done, bit, num);
+        return Ok(num);
+    }
+    match op {
+        "rotate-left"  => return rotate(opt, num, opt.rotate_left, width, Some("--rotate-left"), true),
+        "rotate-right" => return rotate(opt, num, opt.rotate_right, width, Some("--rotate-right"), false),
+        "rol"          => return rotate(opt, num, opt.rol, width, None, true),
+        "ror"          => return rotate(opt, num, opt.ror, width, None, false),
+        "shift-left"   => if let Some(shift) = opt.shift_left {
+            check_shift("--shift-left", shift, width)?;
+            num = (num << shift) & bits::mask(width);
+            verbose!(opt, 2, "Shifted left {} bits: 0x{:X}", shift, num);
+        },
+        "shift-right"  => if let Some(shift) = opt.shift_right {
+            check_shift("--shift-right", shift, width)?;
+            num >>= shift;
+            verbose!(opt, 2, "Shifted right {} bits: 0x{:X}", shift, num);
+        },
+        "not"          => if opt.not {
+            num = bits::apply_mask(num, bits::mask(width), BitOp::Not);
+            verbose!(opt, 2, "Flipped {} bits: 0x{:X}", width, num);
+        },
+        _              => {
+            let (operand, op) = match op {
+                "mask"    => (&opt.mask, BitOp::And),
+                "or-mask" => (&opt.or_mask, BitOp::Or),
+                _         => (&opt.xor_mask, BitOp::Xor),
+            };
+            if let Some(operand) = operand {
+                let mask = parse_mask(opt, operand, from_base)?;
+                num = bits::apply_mask(num, mask, op);
+                verbose!(opt, 2, "{:?} with 0x{:X}: 0x{:X}", op, mask, num);
+            }
+        },
+    }
+    Ok(num)
+}
+
+/// Rotate `num` by `shift`, if there is one, checking it is less than the
+/// width for `flag` and otherwise taking it modulo the width
+fn rotate(opt: &Opt, num: u128, shift: Option<u32>, width: u32, flag: Option<&str>, left: bool) -> Result<u128, ErrorCode> {
+    let shift = match shift {
+        Some(shift) => shift,
+        None        => return Ok(num),
+    };
+    if let Some(flag) = flag {
+        check_shift(flag, shift, width)?;
+    }
+    let num = if left { bits::rotate_left(num, shift, width) } else { bits::rotate_right(num, shift, width) };
+    verbose!(opt, 2, "Rotated {} {} bits: 0x{:X}", if left { "left" } else { "right" }, shift % width, num);
+    Ok(num)
+}
+
+fn check_shift(flag: &str, shift: u32, width: u32) -> Result<(), ErrorCode> {
+    if width == 0 || width > 128 || shift >= width {
+        eprintln!("{} {} must be less than the {} bit width", flag, shift, width);
+        return Err(ErrorCode::WidthErr);
+    }
+    Ok(())
+}
+
+/// A `--mask` operand, in `from_base` unless it has a prefix like `0x`
+fn parse_mask(opt: &Opt, operand: &str, from_base: u32) -> Result<u128, ErrorCode> {
+    let (digits, base) = match literal_input(opt, operand, from_base) {
+        Some((digits, base, _)) => (digits, base),
+        None                    => (operand.to_string(), from_base),
+    };
+    convert_to_base_10(Some(digits), base, opt.sep_char)
+}
+
+/// The encoding picked with `--encoding` or one of its shorthands
+fn chosen_encoding(opt: &Opt) -> Option<Encoding> {
+    if let Some(bias) = opt.excess {
+        Some(Encoding::Excess(bias))
+    } else if opt.ones_complement {
+        Some(Encoding::OnesComplement)
+    } else if opt.sign_magnitude {
+        Some(Encoding::SignMagnitude)
+    } else {
+        opt.encoding
+    }
+}
+
+fn encoding(opt: &Opt) -> Encoding {
+    chosen_encoding(opt).unwrap_or(Encoding::TwosComplement)
+}
+
+/// The width the value ends up in, after `--extend-to`
+fn final_width(opt: &Opt) -> Option<u32> {
+    opt.extend_to.or(opt.width)
+}
+
+fn overflow_mode(opt: &Opt) -> OverflowMode {
+    if opt.allow_overflow {
+        OverflowMode::Wrap
+    } else {
+        opt.on_overflow
+    }
+}
+
+/// Check the value against `--width`, returning its bit pattern and whether
+/// it overflowed.
+///
+/// Values that don't fit are handled by `--on-overflow`: an error, or a
+/// warning and wrapped or clamped to the width. A `bit_pattern` is stored
+/// as-is; only values are put through the `--encoding`.
+fn fit_width(opt: &Opt, negative: bool, magnitude: u128, signed: bool, bit_pattern: bool)
+             -> Result<(u128, bool), ErrorCode> {
+    let width = match opt.width {
+        Some(w) => w,
+        None    => return Ok((magnitude, false)),
+    };
+    if !(1..=128).contains(&width) {
+        eprintln!("Width must be between 1 and 128 bits, not {}", width);
+        return Err(ErrorCode::WidthErr);
+    }
+
+    let mode = overflow_mode(opt);
+    let encoding = if bit_pattern { Encoding::TwosComplement } else { encoding(opt) };
+    let overflowed = !encoding.fits(negative, magnitude, signed, width);
+    if overflowed {
+        let warning = if mode == OverflowMode::Error { "" } else { "Warning: " };
+        let sign = if negative { "-" } else { "" };
+        if encoding.below_range(negative, magnitude) {
+            eprintln!("{}{}{} is below the {} range at any width", warning, sign, magnitude, encoding);
+        } else {
+            let needed = encoding.required_width(negative, magnitude, signed);
+            eprintln!("{}{}{} needs at least {} bits {}and does not fit in {} bits",
+                      warning, sign, magnitude, needed,
+                      if signed { "as a signed value " } else { "" }, width);
+        }
+        match mode {
+            OverflowMode::Error    => return Err(ErrorCode::WidthErr),
+            OverflowMode::Wrap     => verbose!(opt, 1, "Wrapping to the low {} bits", width),
+            OverflowMode::Saturate => {
+                let raw = encoding.saturate(negative, magnitude, negative || opt.signed, width);
+                verbose!(opt, 1, "Clamping to 0x{:X}", raw);
+                return Ok((raw, true));
+            },
+        }
+    }
+
+    let raw = encoding.encode(negative, magnitude, width);
+    if negative || raw != magnitude {
+        verbose!(opt, 2, "Encoded {}{} as {} bit {} 0x{:X}",
+                 if negative { "-" } else { "" }, magnitude, width, encoding.description(), raw);
+    }
+    Ok((raw, overflowed))
+}
+
+/// What `--json` prints for an input that failed to convert
+fn json_error(input: &str, from_base: u32, e: ErrorCode) -> Json {
+    Json::object()
+        .with("input", input)
+        .with("from_base", from_base)
+        .with("ok", false)
+        .with("error", format!("{:?}", e))
+}
+
+/// Write a `json_error` to `out`, or to stderr with `--errors-to-stderr`
+fn write_json_error(opt: &Opt, out: &mut dyn Write, error: Json) -> Result<(), ErrorCode> {
+    if opt.errors_to_stderr {
+        eprintln!("{}", error);
+    } else {
+        writeln!(out, "{}", error)?;
+    }
+    Ok(())
+}
+
+/// Everything `--json` prints for one conversion
+fn conversion_json(opt: &Opt, raw_input: &str, from_base: u32, num: u128, frac: Option<(u128, u128)>,
+                   targets: &[u32]) -> Result<Json, ErrorCode> {
+    let mut results = Vec::new();
+    for &base in targets {
+        let (int, frac_str) = with_fraction(opt, num, frac, base);
+        let value = match as_string_base(&int, base) {
+            Ok(v)  => v,
+            Err(e) => {
+                eprintln!("Error with custom base:\n\t{}", e);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        };
+        results.push((base, pad_digits(opt, value, base) + &frac_str));
+    }
+    let conversion = ConversionResult { input: raw_input.to_string(), from_base, decimal: num, results };
+
+    let mut json = json::conversion_json(&conversion, |base, mut result| {
+        let (int, _) = with_fraction(opt, num, frac, base);
+        if opt.decompose {
+            let values = opt.decompose_style == "values";
+            result = result.with("decomposition",
+                                 format_decomposition(&decompose(num, base), base, values, opt.nonzero_only));
+        }
+        if opt.abbrev || opt.abbrev_only {
+            result = result.with("abbreviated", abbreviated(opt, int, base));
+        }
+        if opt.digit_freq {
+            let digits = as_string_base(&int, base).unwrap_or_default();
+            let counts = stats::digit_frequencies(&digits, base, opt.all_digits).iter()
+                .fold(Json::object(), |counts, &(digit, count)| counts.with(&digit.to_string(), count));
+            result = result.with("digit_freq", counts).with("digit_total", digits.len());
+        }
+        result
+    });
+    if opt.morse {
+        json = json.with("morse", number_to_morse(num));
+    }
+    if opt.unary {
+        json = json.with("unary", unary(num)?);
+    }
+    if opt.width_needed {
+        let mut digits = Vec::new();
+        for &base in targets {
+            digits.push(Json::object().with("base", base).with("digits", digit_count(num, base)?));
+        }
+        json = json.with("width_needed", Json::object().with("bits", bits::min_width(num)).with("digits", digits));
+    }
+    if opt.alignment {
+        json = json.with("alignment", match bits::alignment(num) {
+            Some((zeros, align)) => Json::object().with("trailing_zeros", zeros).with("aligned_to", align),
+            None                 => Json::Null,
+        });
+    }
+    if opt.metric_prefix {
+        json = json.with("metric_prefix", metric_prefixes(opt).into_iter().fold(Json::object(), |json, (si, _)| {
+            json.with(if si { "si" } else { "binary" }, format_metric_prefix(num, si))
+        }));
+    }
+    if let Some(format) = opt.fixed_point {
+        json = json.with("fixed_point", Json::object()
+            .with("format", format.to_string())
+            .with("value", format.format(num)));
+    }
+    if opt.interpret {
+        let widths: Vec<Json> = bits::COMMON_WIDTHS.iter().map(|&width| {
+            let reading = bits::interpret(num, width);
+            Json::object()
+                .with("width", width)
+                .with("unsigned", reading.map(|r| r.0))
+                .with("signed", reading.map(|r| r.1))
+        }).collect();
+        json = json.with("interpret", widths);
+    }
+    if opt.stats {
+        let factorable = num < stats::FACTOR_LIMIT;
+        json = json.with("stats", Json::object()
+            .with("digit_sum", stats::digit_sum(num))
+            .with("digital_root", stats::digital_root(num))
+            .with("even", stats::is_even(num))
+            .with("perfect_square", stats::is_perfect_square(num))
+            .with("perfect_cube", stats::is_perfect_cube(num))
+            .with("largest_prime_factor", if factorable { stats::largest_prime_factor(num) } else { None })
+            .with("euler_totient", if factorable { Some(stats::euler_totient(num)) } else { None }));
+    }
+    if opt.pow2 {
+        json = json.with("pow2", pow2_json(num));
+    }
+    if opt.factor {
+        let factors = factors(opt, num).map(|factors| {
+            factors.into_iter()
+                .map(|(p, exp)| Json::object().with("factor", p).with("exponent", exp))
+                .collect::<Vec<_>>()
+        });
+        json = json.with("factor", Json::object()
+            .with("prime", factor::is_prime(num))
+            .with("timed_out", factors.is_none())
+            .with("factors", factors));
+    }
+    Ok(json)
+}
+
+/// `--factor`: the prime factors of `num`, or `None` if there wasn't time
+/// to find them in `--factor-timeout-ms`
+fn factors(opt: &Opt, num: u128) -> Option<Vec<(u128, u32)>> {
+    let factors = factor::factorize(num, Instant::now() + Duration::from_millis(opt.factor_timeout_ms));
+    if factors.is_none() {
+        verbose!(opt, 1, "Gave up factoring {} after {} ms", num, opt.factor_timeout_ms);
+    }
+    factors
+}
+
+/// `--factor`: whether the value is prime, and if not its factorization in
+/// each target base, like `2^3 * 3`
+fn print_factors(opt: &Opt, num: u128, targets: &[u32], out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let prime = factor::is_prime(num);
+    let label = |label: &str| if opt.bare { String::new() } else { format!("{}: ", label) };
+    writeln!(out, "{}{}", label("Prime"), if prime { "yes" } else { "no" })?;
+    if prime {
+        return Ok(());
+    }
+    if num < 2 {
+        let zero = if num == 0 && !opt.bare { ", 0 is a multiple of every prime" } else { "" };
+        writeln!(out, "{}none{}", label("Factors"), zero)?;
+        return Ok(());
+    }
+    let factors = match factors(opt, num) {
+        Some(factors) => factors,
+        None          => {
+            if opt.bare {
+                writeln!(out, "timeout")?;
+            } else {
+                writeln!(out, "Factors: too large to factor in {} ms", opt.factor_timeout_ms)?;
+            }
+            return Ok(());
+        },
+    };
+    for &base in targets {
+        let mut terms = Vec::new();
+        for &(p, exp) in &factors {
+            let digits = as_string_base(&p, base).map_err(|e| {
+                eprintln!("Error with custom base:\n\t{}", e);
+                ErrorCode::InputBaseErr
+            })?;
+            terms.push(if exp == 1 { digits } else { format!("{}^{}", digits, exp) });
+        }
+        writeln!(out, "{}{}", label(&format!("Base {:02} factors", base)), terms.join(" * "))?;
+    }
+    Ok(())
+}
+
+fn pow2_json(num: u128) -> Json {
+    let info = bits::pow2_info(num);
+    let neighbour = |exp: Option<u32>| exp.map(|e| {
+        let value: u128 = 1 << e;
+        Json::object()
+            .with("exponent", e)
+            .with("value", value)
+            .with("hex", format!("0x{:X}", value))
+            .with("difference", value.max(num) - value.min(num))
+    });
+    Json::object()
+        .with("is_power_of_two", info.is_power_of_two)
+        .with("below", neighbour(info.below))
+        .with("above", neighbour(info.above))
+}
+
+/// `--pow2`: whether the value is a power of two, and the powers of two on
+/// either side when it isn't. `--bare` puts it on one line, like `no 2^7
+/// 2^8`
+fn print_pow2(opt: &Opt, num: u128, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let info = bits::pow2_info(num);
+    if opt.bare {
+        let power = |exp: Option<u32>, none: &str| exp.map_or_else(|| none.to_string(), |e| format!("2^{}", e));
+        if info.is_power_of_two {
+            writeln!(out, "yes {}", power(info.below, "2^0"))?;
+        } else {
+            writeln!(out, "no {} {}", power(info.below, "none"), power(info.above, "2^128"))?;
+        }
+        return Ok(());
+    }
+    if info.is_power_of_two {
+        let exp = info.below.unwrap_or(0);
+        writeln!(out, "Power of two: yes, 2^{} = {} (0x{:X})", exp, num, num)?;
+        return Ok(());
+    }
+    writeln!(out, "Power of two: no")?;
+    match info.below {
+        Some(e) => {
+            let value: u128 = 1 << e;
+            writeln!(out, "  Below: 2^{} = {} (0x{:X}), {} less", e, value, value, num - value)?;
+        },
+        None => writeln!(out, "  Below: none")?,
+    }
+    match info.above {
+        Some(e) => {
+            let value: u128 = 1 << e;
+            writeln!(out, "  Above: 2^{} = {} (0x{:X}), {} more", e, value, value, value - num)?;
+        },
+        None => writeln!(out, "  Above: 2^128, {} more (beyond u128)", u128::MAX - num + 1)?,
+    }
+    Ok(())
+}
+
+fn from_fixed_point_input(from_num: Option<String>, format: FixedPointFormat) -> Result<u128, ErrorCode> {
+    match from_num {
+        Some(v) => format.parse(&v).map_err(|e| {
+            eprintln!("{}", e);
+            ErrorCode::BaseConversionErr
+        }),
+        None    => {
+            eprintln!("no number to convert was provided");
+            Err(ErrorCode::InputBaseErr)
+        },
+    }
+}
+
+/// `--interpret`: the value read as unsigned and signed at each common
+/// width. `--bare` drops the header and the column padding
+fn print_interpretations(opt: &Opt, num: u128, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    if !opt.bare {
+        writeln!(out, "Width  {:<40}  Signed", "Unsigned")?;
+    }
+    for &width in bits::COMMON_WIDTHS.iter() {
+        match (bits::interpret(num, width), opt.bare) {
+            (Some((unsigned, signed)), true)  => writeln!(out, "{} {} {}", width, unsigned, signed)?,
+            (Some((unsigned, signed)), false) => writeln!(out, "{:<5}  {:<40}  {}", width, unsigned, signed)?,
+            (None, true)                      => writeln!(out, "{} overflow", width)?,
+            (None, false)                     => writeln!(out, "{:<5}  overflow", width)?,
+        }
+    }
+    Ok(())
+}
+
+/// `--stats`: a line for each property of the value, just the property with
+/// `--bare`
+fn print_stats(opt: &Opt, num: u128, out: &mut dyn Write) -> Result<(), ErrorCode> {
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    let mut stats = vec![
+        ("Digit sum:           ", stats::digit_sum(num).to_string()),
+        ("Digital root:        ", stats::digital_root(num).to_string()),
+        ("Parity:              ", (if stats::is_even(num) { "even" } else { "odd" }).to_string()),
+        ("Perfect square:      ", yes_no(stats::is_perfect_square(num)).to_string()),
+        ("Perfect cube:        ", yes_no(stats::is_perfect_cube(num)).to_string()),
+    ];
+    if num < stats::FACTOR_LIMIT {
+        let largest = stats::largest_prime_factor(num).map_or_else(|| "none".to_string(), |p| p.to_string());
+        stats.push(("Largest prime factor:", largest));
+        stats.push(("Euler totient:       ", stats::euler_totient(num).to_string()));
+    } else {
+        eprintln!("Warning: skipping prime factor and totient, {} is not below 10^15", num);
+    }
+    for (label, value) in stats {
+        if !opt.bare {
+            write!(out, "{} ", label)?;
+        }
+        writeln!(out, "{}", value)?;
+    }
+    Ok(())
+}
+
+/// Print each digit of `digits` with its place value to stderr
+fn log_digits(digits: &str, base: u32) {
+    let len = digits.chars().count() as u32;
+    for (i, ch) in digits.chars().enumerate() {
+        let place = len - 1 - i as u32;
+        match ch.to_digit(base) {
+            Some(d) => eprintln!("  '{}' = {} * {}^{}", ch, d, base, place),
+            None    => eprintln!("  '{}' is not a digit in base {}", ch, base),
+        }
+    }
+}
+
+fn get_bases(opt: &Opt, aliases: &HashMap<String, u32>, to_bases: &mut Vec<String>) -> (u32, Option<String>) {
+    to_bases.extend(opt.to.iter().cloned());
+    let base_char = match &opt.from_base_char {
+        Some(v) => v,
+        None    => return (opt.from_base, None),
+    };
+    match resolve_base(base_char, aliases) {
+        Some(v) => (v, opt.from_num.clone()),
+        None => {
+            // No base_char. Push from_num to the bases Vec, push base_char to from_num.
+            if let Some(a_base) = &opt.from_num {
+                to_bases.insert(0, a_base.clone());
+            }
+            // base_char wasn't provided, use the `-b` flag value as the base.
+            (opt.from_base, Some(base_char.clone()))
+        },
+    }
+}
+
+fn unary(num: u128) -> Result<String, ErrorCode> {
+    as_string_base_unary(num).map_err(|e| {
+        eprintln!("Error with --unary:\n\t{}", e);
+        ErrorCode::OverflowErr
+    })
+}
+
+fn from_morse_input(from_num: Option<String>) -> Result<u128, ErrorCode> {
+    match from_num {
+        Some(v) => morse_to_number(&v),
+        None    => {
+            eprintln!("no number to convert was provided");
+            Err(ErrorCode::InputBaseErr)
+        },
+    }
+}
+
+
+#[derive(StructOpt, Debug, Clone)]
+#[structopt(name = "numconverter", about = "A CLI number conversion utility written in Rust",
+            setting = AppSettings::AllowNegativeNumbers)]
+struct Opt {
+    /// Pad the output with leading 0s
+    #[structopt(short, long, default_value = "0")]
+    pad: u16,
+
+    /// Pad the output with leading 0s up to the next 8, 16, 32, 64 or 128 bit width
+    #[structopt(long)]
+    width_auto: bool,
+
+    /// Show only the first N digits of long output, then `...` and how many were left out.
+    /// Applies after padding
+    #[structopt(long, value_name = "N")]
+    truncate_at: Option<usize>,
+
+    /// Bit width of the value. Inputs that don't fit are an error
+    #[structopt(short, long)]
+    width: Option<u32>,
+
+    /// Treat decimal input as a signed value, so it needs room for a sign bit in --width.
+    /// Negative input is always signed; other bases are read as bit patterns
+    #[structopt(long)]
+    signed: bool,
+
+    /// Warn instead of failing when the input doesn't fit in --width, and wrap it.
+    /// Same as --on-overflow wrap
+    #[structopt(long)]
+    allow_overflow: bool,
+
+    /// What to do when the input doesn't fit in --width: error, wrap to the low bits,
+    /// or saturate to the largest/smallest value
+    #[structopt(long, default_value = "error", possible_values = &["error", "wrap", "saturate"])]
+    on_overflow: OverflowMode,
+
+    /// How negative input is stored in --width bits: twos, ones (ones' complement) or
+    /// sign-magnitude. Also prints the value the bit pattern stands for, including -0
+    #[structopt(long, requires = "width", possible_values = &["twos", "ones", "sign-magnitude"],
+                conflicts_with_all = &["ones-complement", "sign-magnitude"])]
+    encoding: Option<Encoding>,
+
+    /// Same as --encoding ones. With --negation, negates by flipping every bit
+    #[structopt(long, requires = "width", conflicts_with = "sign-magnitude")]
+    ones_complement: bool,
+
+    /// Same as --encoding sign-magnitude
+    #[structopt(long, requires = "width")]
+    sign_magnitude: bool,
+
+    /// Encode input in --width bits as excess-K (offset binary): store the value plus K,
+    /// e.g. --excess 127 for float exponents. Values outside -K to 2^width-1-K don't fit
+    #[structopt(long, requires = "width", value_name = "K",
+                conflicts_with_all = &["encoding", "ones-complement", "sign-magnitude"])]
+    excess: Option<u128>,
+
+    /// Negate the value in --width bits, -N mod 2^width in two's complement. The
+    /// --encoding decides how, e.g. flipping every bit in ones' complement
+    #[structopt(long, requires = "width")]
+    negation: bool,
+
+    /// Extend a --width value to N bits: sign extension with --signed, zero extension
+    /// otherwise. Applied before any other operation on the value
+    #[structopt(long)]
+    extend_to: Option<u32>,
+
+    /// Rotate the --width bits of the value left by N bits, N less than the width
+    #[structopt(long, requires = "width", value_name = "N")]
+    rotate_left: Option<u32>,
+
+    /// Rotate the --width bits of the value right by N bits, N less than the width
+    #[structopt(long, requires = "width", value_name = "N")]
+    rotate_right: Option<u32>,
+
+    /// Rotate the --width bits of the value left by N bits, like a CPU's rol. N is taken
+    /// modulo the width
+    #[structopt(long, requires = "width", value_name = "N")]
+    rol: Option<u32>,
+
+    /// Rotate the --width bits of the value right by N bits, like a CPU's ror. N is taken
+    /// modulo the width
+    #[structopt(long, requires = "width", value_name = "N")]
+    ror: Option<u32>,
+
+    /// Shift the value left by N bits. Bits shifted past --width, or 128 bits, are lost
+    #[structopt(long, value_name = "N")]
+    shift_left: Option<u32>,
+
+    /// Shift the value right by N bits
+    #[structopt(long, value_name = "N")]
+    shift_right: Option<u32>,
+
+    /// AND the value with MASK, read in the input base or with a prefix like 0x
+    #[structopt(long, value_name = "MASK")]
+    mask: Option<String>,
+
+    /// OR the value with MASK
+    #[structopt(long, value_name = "MASK")]
+    or_mask: Option<String>,
+
+    /// XOR the value with MASK
+    #[structopt(long, value_name = "MASK")]
+    xor_mask: Option<String>,
+
+    /// Flip every bit of the value within --width, or 128 bits without one
+    #[structopt(long)]
+    not: bool,
+
+    /// Set bit BIT of the value, 0 being the lowest. Can be given more than once, and runs in
+    /// command-line order with --clear-bit, --toggle-bit and the other bit operations
+    #[structopt(long, value_name = "BIT", number_of_values = 1)]
+    set_bit: Vec<u32>,
+
+    /// Clear bit BIT of the value. Can be given more than once
+    #[structopt(long, value_name = "BIT", number_of_values = 1)]
+    clear_bit: Vec<u32>,
+
+    /// Flip bit BIT of the value. Can be given more than once
+    #[structopt(long, value_name = "BIT", number_of_values = 1)]
+    toggle_bit: Vec<u32>,
+
+    /// Print true and exit 0 if bit BIT of the value is set, or false and exit 1 if not,
+    /// instead of the conversions. Can be given more than once. Errors exit 2
+    #[structopt(long, value_name = "BIT", number_of_values = 1)]
+    test_bit: Vec<u32>,
+
+    /// With --test-bit, test that every bit is set. The default
+    #[structopt(long, requires = "test-bit", conflicts_with = "any")]
+    all: bool,
+
+    /// With --test-bit, test that at least one of the bits is set
+    #[structopt(long, requires = "test-bit")]
+    any: bool,
+
+    /// Print true and exit 0 if the value is a multiple of N, like an offset meeting an
+    /// alignment, or false and exit 1 if not, instead of the conversions. N is in base 10
+    /// unless it has a prefix like 0x. Errors exit 2
+    #[structopt(long, value_name = "N", conflicts_with = "test-bit")]
+    aligned_to: Option<String>,
+
+    /// Reverse the order of the --width bits of the value, so the lowest bit becomes the
+    /// highest. Not the same as reversing the bytes
+    #[structopt(long, requires = "width")]
+    reverse_bits: bool,
+
+    /// Reverse the order of the bytes of the value, as many as it takes up, so a little
+    /// endian field reads as big endian. With --width, all of its bytes, leading zero
+    /// bytes included
+    #[structopt(long)]
+    reverse_bytes: bool,
+
+    /// Put a spacer every N characters
+    #[structopt(short = "-l", long, default_value = "4")]
+    sep_length: u32,
+
+    /// Spacer lengths for particular bases, e.g. 2:8,10:3,16:4. Other bases use --sep-length
+    #[structopt(long, value_name = "BASE:LENGTH,...", parse(try_from_str = parse_group_by))]
+    group_by: Option<HashMap<u32, u32>>,
+
+    /// Specify spacer char
+    #[structopt(long, default_value = "_")]
+    sep_char: char,
+
+    /// Do not pad the output
+    #[structopt(long)]
+    no_sep: bool,
+
+    /// Char between the whole and fractional digits of the input, e.g. , for 3,14159
+    #[structopt(long, default_value = ".")]
+    radix_point: char,
+
+    /// Char between the whole and fractional digits of the output [default: --radix-point]
+    #[structopt(long)]
+    output_radix_point: Option<char>,
+
+    /// Most fractional digits to print for input with a fractional part
+    #[structopt(long, default_value = "10")]
+    precision: usize,
+
+    /// Print a value with a fractional part as NUMERATOR/DENOMINATOR in each base, e.g.
+    /// 3/7 in base 2 is 11/111
+    #[structopt(long)]
+    as_fraction: bool,
+
+    /// Print the whole fractional part with the digits that repeat in brackets, e.g.
+    /// 0.1 in base 2 is 0.0(0011)
+    #[structopt(long)]
+    repeating: bool,
+
+    /// Most fractional digits --repeating looks through for the end or a cycle before
+    /// falling back to --precision digits
+    #[structopt(long, default_value = "1000", value_name = "N")]
+    max_period: usize,
+
+    /// How to round the last fractional digit: trunc, nearest (ties away from zero), up or
+    /// down
+    #[structopt(long, default_value = "trunc", value_name = "MODE")]
+    round: Rounding,
+
+    /// Input Base, as a number or a name like hex or base36
+    ///
+    /// base_char takes precedence over this setting
+    #[structopt(short, long, visible_alias = "from", default_value = "10", parse(try_from_str = parse_base))]
+    from_base: u32,
+
+    /// Do not print output (for use with clipboard)
+    #[structopt(short, long)]
+    silent: bool,
+
+    /// Disable Pretty Print
+    #[structopt(long)]
+    bare: bool,
+
+    /// Also print the number in Morse code
+    #[structopt(long)]
+    morse: bool,
+
+    /// Also print the number in unary (base 1), as that many 1s. At most 1000
+    #[structopt(long)]
+    unary: bool,
+
+    /// Also print the fewest bits that hold the value, and the fewest digits in each target
+    /// base, for sizing fields. 0 needs 1 of each
+    #[structopt(long)]
+    width_needed: bool,
+
+    /// Convert how far the value is below the next power of two instead of the value, like
+    /// 16 for 240, which is 16 short of 256. 0 for a power of two
+    #[structopt(long)]
+    complement: bool,
+
+    /// With --complement, use the next power of B instead of two
+    #[structopt(long, value_name = "B", requires = "complement", parse(try_from_str = parse_base))]
+    power2_complement_base: Option<u32>,
+
+    /// Also print the number of trailing zero bits of the value and the largest power of two
+    /// that divides it, the alignment of an address
+    #[structopt(long)]
+    alignment: bool,
+
+    /// Also print the number as a percentage of TOTAL, e.g. --percent=255, or without one of
+    /// the largest value that fits in --width (or 128 bits). TOTAL is in the input base
+    /// unless it has a prefix like 0x
+    #[structopt(long, value_name = "TOTAL", require_equals = true)]
+    percent: Option<Option<String>>,
+
+    /// Also print the number in parts per thousand of TOTAL, like --percent
+    #[structopt(long, value_name = "TOTAL", require_equals = true)]
+    permille: Option<Option<String>>,
+
+    /// Decimal places for --percent and --permille
+    #[structopt(long, value_name = "N", default_value = "2")]
+    ratio_precision: u32,
+
+    /// Also print the number as a dimensionless quantity with an SI (base 10) and a binary
+    /// (base 2) prefix, like 1.074 G and 1.000 Gi
+    #[structopt(long)]
+    metric_prefix: bool,
+
+    /// With --metric-prefix, only the SI prefix for 10 or only the binary prefix for 2
+    #[structopt(long, requires = "metric-prefix", possible_values = &["10", "2"])]
+    metric_base: Option<u32>,
+
+    /// Show the working for each conversion
+    #[structopt(long)]
+    explain: bool,
+
+    /// Print digits as Unicode subscripts
+    #[structopt(long, visible_alias = "unicode-subscript", conflicts_with = "superscript")]
+    subscript: bool,
+
+    /// Print digits as Unicode superscripts
+    #[structopt(long, visible_alias = "unicode-superscript")]
+    superscript: bool,
+
+    /// Start each base's line with a symbol for it, so the bases can be told apart without
+    /// color: ▲ for 2, ● for 8, ■ for 10, ◆ for 16 and the base in superscript for others
+    #[structopt(long)]
+    color_blind: bool,
+
+    /// With --subscript/--superscript, print letters as-is instead of failing
+    #[structopt(long)]
+    keep_letters: bool,
+
+    /// Print the value as unsigned and two's complement signed at 8 to 128 bits
+    #[structopt(long)]
+    interpret: bool,
+
+    /// Print digit sum, parity, squareness, largest prime factor and totient
+    #[structopt(long)]
+    stats: bool,
+
+    /// Report the nearest powers of two
+    #[structopt(long)]
+    pow2: bool,
+
+    /// Report whether the value is prime, and if not its prime factors in each target base
+    #[structopt(long)]
+    factor: bool,
+
+    /// With --factor, give up finding the factors after MS milliseconds
+    #[structopt(long, value_name = "MS", default_value = "1000")]
+    factor_timeout_ms: u64,
+
+    /// Luhn check digits: "check" says whether the last digit of the base 10 input is
+    /// its check digit, failing if not, and "append" adds the check digit to the input
+    #[structopt(long, possible_values = &["check", "append"], conflicts_with = "markdown")]
+    luhn: Option<String>,
+
+    /// Print how many bits the value differs from OTHER in, and their XOR in each target
+    /// base. OTHER is in the input base unless it has a prefix like 0x
+    #[structopt(long, value_name = "OTHER")]
+    hamming: Option<String>,
+
+    /// Print the parity bit the value needs so its count of set bits is even or odd.
+    /// With --check-parity, picks the scheme to check against instead
+    #[structopt(long, possible_values = &["even", "odd"])]
+    parity: Option<String>,
+
+    /// Check that the low N bits of the value, the top one a parity bit, have the right
+    /// parity (even unless --parity odd), failing if not
+    #[structopt(long, value_name = "N")]
+    check_parity: Option<u32>,
+
+    /// Convert the CRC-32 (IEEE) of the value's bytes instead of the value. The bytes are
+    /// --width bits, or as few as hold the value, in le (the default) or be order, e.g.
+    /// --crc32=be
+    #[structopt(long, value_name = "ORDER", possible_values = &["le", "be"], require_equals = true,
+                conflicts_with = "crc32c")]
+    crc32: Option<Option<String>>,
+
+    /// Same as --crc32, with the CRC-32C (Castagnoli) polynomial
+    #[structopt(long, value_name = "ORDER", possible_values = &["le", "be"], require_equals = true)]
+    crc32c: Option<Option<String>>,
+
+    /// Replace the value with its remainder divided by N, which is in base 10 unless it has a
+    /// base prefix. With --signed a negative value's remainder is never negative
+    #[structopt(long = "mod", value_name = "N")]
+    modulo: Option<String>,
+
+    /// Give --mod's remainder of a negative value the value's sign, as C and Rust's % do
+    #[structopt(long, requires = "modulo")]
+    truncated_mod: bool,
+
+    /// Print the Shannon entropy of the digits in each target base, in bits per digit
+    #[structopt(long)]
+    entropy: bool,
+
+    /// Also print each value abbreviated, like 1M or 1Ki. Power of two bases step by
+    /// 1024 instead of 1000
+    #[structopt(long, conflicts_with = "markdown")]
+    abbrev: bool,
+
+    /// Print each value abbreviated instead of its digits
+    #[structopt(long, conflicts_with_all = &["abbrev", "markdown"])]
+    abbrev_only: bool,
+
+    /// How --abbrev shortens values: "si" for a prefix like 1M, "decimal" for 1_000_000
+    #[structopt(long, default_value = "si", possible_values = &["si", "decimal"])]
+    abbrev_style: AbbrevStyle,
+
+    /// Decimal places for --abbrev-style si, at most 6
+    #[structopt(long, default_value = "1", value_name = "N")]
+    abbrev_precision: u32,
+
+    /// Print how many times each digit occurs in each target base, and the total
+    #[structopt(long)]
+    digit_freq: bool,
+
+    /// Include digits that don't occur in --digit-freq
+    #[structopt(long, requires = "digit-freq")]
+    all_digits: bool,
+
+    /// How to print the results: text, json, jsonl, markdown, markdown-bold, html, latex or
+    /// xml. The same as the --json, --jsonl and --markdown flags, except that markdown prints
+    /// one number as a table with a row for each base, and markdown-bold puts the base 16 row
+    /// in bold. html, latex and xml print a table or document for each number
+    #[structopt(long, possible_values = OutputFormat::NAMES, conflicts_with_all = &["json", "jsonl", "markdown"])]
+    format: Option<OutputFormat>,
+
+    /// With --format html, the class attribute of the table
+    #[structopt(long, requires = "format", value_name = "CLASS")]
+    css_class: Option<String>,
+
+    /// With --format html, print a whole HTML document instead of just the table
+    #[structopt(long, requires = "format")]
+    full_document: bool,
+
+    /// With --format latex, put the table in a floating table environment with this caption
+    #[structopt(long, requires = "format", value_name = "CAPTION")]
+    latex_caption: Option<String>,
+
+    /// With --format latex, put the table in a floating table environment with this label
+    #[structopt(long, requires = "format", value_name = "LABEL")]
+    latex_label: Option<String>,
+
+    /// With --format xml, put each result on a line of its own indented N spaces
+    #[structopt(long, requires = "format", value_name = "N")]
+    xml_indent: Option<usize>,
+
+    /// Print the results as a JSON object
+    #[structopt(long)]
+    json: bool,
+
+    /// Print the results as a Markdown table, with a row for each input and a column for
+    /// each base
+    #[structopt(long, conflicts_with_all = &["json", "jsonl"])]
+    markdown: bool,
+
+    /// Print one compact JSON object per line of a batch file as it's converted, instead of
+    /// one array at the end. Lines that fail to convert get an object with an error field
+    #[structopt(long, conflicts_with = "json")]
+    jsonl: bool,
+
+    /// With --jsonl, print the objects for lines that fail to convert to stderr
+    #[structopt(long, requires = "jsonl")]
+    errors_to_stderr: bool,
+
+    /// Convert back and forth N times and report any value that changes
+    #[structopt(long)]
+    repeat: Option<u32>,
+
+    /// Print each value as a sum of powers of its base
+    #[structopt(long)]
+    decompose: bool,
+
+    /// Write --decompose terms as "d·b^e" powers or as their values
+    #[structopt(long, default_value = "powers", possible_values = &["powers", "values"])]
+    decompose_style: String,
+
+    /// Leave zero terms out of --decompose
+    #[structopt(long)]
+    nonzero_only: bool,
+
+    /// Also show the value as a fixed-point number (e.g. Q8.8 signed, UQ4.12 unsigned)
+    #[structopt(long)]
+    fixed_point: Option<FixedPointFormat>,
+
+    /// Convert the IEEE 754 double bits of a named constant (pi, e, phi, sqrt2 or ln2) instead of
+    /// from_num
+    #[structopt(long = "const", value_name = "NAME",
+                conflicts_with_all = &["input-file", "from-morse", "from-fixed-point"])]
+    constant: Option<String>,
+
+    /// Read the input number as a decimal fraction in a fixed-point format (e.g. Q8.8)
+    #[structopt(long, conflicts_with = "from-morse")]
+    from_fixed_point: Option<FixedPointFormat>,
+
+    /// Read the input number as Morse code (digits separated by spaces, words by '/')
+    #[structopt(long)]
+    from_morse: bool,
+
+    /// Verbosity, printed to stderr (-v settings, -vv parse steps, -vvv digits)
+    #[structopt(short, long, parse(from_occurrences))]
+    verbosity: u8,
+
+    /// Register a name for a base, e.g. phone=36. Can be repeated
+    #[structopt(long, number_of_values = 1)]
+    base_alias: Vec<String>,
+
+    /// Save the --base-alias definitions to the config file for later runs
+    #[structopt(long)]
+    save_alias: bool,
+
+    /// Bases to convert to, in addition to any positional ones
+    #[structopt(long)]
+    to: Vec<String>,
+
+    /// Compare two numbers: print both in each target base, their XOR and the bits that
+    /// differ. Each can have a base prefix, like 0x, 0b, 0o or BASE:
+    #[structopt(long, number_of_values = 2, value_names = &["A", "B"], allow_hyphen_values = true,
+                conflicts_with_all = &["input-file", "const"])]
+    diff: Vec<String>,
+
+    /// Print the greatest common divisor of two or more numbers, separated by commas, in each
+    /// target base. Each can have a base prefix, like 0x, 0b, 0o or BASE:
+    #[structopt(long, value_name = "N,N...", use_delimiter = true, require_delimiter = true,
+                conflicts_with_all = &["input-file", "const", "diff", "lcm"])]
+    gcd: Vec<String>,
+
+    /// Print the least common multiple of two or more numbers, separated by commas, in each
+    /// target base. Each can have a base prefix, like 0x, 0b, 0o or BASE:
+    #[structopt(long, value_name = "N,N...", use_delimiter = true, require_delimiter = true,
+                conflicts_with_all = &["input-file", "const", "diff"])]
+    lcm: Vec<String>,
+
+    /// Convert every value from START up to END as a table, with END included for
+    /// START..=END. Each bound can have a base prefix, like 0x, 0b, 0o or BASE:
+    #[structopt(long, value_name = "START..END",
+                conflicts_with_all = &["input-file", "const", "diff"])]
+    range: Option<String>,
+
+    /// Step between the values of --range: N to add N, or *N to multiply by N
+    #[structopt(long, default_value = "1", value_name = "N|*N", allow_hyphen_values = true)]
+    step: Step,
+
+    /// The most values --range converts
+    #[structopt(long, default_value = "65536", value_name = "N")]
+    limit: usize,
+
+    /// Convert a random u64, or a value in --random-range
+    #[structopt(long, conflicts_with_all = &["input-file", "const", "diff", "range"])]
+    random: bool,
+
+    /// Bounds for --random, both included. Each can have a base prefix, like 0x, 0b, 0o or BASE:
+    #[structopt(long, number_of_values = 2, value_names = &["MIN", "MAX"], requires = "random")]
+    random_range: Vec<String>,
+
+    /// Seed for --random and --benchmark, to get the same values every time
+    #[structopt(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// How many random values --random converts
+    #[structopt(long, default_value = "1", value_name = "N")]
+    random_count: usize,
+
+    /// Time converting --count random u64s to each target base, printing the throughput
+    /// instead of the results
+    #[structopt(long, conflicts_with_all = &["input-file", "const", "diff", "range", "random"])]
+    benchmark: bool,
+
+    /// How many numbers --benchmark converts
+    #[structopt(long, default_value = "100000", value_name = "N")]
+    count: usize,
+
+    /// Numbers --benchmark converts before it starts timing
+    #[structopt(long, default_value = "0", value_name = "N")]
+    benchmark_warmup: usize,
+
+    /// Copy the value in BASE, or the first target base, to the terminal's clipboard with an
+    /// OSC 52 escape sequence. Works over SSH and with piped output
+    #[structopt(long, value_name = "BASE")]
+    osc52: Option<Option<String>>,
+
+    /// Convert each new number copied to the clipboard, until Ctrl-C. Needs the clipboard
+    /// feature
+    #[structopt(long, conflicts_with_all = &["input-file", "const", "diff", "range", "random", "benchmark"])]
+    watch_clipboard: bool,
+
+    /// Read numbers to convert from a prompt, one line at a time, written like the
+    /// positional arguments. :history lists earlier lines, :!N runs line N again and
+    /// :quit or Ctrl-D exits
+    #[structopt(short = "I", long, conflicts_with_all = &["input-file", "watch-clipboard"])]
+    interactive: bool,
+
+    /// Don't load or save the --interactive history file
+    #[structopt(long)]
+    no_history: bool,
+
+    /// Most lines of --interactive history to keep
+    #[structopt(long, default_value = "1000", value_name = "N")]
+    history_size: usize,
+
+    /// Describe BASE (a number, name or alias) instead of converting: its digits, largest
+    /// values, digits per data type and whether it's prime or highly composite
+    #[structopt(long, value_name = "BASE")]
+    base_info: Option<String>,
+
+    /// List every base that can read from_num, and the value it reads in each
+    #[structopt(long)]
+    scan_bases: bool,
+
+    /// List every base up to MAX that can read from_num, with the value it reads in each in
+    /// decimal and in the target bases. MAX can be a name like hex or b36
+    #[structopt(long, value_name = "MAX", parse(try_from_str = parse_base), conflicts_with = "scan-bases")]
+    find_base: Option<u32>,
+
+    /// Lowest base for --scan-bases and --find-base to try
+    #[structopt(long, default_value = "2", value_name = "BASE")]
+    min_from_base: u32,
+
+    /// Highest base for --scan-bases to try
+    #[structopt(long, default_value = "36", value_name = "BASE")]
+    max_from_base: u32,
+
+    /// Convert to every base from 2 to 36. Can't be used with other target bases
+    #[structopt(long, conflicts_with = "only")]
+    all_bases: bool,
+
+    /// Print how many digits the whole part of the value has in each target base instead
+    /// of the value, as BASE:COUNT pairs for more than one base
+    #[structopt(long, conflicts_with_all = &["json", "jsonl", "markdown"])]
+    digit_count_only: bool,
+
+    /// Print the whole part of the value in each target base as a list of digit values,
+    /// like [1, 17, 112], for any base from 2 up, even past base 36
+    #[structopt(long, conflicts_with_all = &["json", "jsonl", "markdown", "digit-count-only"])]
+    digit_list: bool,
+
+    /// Put SEP between the --digit-list digits instead of listing them in brackets,
+    /// e.g. --digit-sep : for 31:24:57 in base 60
+    #[structopt(long, requires = "digit-list", value_name = "SEP")]
+    digit_sep: Option<String>,
+
+    /// Print only the value in BASE (a number, name or alias), with no label or
+    /// extra lines. Can't be used with other target bases
+    #[structopt(long, value_name = "BASE")]
+    only: Option<String>,
+
+    /// Convert each number in FILE, one per line, instead of a single number. Blank lines
+    /// and lines starting with # are skipped. Use - for stdin. Same as a from_num of @FILE
+    #[structopt(short, long, parse(from_os_str))]
+    input_file: Option<PathBuf>,
+
+    /// Write the results to FILE instead of stdout, - for stdout. FILE is only replaced
+    /// once every conversion has succeeded
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Add to the end of the --output file instead of replacing it
+    #[structopt(long, requires = "output")]
+    append: bool,
+
+    /// Don't print the newline after the last line of output
+    #[structopt(short = "n", long, conflicts_with = "null")]
+    no_newline: bool,
+
+    /// End each line of output with a NUL instead of a newline, e.g. for xargs -0.
+    /// Batch results aren't separated by an empty line
+    #[structopt(short = "z", long)]
+    null: bool,
+
+    /// Line to print between the results for each --input-file number
+    #[structopt(long, default_value = "", allow_hyphen_values = true)]
+    output_separator: String,
+
+    /// Carry on with the rest of --input-file or @FILE when a line fails to convert. Exits 1 if
+    /// some lines failed, 2 if all of them did
+    #[structopt(long)]
+    keep_going: bool,
+
+    /// Convert the lines of --input-file on a thread for each core. The output is in the
+    /// same order, and --keep-going still decides whether a failure stops the batch
+    #[structopt(long)]
+    parallel: bool,
+
+    /// Print how many --input-file lines converted, failed and were skipped to stderr
+    #[structopt(long)]
+    summary: bool,
+
+    /// Don't print progress to stderr for long --input-file batches
+    #[structopt(long)]
+    no_progress: bool,
+
+    /// Let each line of --input-file pick its base with a 0x, 0b or 0o prefix, or N: for
+    /// base N (e.g. 7:123). Lines without a prefix use --from-base
+    #[structopt(long)]
+    from_file_base: bool,
+
+    /// Char representation of input base (b, o, d, or h), a name like hex or base36,
+    /// or a base alias [optional]
+    from_base_char: Option<String>,
+
+    /// Number to convert, or @FILE to convert each number in FILE like --input-file.
+    /// Use @@ for a number starting with @
+    from_num: Option<String>,
+
+    /// Bases to convert to
+    to_bases: Vec<String>,
+
+    /// Where each of the `BIT_OPS` flags given was on the command line
+    #[structopt(skip)]
+    bit_op_indices: Vec<(&'static str, usize)>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bases() {
+        let mut opt = Opt{
+            pad: 0,
+            width_auto: false,
+            truncate_at: None,
+            width: None,
+            signed: false,
+            allow_overflow: false,
+            on_overflow: OverflowMode::Error,
+            encoding: None,
+            ones_complement: false,
+            sign_magnitude: false,
+            excess: None,
+            negation: false,
+            extend_to: None,
+            rotate_left: None,
+            rotate_right: None,
+            rol: None,
+            ror: None,
+            shift_left: None,
+            shift_right: None,
+            mask: None,
+            or_mask: None,
+            xor_mask: None,
+            not: false,
+            set_bit: Vec::new(),
+            clear_bit: Vec::new(),
+            toggle_bit: Vec::new(),
+            test_bit: Vec::new(),
+            all: false,
+            any: false,
+            aligned_to: None,
+            reverse_bits: false,
+            reverse_bytes: false,
+            sep_length: 4,
+            group_by: None,
+            sep_char: '_',
+            no_sep: false,
+            radix_point: '.',
+            output_radix_point: None,
+            precision: 10,
+            round: Rounding::Trunc,
+            as_fraction: false,
+            repeating: false,
+            max_period: 1000,
+            from_base: 10,
+            silent: false,
+            bare: false,
+            morse: false,
+            unary: false,
+            width_needed: false,
+            complement: false,
+            power2_complement_base: None,
+            alignment: false,
+            percent: None,
+            permille: None,
+            ratio_precision: 2,
+            metric_prefix: false,
+            metric_base: None,
+            from_morse: false,
+            explain: false,
+            subscript: false,
+            superscript: false,
+            color_blind: false,
+            keep_letters: false,
+            interpret: false,
+            stats: false,
+            pow2: false,
+            factor: false,
+            factor_timeout_ms: 1000,
+            luhn: None,
+            crc32: None,
+            crc32c: None,
+            modulo: None,
+            truncated_mod: false,
+            hamming: None,
+            parity: None,
+            check_parity: None,
+            entropy: false,
+            abbrev: false,
+            abbrev_only: false,
+            abbrev_style: AbbrevStyle::Si,
+            abbrev_precision: 1,
+            digit_freq: false,
+            all_digits: false,
+            json: false,
+            jsonl: false,
+            format: None,
+            css_class: None,
+            full_document: false,
+            latex_caption: None,
+            latex_label: None,
+            xml_indent: None,
+            errors_to_stderr: false,
+            markdown: false,
+            repeat: None,
+            decompose: false,
+            decompose_style: "powers".to_owned(),
+            nonzero_only: false,
+            fixed_point: None,
+            from_fixed_point: None,
+            constant: None,
+            verbosity: 0,
+            base_alias: Vec::new(),
+            save_alias: false,
+            to: Vec::new(),
+            base_info: None,
+            diff: Vec::new(),
+            gcd: Vec::new(),
+            lcm: Vec::new(),
+            range: None,
+            step: Step::Add(1),
+            limit: 65536,
+            random: false,
+            random_range: Vec::new(),
+            seed: None,
+            random_count: 1,
+            benchmark: false,
+            count: 100000,
+            benchmark_warmup: 0,
+            watch_clipboard: false,
+            osc52: None,
+            interactive: false,
+            no_history: false,
+            history_size: 1000,
+            scan_bases: false,
+            find_base: None,
+            min_from_base: 2,
+            max_from_base: 36,
+            all_bases: false,
+            digit_count_only: false,
+            digit_list: false,
+            digit_sep: None,
+            only: None,
+            input_file: None,
+            from_file_base: false,
+            no_newline: false,
+            null: false,
+            output_separator: String::new(),
+            keep_going: false,
+            parallel: false,
+            summary: false,
+            no_progress: false,
+            output: None,
+            append: false,
+            from_base_char: Some("b".to_owned()),
+            from_num: Some("187".to_owned()),
+            to_bases: Vec::new(),
+            bit_op_indices: Vec::new(),
+        };
+
+        let aliases = HashMap::new();
+        let mut to_bases: Vec<String> = opt.to_bases.clone();
+        let res = get_bases(&opt, &aliases, &mut to_bases);
+        assert_eq!(res.0, 2);
+        assert_eq!(res.1, Some("187".to_owned()));
+        assert!(to_bases.is_empty());
+
+        opt.from_base_char = Some("80".to_owned());
+        let res = get_bases(&opt, &aliases, &mut to_bases);
+        assert_eq!(res.0, 10);
+        assert_eq!(res.1, Some("80".to_owned()));
+        assert!(!to_bases.is_empty());
+    }
+
+    fn test_aliases(opt: &Opt) -> HashMap<String, u32> {
+        let mut aliases = HashMap::new();
+        add_base_aliases(opt, &mut aliases).unwrap();
+        aliases
+    }
+
+    fn run_args(args: &[&str]) -> Vec<u8> {
+        let opt = parse_opt(args);
+        let mut out = Vec::new();
+        run(&opt, &test_aliases(&opt), &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_verbose_stdout_unchanged() {
+        let quiet = run_args(&["numconverter", "h", "BB", "--morse"]);
+        assert_eq!(quiet, b"Base 02: 1011_1011\nBase 08: 273\nBase 10: 187\nBase 16: BB\nMorse: .---- ---.. --...\n");
+        for v in &["-v", "-vv", "-vvv"] {
+            assert_eq!(run_args(&["numconverter", v, "h", "BB", "--morse"]), quiet);
+        }
+
+        let quiet = run_args(&["numconverter", "1_000", "2", "--bare"]);
+        assert_eq!(run_args(&["numconverter", "-vvv", "1_000", "2", "--bare"]), quiet);
+    }
+
+    #[test]
+    fn test_unicode_scripts() {
+        assert_eq!(run_args(&["numconverter", "h", "1FF", "16", "--subscript", "--keep-letters"]),
+                   "Base 16: ₁FF\n".as_bytes());
+        assert_eq!(run_args(&["numconverter", "65536", "10", "--superscript"]),
+                   "Base 10: ⁶_⁵⁵³⁶\n".as_bytes());
+    }
+
+    #[test]
+    fn test_fixed_point_output() {
+        assert_eq!(run_args(&["numconverter", "h", "0180", "16", "--fixed-point", "Q8.8"]),
+                   b"Base 16: 180\nFixed-point Q8.8: 1.5\n");
+        assert_eq!(run_args(&["numconverter", "--from-fixed-point", "Q8.8", "--", "-0.5", "16"]),
+                   b"Base 16: FF80\n");
+    }
+
+    #[test]
+    fn test_pow2_output() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "187", "10", "--pow2"])).unwrap(),
+                   "Base 10: 187\nPower of two: no\n  Below: 2^7 = 128 (0x80), 59 less\n  Above: 2^8 = 256 (0x100), 69 more\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "1", "10", "--pow2", "--bare"])).unwrap(), "1\nyes 2^0\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "187", "10", "--pow2", "--bare"])).unwrap(),
+                   "187\nno 2^7 2^8\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "0", "10", "--pow2", "--bare"])).unwrap(),
+                   "0\nno none 2^0\n");
+    }
+
+    #[test]
+    fn test_bare_reports() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "36", "10", "--stats"])).unwrap(),
+                   "Base 10: 36\nDigit sum:            9\nDigital root:         9\nParity:               even\n\
+                    Perfect square:       yes\nPerfect cube:         no\nLargest prime factor: 3\nEuler totient:        12\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "36", "10", "--stats", "--bare"])).unwrap(),
+                   "36\n9\n9\neven\nyes\nno\n3\n12\n");
+        let interpret = String::from_utf8(run_args(&["numconverter", "255", "10", "--interpret", "--bare"])).unwrap();
+        assert!(interpret.starts_with("255\n8 255 -1\n16 255 255\n"), "{}", interpret);
+        assert!(!interpret.contains("Unsigned"));
+    }
+
+    #[test]
+    fn test_json_output() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "h", "FF", "2", "--json"])).unwrap(),
+                   "{\"input\":\"FF\",\"from_base\":16,\"ok\":true,\"decimal\":255,\"results\":[{\"base\":2,\"value\":\"11111111\"}]}\n");
+        let json = String::from_utf8(run_args(&["numconverter", "0", "16", "--json", "--pow2"])).unwrap();
+        assert!(json.contains("\"pow2\":{\"is_power_of_two\":false,\"below\":null,\"above\":{\"exponent\":0,\"value\":1,\"hex\":\"0x1\",\"difference\":1}}"));
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(run_args(&["numconverter", "h", "F", "16", "2", "--width-auto"]),
+                   b"Base 16: 0F\nBase 02: 1111\n");
+        assert_eq!(run_args(&["numconverter", "h", "1F", "2", "--width-auto", "--no-sep"]),
+                   b"Base 02: 00011111\n");
+        assert_eq!(run_args(&["numconverter", "5", "2", "--pad", "6", "--bare"]), b"00_0101\n");
+        assert_eq!(run_args(&["numconverter", "5", "2", "--pad", "2", "--bare"]), b"101\n");
+        assert_eq!(run_args(&["numconverter", "5", "2", "--pad", "0", "--bare"]), b"101\n");
+        let wide = run_args(&["numconverter", "1", "2", "--pad", "128", "--bare", "--no-sep"]);
+        assert_eq!(wide, format!("{}1\n", "0".repeat(127)).into_bytes());
+        let wider = run_args(&["numconverter", "1", "16", "--pad", "300", "--bare", "--no-sep"]);
+        assert_eq!(wider.len(), 301);
+    }
+
+    fn run_err(args: &[&str]) -> ErrorCode {
+        let opt = parse_opt(args);
+        run(&opt, &test_aliases(&opt), &mut Vec::new()).unwrap_err()
+    }
+
+    /// The output of `args` and the exit code `main` would give for it
+    fn run_exit(args: &[&str]) -> (String, u8) {
+        let opt = parse_opt(args);
+        let mut out = Vec::new();
+        let code = run(&opt, &test_aliases(&opt), &mut out).err().map_or(0, |e| exit_code(&opt, &e));
+        (String::from_utf8(out).unwrap(), code)
+    }
+
+    /// Write a batch file for one test, returning its path
+    fn batch_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("numconverter-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_width_overflow() {
+        assert_eq!(run_err(&["numconverter", "h", "12345", "--width", "16"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "h", "12345", "16", "--width", "16", "--allow-overflow"]),
+                   b"Base 16: 2345\n");
+        assert_eq!(run_args(&["numconverter", "200", "16", "--width", "8"]), b"Base 16: C8\n");
+        assert_eq!(run_err(&["numconverter", "200", "--width", "8", "--signed"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "-200", "--width", "8"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "-128", "16", "--width", "8"]), b"Base 16: 80\n");
+        assert_eq!(run_args(&["numconverter", "h", "FB", "16", "--width", "8", "--signed"]), b"Base 16: FB\n");
+        assert_eq!(run_err(&["numconverter", "-1"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "1", "--width", "129"]), ErrorCode::WidthErr);
+    }
+
+    #[test]
+    fn test_base_alias_cli() {
+        assert_eq!(run_args(&["numconverter", "--base-alias", "phone=36", "phone", "ZZ", "10"]), b"Base 10: 1295\n");
+        assert_eq!(run_args(&["numconverter", "--base-alias", "phone=36", "1295", "--to", "phone", "h"]),
+                   b"Base 36: ZZ\nBase 16: 50F\n");
+
+        let opt = Opt::from_iter(&["numconverter", "--base-alias", "h=12", "5"]);
+        assert_eq!(add_base_aliases(&opt, &mut HashMap::new()), Err(ErrorCode::InputBaseErr));
+    }
+
+    #[test]
+    fn test_save_alias() {
+        let dir = env::temp_dir().join(format!("numconverter-test-{}", std::process::id()));
+        env::set_var("XDG_CONFIG_HOME", &dir);
+
+        let opt = Opt::from_iter(&["numconverter", "--base-alias", "phone=36", "--save-alias"]);
+        save_aliases(&opt).unwrap();
+        let opt = Opt::from_iter(&["numconverter", "--base-alias", "tri=3", "--save-alias"]);
+        save_aliases(&opt).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("numconverter").join("aliases")).unwrap(), "phone=36\ntri=3\n");
+
+        let aliases = load_saved_aliases();
+        assert_eq!(aliases.get("phone"), Some(&36));
+        assert_eq!(aliases.get("tri"), Some(&3));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_on_overflow() {
+        let run_mode = |input: &str, mode: &str, signed: bool| {
+            let mut args = vec!["numconverter", "--bare", "--width", "8", "--on-overflow", mode, input, "16"];
+            if signed {
+                args.push("--signed");
+            }
+            String::from_utf8(run_args(&args)).unwrap()
+        };
+        assert_eq!(run_err(&["numconverter", "256", "--width", "8", "--on-overflow", "error"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "128", "--width", "8", "--signed"]), ErrorCode::WidthErr);
+
+        assert_eq!(run_mode("255",  "wrap", false), "FF\n");
+        assert_eq!(run_mode("256",  "wrap", false), "0\n");
+        assert_eq!(run_mode("257",  "wrap", false), "1\n");
+        assert_eq!(run_mode("128",  "wrap", true),  "80\n");
+        assert_eq!(run_mode("-129", "wrap", true),  "7F\n");
+
+        assert_eq!(run_mode("256",  "saturate", false), "FF\n");
+        assert_eq!(run_mode("128",  "saturate", true),  "7F\n");
+        assert_eq!(run_mode("-129", "saturate", true),  "80\n");
+        assert_eq!(run_mode("-128", "saturate", true),  "80\n");
+
+        let json = String::from_utf8(run_args(&["numconverter", "--json", "--width", "8", "--on-overflow", "saturate", "300", "16"])).unwrap();
+        assert!(json.ends_with(",\"wrapped\":false,\"clamped\":true}\n"));
+    }
+
+    #[test]
+    fn test_word_base_names() {
+        assert_eq!(run_args(&["numconverter", "HEX", "ff", "dec"]), b"Base 10: 255\n");
+        assert_eq!(run_args(&["numconverter", "--from", "bin", "1010", "base16"]), b"Base 16: A\n");
+        assert_eq!(run_args(&["numconverter", "-f", "b36", "zz", "10"]), b"Base 10: 1295\n");
+    }
+
+    #[test]
+    fn test_extend_to() {
+        assert_eq!(run_args(&["numconverter", "h", "FB", "16", "--width", "8", "--signed", "--extend-to", "32"]),
+                   b"Base 16: FFFF_FFFB\n");
+        assert_eq!(run_args(&["numconverter", "h", "FB", "16", "--width", "8", "--extend-to", "32"]),
+                   b"Base 16: FB\n");
+        assert_eq!(run_args(&["numconverter", "-5", "16", "--width", "8", "--signed", "--extend-to", "16"]),
+                   b"Base 16: FFFB\n");
+        assert_eq!(run_args(&["numconverter", "h", "7B", "16", "--width", "8", "--signed", "--extend-to", "32"]),
+                   b"Base 16: 7B\n");
+        assert_eq!(run_err(&["numconverter", "h", "FB", "--extend-to", "32"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "h", "FB", "--width", "16", "--extend-to", "8"]), ErrorCode::WidthErr);
+    }
+
+    #[test]
+    fn test_ones_complement() {
+        assert_eq!(run_args(&["numconverter", "-0", "2", "--width", "8", "--ones-complement"]),
+                   b"Base 02: 1111_1111\nOnes' complement: -0\n");
+        assert_eq!(run_args(&["numconverter", "0", "2", "--width", "8", "--ones-complement"]),
+                   b"Base 02: 0\nOnes' complement: 0\n");
+        assert_eq!(run_args(&["numconverter", "-1", "16", "--width", "8", "--ones-complement"]),
+                   b"Base 16: FE\nOnes' complement: -1\n");
+        assert_eq!(run_args(&["numconverter", "-127", "16", "--width", "8", "--ones-complement"]),
+                   b"Base 16: 80\nOnes' complement: -127\n");
+        assert_eq!(run_err(&["numconverter", "-128", "16", "--width", "8", "--ones-complement"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "h", "FF", "10", "--width", "8", "--ones-complement"]),
+                   b"Base 10: 255\nOnes' complement: -0\n");
+        // Two's complement has no -0
+        assert_eq!(run_args(&["numconverter", "-0", "16", "--width", "8"]), b"Base 16: 0\n");
+    }
+
+    #[test]
+    fn test_encodings() {
+        let table = [
+            ("twos",           "FB", "Two's complement: -5"),
+            ("ones",           "FA", "Ones' complement: -5"),
+            ("sign-magnitude", "85", "Sign-magnitude: -5"),
+        ];
+        for &(encoding, hex, decoded) in table.iter() {
+            assert_eq!(run_args(&["numconverter", "-5", "16", "--width", "8", "--encoding", encoding]),
+                       format!("Base 16: {}\n{}\n", hex, decoded).as_bytes());
+        }
+        assert_eq!(run_args(&["numconverter", "-0", "16", "--width", "8", "--sign-magnitude"]),
+                   b"Base 16: 80\nSign-magnitude: -0\n");
+        assert_eq!(run_args(&["numconverter", "h", "FF", "10", "--width", "8", "--sign-magnitude"]),
+                   b"Base 10: 255\nSign-magnitude: -127\n");
+        assert_eq!(run_err(&["numconverter", "-128", "16", "--width", "8", "--sign-magnitude"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "-5", "16", "--width", "8", "--sign-magnitude", "--signed",
+                              "--extend-to", "16"]),
+                   b"Base 16: 8005\nSign-magnitude: -5\n");
+    }
 
     #[test]
-    fn test_get_bases() {
-        let mut opt = Opt{
-            pad: 0,
-            sep_length: 4,
-            sep_char: '_',
-            no_sep: false,
-            from_base: 10,
-            silent: false,
-            bare: false,
-            verbosity: 0,
-            from_base_char: "b".to_owned(),
-            from_num: Some("187".to_owned()),
-            to_bases: Vec::new(),
+    fn test_from_file_base() {
+        let mixed = batch_file("mixed", "# mixed prefixes\n0xBB\n\n187\n0b1011_1011\n7:355\n");
+        let all = batch_file("all", "0xFF\n0o17\n36:ZZ\n0B1\n");
+        let none = batch_file("none", "BB\nFF\n");
+
+        assert_eq!(run_args(&["numconverter", "-i", mixed.to_str().unwrap(), "--from-file-base", "16"]),
+                   b"Base 16: BB\n\nBase 16: BB\n\nBase 16: BB\n\nBase 16: BB\n");
+        assert_eq!(run_args(&["numconverter", "-i", all.to_str().unwrap(), "--from-file-base", "10"]),
+                   b"Base 10: 255\n\nBase 10: 15\n\nBase 10: 1295\n\nBase 10: 1\n");
+        assert_eq!(run_args(&["numconverter", "-i", none.to_str().unwrap(), "--from-file-base", "-f", "16", "--to", "10"]),
+                   b"Base 10: 187\n\nBase 10: 255\n");
+        // A base_char still picks the base of the file, the rest are target bases
+        assert_eq!(run_args(&["numconverter", "-i", none.to_str().unwrap(), "h", "2", "8", "--bare"]),
+                   b"1011_1011\n273\n\n1111_1111\n377\n");
+        assert_eq!(run_args(&["numconverter", "-i", none.to_str().unwrap(), "-f", "16", "10", "--json"]),
+                   b"[{\"input\":\"BB\",\"from_base\":16,\"ok\":true,\"decimal\":187,\"results\":[{\"base\":10,\"value\":\"187\"}]},\
+                     {\"input\":\"FF\",\"from_base\":16,\"ok\":true,\"decimal\":255,\"results\":[{\"base\":10,\"value\":\"255\"}]}]\n" as &[u8]);
+        // Prefixes are only read with --from-file-base
+        assert_eq!(run_err(&["numconverter", "-i", all.to_str().unwrap(), "10"]), ErrorCode::BaseConversionErr);
+
+        for path in &[mixed, all, none] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_excess() {
+        assert_eq!(run_args(&["numconverter", "-1", "16", "--width", "8", "--excess", "127"]),
+                   b"Base 16: 7E\nExcess-127: -1\n");
+        assert_eq!(run_args(&["numconverter", "128", "16", "--width", "8", "--excess", "127"]),
+                   b"Base 16: FF\nExcess-127: 128\n");
+        assert_eq!(run_args(&["numconverter", "h", "81", "10", "--width", "8", "--excess", "127"]),
+                   b"Base 10: 129\nExcess-127: 2\n");
+        assert_eq!(run_err(&["numconverter", "129", "16", "--width", "8", "--excess", "127"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "-128", "16", "--width", "8", "--excess", "127"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "0", "16", "--width", "8", "--excess", "256"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "-200", "16", "--width", "8", "--excess", "127",
+                              "--on-overflow", "saturate"]),
+                   b"Base 16: 0\nExcess-127: -127\n");
+    }
+
+    #[test]
+    fn test_output_file() {
+        let dir = env::temp_dir().join(format!("numconverter-output-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("results.txt");
+        let path_arg = path.to_str().unwrap();
+        let run_file = |args: &[&str]| {
+            let opt = Opt::from_iter(args);
+            run_to_file(&opt, &test_aliases(&opt), opt.output.as_ref().unwrap())
         };
 
-        let mut to_bases: Vec<String> = opt.to_bases.clone();
-        let res = get_bases(&opt, &mut to_bases);
-        assert_eq!(res.0, 2);
-        assert_eq!(res.1, Some("187".to_owned()));
-        assert!(to_bases.is_empty());
+        run_file(&["numconverter", "h", "FF", "10", "-o", path_arg]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Base 10: 255\n");
+        run_file(&["numconverter", "h", "10", "10", "-o", path_arg, "--append"]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Base 10: 255\nBase 10: 16\n");
 
-        opt.from_base_char = "80".to_owned();
-        let res = get_bases(&opt, &mut to_bases);
-        assert_eq!(res.0, 10);
-        assert_eq!(res.1, Some("80".to_owned()));
-        assert!(!to_bases.is_empty());
+        // A failed conversion leaves the file alone, or is warned about when appending
+        assert_eq!(run_file(&["numconverter", "h", "XY", "10", "-o", path_arg]), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Base 10: 255\nBase 10: 16\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // Files that can't be written are an I/O error, not a panic
+        let missing = dir.join("missing").join("results.txt");
+        assert_eq!(run_file(&["numconverter", "h", "FF", "-o", missing.to_str().unwrap()]), Err(ErrorCode::IoErr));
+        assert_eq!(run_file(&["numconverter", "h", "FF", "-o", missing.to_str().unwrap(), "--append"]),
+                   Err(ErrorCode::IoErr));
+        assert_eq!(run_file(&["numconverter", "h", "FF", "-o", dir.to_str().unwrap()]), Err(ErrorCode::IoErr));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_separator() {
+        let three = batch_file("three", "1\n2\n3\n");
+        let one = batch_file("one", "1\n");
+        let three_arg = three.to_str().unwrap();
+
+        assert_eq!(run_args(&["numconverter", "-i", three_arg, "10", "--output-separator", "---"]),
+                   b"Base 10: 1\n---\nBase 10: 2\n---\nBase 10: 3\n");
+        assert_eq!(run_args(&["numconverter", "-i", one.to_str().unwrap(), "10", "--output-separator", "---"]),
+                   b"Base 10: 1\n");
+        assert_eq!(run_args(&["numconverter", "-i", three_arg, "10", "--output-separator", "---", "--json", "--bare"]),
+                   b"[{\"input\":\"1\",\"from_base\":10,\"ok\":true,\"decimal\":1,\"results\":[{\"base\":10,\"value\":\"1\"}]},\
+                     {\"input\":\"2\",\"from_base\":10,\"ok\":true,\"decimal\":2,\"results\":[{\"base\":10,\"value\":\"2\"}]},\
+                     {\"input\":\"3\",\"from_base\":10,\"ok\":true,\"decimal\":3,\"results\":[{\"base\":10,\"value\":\"3\"}]}]\n" as &[u8]);
+
+        let mut out = Vec::new();
+        print_group_separator(&mut out, "").unwrap();
+        print_group_separator(&mut out, "===").unwrap();
+        assert_eq!(out, b"\n===\n");
+        for path in &[three, one] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_line_end_flags() {
+        assert_eq!(run_args(&["numconverter", "h", "FF", "10", "--bare", "-n"]), b"255");
+        assert_eq!(run_args(&["numconverter", "h", "FF", "10", "16", "--bare", "-z"]), b"255\0FF\0");
+        assert_eq!(run_args(&["numconverter", "h", "FF", "10", "--json", "-n"]),
+                   b"{\"input\":\"FF\",\"from_base\":16,\"ok\":true,\"decimal\":255,\"results\":[{\"base\":10,\"value\":\"255\"}]}" as &[u8]);
+
+        let batch = batch_file("line-end", "1\n2\n");
+        assert_eq!(run_args(&["numconverter", "-i", batch.to_str().unwrap(), "10", "--bare", "-z"]), b"1\x002\0");
+        assert_eq!(run_args(&["numconverter", "-i", batch.to_str().unwrap(), "10", "--bare", "-n"]), b"1\n\n2");
+        fs::remove_file(&batch).unwrap();
+
+        assert!(Opt::from_iter_safe(&["numconverter", "h", "FF", "-n", "-z"]).is_err());
+    }
+
+    #[test]
+    fn test_only() {
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "h"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "b", "1111", "--only", "hex", "--morse", "--stats", "--decompose"]),
+                   b"F\n");
+        assert_eq!(run_args(&["numconverter", "-1", "--only", "16", "--width", "8", "--ones-complement"]), b"FE\n");
+        for (args, _) in EXTRA_OUTPUT {
+            let args = [&["numconverter", "255", "--only", "16"][..], args].concat();
+            assert_eq!(String::from_utf8(run_args(&args)).unwrap(), "FF\n", "{:?}", args);
+        }
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--entropy"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--digit-freq"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--luhn", "append"]), b"9F6\n");
+        assert_eq!(run_args(&["numconverter", "79927398713", "--only", "10", "--luhn", "check", "--no-sep"]), b"79927398713\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--abbrev"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--parity", "even"]), b"FF\n");
+        assert_eq!(run_exit(&["numconverter", "h", "8B", "--only", "16", "--check-parity", "8"]), ("8B\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "h", "8A", "--only", "16", "--check-parity", "8"]), ("8A\n".to_string(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "8A", "--only", "16", "--check-parity", "8", "--parity", "odd"]),
+                   ("8A\n".to_string(), 0));
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--hamming", "0"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--width-needed"]), b"FF\n");
+        assert_eq!(run_err(&["numconverter", "h", "FF", "10", "--only", "16"]), ErrorCode::TargetBaseErr);
+        assert_eq!(run_err(&["numconverter", "255", "10", "--only", "16"]), ErrorCode::TargetBaseErr);
+        assert_eq!(run_err(&["numconverter", "255", "--to", "2", "--only", "16"]), ErrorCode::TargetBaseErr);
+    }
+
+    #[test]
+    fn test_bare_target_extras() {
+        assert_eq!(run_args(&["numconverter", "255", "16", "--entropy"]), b"Base 16: FF\n  Entropy: 0.00 bits\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--entropy", "--bare"]), b"FF\n0.00\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--digit-freq", "--bare"]), b"FF\nF:2\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--abbrev", "--bare"]), b"FF\n255\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--repeat", "3", "--bare"]), b"FF\nstable\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "255", "16", "--decompose", "--bare"])).unwrap(),
+                   "FF\n15·16^1 + 15·16^0\n");
+        let explain = String::from_utf8(run_args(&["numconverter", "255", "16", "--explain", "--bare"])).unwrap();
+        assert!(!explain.contains(" to base ") && !explain.contains("\n  "), "{}", explain);
+        assert_eq!(run_args(&["numconverter", "255", "--only", "16", "--decompose", "--repeat", "3", "--explain"]), b"FF\n");
+    }
+
+    #[test]
+    fn test_base_info() {
+        let out = String::from_utf8(run_args(&["numconverter", "--base-info", "hex"])).unwrap();
+        assert!(out.starts_with("Base 16\nDigits: 0-9A-F\n"));
+        assert!(out.ends_with("Prime power: yes, 2^4\nHighly composite: no\n"));
+        assert_eq!(run_err(&["numconverter", "--base-info", "37"]), ErrorCode::InputBaseErr);
+        assert_eq!(run_err(&["numconverter", "--base-info", "cheese"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_radix_point() {
+        assert_eq!(run_args(&["numconverter", "10.5", "2", "16"]), b"Base 02: 1010.1\nBase 16: A.8\n");
+        assert_eq!(run_args(&["numconverter", "3,14159", "10", "--radix-point", ","]), b"Base 10: 3,14159\n");
+        assert_eq!(run_args(&["numconverter", "3,25", "2", "--radix-point", ",", "--output-radix-point", "."]),
+                   b"Base 02: 11.01\n");
+        assert_eq!(run_args(&["numconverter", "h", ".8", "10"]), b"Base 10: 0.5\n");
+        assert_eq!(run_args(&["numconverter", "0.1", "2", "--precision", "6"]), b"Base 02: 0.000110\n");
+        assert_eq!(run_args(&["numconverter", "1.5", "2", "--json"]),
+                   b"{\"input\":\"1.5\",\"from_base\":10,\"ok\":true,\"decimal\":1,\"results\":[{\"base\":2,\"value\":\"1.1\"}]}\n" as &[u8]);
+        assert_eq!(run_err(&["numconverter", "1.5", "--width", "8"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "1.G"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "1_5", "--radix-point", "_"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_batch_exit_codes() {
+        let mixed = batch_file("mixed-validity", "1\nXY\n3\n# comment\n1.G\n5\n");
+        let bad = batch_file("all-bad", "XY\nZZ\n");
+        let good = batch_file("all-good", "1\n2\n");
+        let batch = |path: &PathBuf, extra: &[&str]| {
+            let mut args = vec!["numconverter", "-i", path.to_str().unwrap(), "10", "--bare"];
+            args.extend_from_slice(extra);
+            let opt = Opt::from_iter(&args);
+            let mut out = Vec::new();
+            let summary = run_batch(&opt, &test_aliases(&opt), opt.input_file.as_ref().unwrap(), 10,
+                                    &["10".to_string()], &mut out).unwrap();
+            let exit = summary.result().err().map_or(0, |e| exit_code(&opt, &e));
+            (summary, exit, out)
+        };
+
+        let (summary, exit, out) = batch(&mixed, &["--keep-going", "--summary"]);
+        assert_eq!((summary.total, summary.converted, summary.failed, summary.skipped), (5, 3, 2, 0));
+        assert_eq!(summary.to_string(), "Summary: 3 of 5 lines converted, 2 failed, 0 skipped");
+        assert_eq!(exit, 1);
+        assert_eq!(out, b"1\n\n3\n\n5\n");
+
+        let (summary, exit, _) = batch(&mixed, &[]);
+        assert_eq!((summary.converted, summary.failed, summary.skipped), (1, 1, 3));
+        assert_eq!(exit, 2);
+
+        let (summary, exit, _) = batch(&bad, &["--keep-going"]);
+        assert_eq!((summary.converted, summary.failed, summary.skipped), (0, 2, 0));
+        assert_eq!(exit, 2);
+
+        let (summary, exit, _) = batch(&good, &["--keep-going"]);
+        assert_eq!((summary.converted, summary.failed), (2, 0));
+        assert_eq!(exit, 0);
+
+        assert_eq!(run_err(&["numconverter", "-i", mixed.to_str().unwrap(), "--keep-going"]), ErrorCode::PartialErr);
+        assert_eq!(exit_code(&Opt::from_iter(&["numconverter", "XY"]), &ErrorCode::BaseConversionErr), 1);
+        for path in &[mixed, bad, good] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_at_file() {
+        let values = batch_file("at-values", "# hex values\nFF\n\n10\n");
+        let at_values = format!("@{}", values.display());
+        assert_eq!(run_args(&["numconverter", "h", &at_values, "10", "--bare"]), b"255\n\n16\n");
+        assert_eq!(run_args(&["numconverter", &at_values, "-f", "16", "--to", "2", "--bare"]),
+                   b"1111_1111\n\n1_0000\n");
+        assert_eq!(run_err(&["numconverter", "h", &at_values, "-i", values.to_str().unwrap()]), ErrorCode::InputBaseErr);
+        assert_eq!(exit_code(&Opt::from_iter(&["numconverter", "h", &at_values]), &ErrorCode::BaseConversionErr), 2);
+        fs::remove_file(&values).unwrap();
+
+        // @@ and -- give a literal @, which isn't a digit
+        assert_eq!(run_err(&["numconverter", "@@FF"]), ErrorCode::BaseConversionErr);
+        let escaped = escape_after_double_dash(["numconverter", "h", "--", "@FF", "10"].iter().map(OsString::from));
+        assert_eq!(escaped, ["numconverter", "h", "--", "@@FF", "10"].iter().map(OsString::from).collect::<Vec<_>>());
+        assert_eq!(at_file("@-"), Some(PathBuf::from("-")));
+        assert_eq!(at_file("@@-"), None);
+        assert_eq!(at_file("FF"), None);
+    }
+
+    #[test]
+    fn test_truncate_at() {
+        assert_eq!(run_args(&["numconverter", "d", "340282366920938463463374607431768211455", "2", "--truncate-at", "6"]),
+                   b"Base 02: 1111_11... (+122 digits)\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--truncate-at", "2"]), b"Base 16: FF\n");
+        // Padding comes first, so the leading zeros are what's kept
+        assert_eq!(run_args(&["numconverter", "255", "16", "--pad", "8", "--truncate-at", "3", "--bare"]),
+                   b"000... (+5 digits)\n");
+    }
+
+    #[test]
+    fn test_scan_bases() {
+        let out = String::from_utf8(run_args(&["numconverter", "FF", "--scan-bases"])).unwrap();
+        assert!(out.starts_with("Base  Decimal\n  16  255\n  17  270\n"));
+        assert!(out.ends_with("  36  555\n"));
+        assert_eq!(run_args(&["numconverter", "1_0", "--scan-bases", "--min-from-base", "8", "--max-from-base", "10"]),
+                   b"Base  Decimal\n   8  8\n   9  9\n  10  10\n");
+        let range = ["numconverter", "1_0", "--scan-bases", "--min-from-base", "8", "--max-from-base", "10"];
+        assert_eq!(run_args(&[&range[..], &["--bare"]].concat()), b"8 8\n9 9\n10 10\n");
+        assert_eq!(run_args(&[&range[..], &["--silent"]].concat()), b"");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "zz", "--scan-bases", "--json"])).unwrap(),
+                   "[{\"base\":36,\"decimal\":1295}]\n");
+        assert_eq!(run_err(&["numconverter", "Z", "--scan-bases", "--max-from-base", "35"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "Z", "--scan-bases", "--min-from-base", "1"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_find_base() {
+        assert_eq!(run_args(&["numconverter", "cafebabe", "--find-base", "hex"]), b"Base 16: 3405691582\n");
+        assert_eq!(run_args(&["numconverter", "cafebabe", "--find-base", "16", "8"]), b"Base 16: 3405691582\n  Base 08: 312_7753_5276\n");
+        assert_eq!(run_args(&["numconverter", "zz", "--find-base", "36"]), b"Base 36: 1295\n");
+        assert_eq!(run_args(&["numconverter", "10", "--find-base", "4", "2"]),
+                   b"Base 02: 2\n  Base 02: 10\nBase 03: 3\n  Base 02: 11\nBase 04: 4\n  Base 02: 100\n");
+        let ff = String::from_utf8(run_args(&["numconverter", "ff", "--find-base", "b36", "--min-from-base", "35"])).unwrap();
+        assert_eq!(ff, "Base 35: 540\nBase 36: 555\n");
+        assert_eq!(run_args(&["numconverter", "10", "--find-base", "3", "2", "16", "--bare"]), b"2 2 10 2\n3 3 11 3\n");
+        assert_eq!(run_args(&["numconverter", "zz", "--find-base", "36", "--bare"]), b"36 1295\n");
+        assert_eq!(run_args(&["numconverter", "zz", "--find-base", "36", "10", "--silent"]), b"");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "zz", "--find-base", "36", "10", "--json"])).unwrap(),
+                   "[{\"base\":36,\"decimal\":1295,\"results\":[{\"base\":10,\"value\":\"1295\"}]}]\n");
+        assert_eq!(run_err(&["numconverter", "zz", "--find-base", "35"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "zz", "--find-base", "37"]), ErrorCode::InputBaseErr);
+    }
+
+    /// Whether `s` is exactly one JSON value, for checking --jsonl lines
+    fn is_json(s: &str) -> bool {
+        fn string(s: &[u8], i: &mut usize) -> bool {
+            if s.get(*i) != Some(&b'"') {
+                return false;
+            }
+            *i += 1;
+            while let Some(&c) = s.get(*i) {
+                *i += if c == b'\\' { 2 } else { 1 };
+                if c == b'"' {
+                    return true;
+                }
+            }
+            false
+        }
+        fn value(s: &[u8], i: &mut usize) -> bool {
+            match s.get(*i) {
+                Some(&open) if open == b'{' || open == b'[' => {
+                    let close = if open == b'{' { b'}' } else { b']' };
+                    *i += 1;
+                    if s.get(*i) == Some(&close) {
+                        *i += 1;
+                        return true;
+                    }
+                    loop {
+                        if open == b'{' && !(string(s, i) && s.get(*i) == Some(&b':')) {
+                            return false;
+                        }
+                        if open == b'{' {
+                            *i += 1;
+                        }
+                        if !value(s, i) {
+                            return false;
+                        }
+                        match s.get(*i) {
+                            Some(b',') => *i += 1,
+                            Some(&c) if c == close => {
+                                *i += 1;
+                                return true;
+                            },
+                            _ => return false,
+                        }
+                    }
+                },
+                Some(b'"') => string(s, i),
+                Some(_) => {
+                    let start = *i;
+                    while s.get(*i).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'-') {
+                        *i += 1;
+                    }
+                    let token = std::str::from_utf8(&s[start..*i]).unwrap();
+                    ["true", "false", "null"].contains(&token) || token.parse::<i128>().is_ok()
+                        || token.parse::<u128>().is_ok()
+                },
+                None => false,
+            }
+        }
+        let mut i = 0;
+        value(s.as_bytes(), &mut i) && i == s.len()
+    }
+
+    #[test]
+    fn test_jsonl() {
+        let path = batch_file("jsonl.txt", "FF\nXYZ\n# comment\n10\n");
+        let run_jsonl = |extra: &[&str]| {
+            let opt = Opt::from_iter([&["numconverter", "h", "-i", path.to_str().unwrap(), "10", "--jsonl",
+                                        "--keep-going"], extra].concat());
+            let mut out = Vec::new();
+            assert_eq!(run(&opt, &test_aliases(&opt), &mut out), Err(ErrorCode::PartialErr));
+            String::from_utf8(out).unwrap()
+        };
+        let out = run_jsonl(&[]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| is_json(line)), "{}", out);
+        assert!(lines[0].starts_with(r#"{"input":"FF","from_base":16,"#));
+        assert!(lines[1].contains(r#""ok":false"#) && lines[1].contains(r#""error":"#));
+        assert!(lines[2].starts_with(r#"{"input":"10","#));
+        assert_eq!(run_jsonl(&["--errors-to-stderr"]).lines().count(), 2);
+        assert!(!is_json(r#"{"a":1"#) && !is_json(r#"[{"a":1}]x"#) && is_json(r#"[{"a":"\"}"},null]"#));
+    }
+
+    #[test]
+    fn test_format_jsonl() {
+        let path = batch_file("format-jsonl.txt", "FF\nXY\n10\n");
+        let opt = Opt::from_iter(&["numconverter", "h", "-i", path.to_str().unwrap(), "2", "--format", "jsonl", "--keep-going"]);
+        let mut out = Vec::new();
+        assert_eq!(run(&opt, &test_aliases(&opt), &mut out), Err(ErrorCode::PartialErr));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| is_json(line)), "{}", out);
+        assert_eq!(lines[0], r#"{"input":"FF","from_base":16,"ok":true,"decimal":255,"results":[{"base":2,"value":"11111111"}]}"#);
+        assert_eq!(lines[1], r#"{"input":"XY","from_base":16,"ok":false,"error":"Base Conversion Error"}"#);
+        // The same as --jsonl
+        let jsonl = Opt::from_iter(&["numconverter", "h", "-i", path.to_str().unwrap(), "2", "--jsonl", "--keep-going"]);
+        let mut jsonl_out = Vec::new();
+        let _ = run(&jsonl, &test_aliases(&jsonl), &mut jsonl_out);
+        assert_eq!(jsonl_out, out.as_bytes());
+        assert_eq!(run_args(&["numconverter", "h", "FF", "2", "--format", "jsonl"]),
+                   b"{\"input\":\"FF\",\"from_base\":16,\"ok\":true,\"decimal\":255,\"results\":[{\"base\":2,\"value\":\"11111111\"}]}\n");
+        assert_eq!(run_args(&["numconverter", "h", "FF", "2", "--format", "json"]),
+                   run_args(&["numconverter", "h", "FF", "2", "--json"]));
+        assert_eq!(run_args(&["numconverter", "h", "FF", "2", "--format", "text"]), b"Base 02: 1111_1111\n");
+    }
+
+    #[test]
+    fn test_const() {
+        assert_eq!(run_args(&["numconverter", "--const", "pi", "--to", "16"]), b"Base 16: 4009_21FB_5444_2D18\n");
+        assert_eq!(run_args(&["numconverter", "--const", "e", "--only", "16"]), b"4005_BF0A_8B14_5769\n");
+        assert_eq!(run_args(&["numconverter", "--const", "ln2", "h", "--bare", "--no-sep"]), b"3FE62E42FEFA39EF\n");
+        assert_eq!(run_args(&["numconverter", "--const", "phi", "16", "8", "--bare", "--no-sep"]),
+                   b"3FF9E3779B97F4A8\n377717067363345772250\n");
+        assert_eq!(run_err(&["numconverter", "--const", "tau"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let path = batch_file("markdown.txt", "255\n4096\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "-i", path.to_str().unwrap(), "2", "10", "16",
+                                                "--markdown"])).unwrap(),
+                   "| Input | Base 2 | Base 10 | Base 16 |\n\
+                    |---:|---:|---:|---:|\n\
+                    | `255` | `1111_1111` | `255` | `FF` |\n\
+                    | `4096` | `1_0000_0000_0000` | `4096` | `1000` |\n");
+        assert_eq!(run_args(&["numconverter", "10", "16", "--markdown", "--morse"]),
+                   b"| Input | Base 16 |\n|---:|---:|\n| `10` | `A` |\n");
+    }
+
+    #[test]
+    fn test_format_markdown() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--format", "markdown", "255", "--to", "2", "8", "10", "16"]))
+                       .unwrap(),
+                   "| Input | Base |       Value |\n\
+                    |------:|-----:|------------:|\n\
+                    | `255` |    2 | `1111_1111` |\n\
+                    | `255` |    8 |       `377` |\n\
+                    | `255` |   10 |       `255` |\n\
+                    | `255` |   16 |        `FF` |\n");
+        let bold = String::from_utf8(run_args(&["numconverter", "--format", "markdown-bold", "255", "2", "16"])).unwrap();
+        assert_eq!(bold.lines().last(), Some("| **`255`** | **16** |    **`FF`** |"));
+        assert!(!bold.lines().nth(2).unwrap().contains("**"));
+        // More than one number is the --markdown table
+        let path = batch_file("format-markdown.txt", "255\n4096\n");
+        assert_eq!(run_args(&["numconverter", "-i", path.to_str().unwrap(), "2", "16", "--format", "markdown"]),
+                   run_args(&["numconverter", "-i", path.to_str().unwrap(), "2", "16", "--markdown"]));
+    }
+
+    #[test]
+    fn test_format_html() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--format", "html", "255", "2", "16"])).unwrap(),
+                   "<table>\n  <caption>255</caption>\n  <thead>\n    <tr><th>Base</th><th>Value</th></tr>\n  </thead>\n  \
+                    <tbody>\n    <tr data-base=\"2\"><td>2</td><td>1111_1111</td></tr>\n    \
+                    <tr data-base=\"16\"><td>16</td><td>FF</td></tr>\n  </tbody>\n</table>\n");
+        let classed = String::from_utf8(run_args(&["numconverter", "--format", "html", "--css-class", "nums", "5", "10"])).unwrap();
+        assert!(classed.starts_with("<table class=\"nums\">\n"));
+        let doc = String::from_utf8(run_args(&["numconverter", "--format", "html", "--full-document", "5", "10"])).unwrap();
+        assert!(doc.starts_with("<!DOCTYPE html>\n") && doc.contains("<title>5</title>") && doc.ends_with("</html>\n"));
+        assert_eq!(doc.matches("<table>").count(), 1);
+    }
+
+    #[test]
+    fn test_format_latex() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--format", "latex", "255", "2", "10", "16"])).unwrap(),
+                   "\\begin{tabular}{rl}\n\\hline\nBase & Value \\\\\n\\hline\n2 & 1111\\_1111 \\\\\n\
+                    10 & 255 \\\\\n16 & FF \\\\\n\\hline\n\\end{tabular}\n");
+        let float = String::from_utf8(run_args(&["numconverter", "--format", "latex", "--latex-caption", "A byte",
+                                                 "--latex-label", "tab:byte", "255", "16"])).unwrap();
+        assert!(float.starts_with("\\begin{table}\n\\centering\n\\caption{A byte}\n\\label{tab:byte}\n"));
+        assert!(float.ends_with("\\end{tabular}\n\\end{table}\n"));
+    }
+
+    #[test]
+    fn test_format_xml() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--format", "xml", "255", "2", "16"])).unwrap(),
+                   "<?xml version=\"1.0\"?>\n<conversion input=\"255\" from_base=\"10\">\
+                    <result base=\"2\" value=\"11111111\"/><result base=\"16\" value=\"FF\"/></conversion>\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--format", "xml", "--xml-indent", "2", "h", "ff", "10"])).unwrap(),
+                   "<?xml version=\"1.0\"?>\n<conversion input=\"ff\" from_base=\"16\">\n  \
+                    <result base=\"10\" value=\"255\"/>\n</conversion>\n");
+    }
+
+    #[test]
+    fn test_group_by() {
+        assert_eq!(run_args(&["numconverter", "1000000", "2", "10", "16", "--group-by", "2:8,10:3"]),
+                   b"Base 02: 1111_01000010_01000000\nBase 10: 1_000_000\nBase 16: F_4240\n");
+    }
+
+    #[test]
+    fn test_color_blind() {
+        let out = String::from_utf8(run_args(&["numconverter", "255", "--color-blind"])).unwrap();
+        assert_eq!(out, "▲ Base 02: 1111_1111\n● Base 08: 377\n■ Base 10: 255\n◆ Base 16: FF\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "255", "36", "--color-blind", "--bare"])).unwrap(),
+                   "³⁶ 73\n");
+        assert!(String::from_utf8(run_args(&["numconverter", "0x10000", "16", "--color-blind", "--abbrev-only"])).unwrap()
+            .starts_with("◆ Base 16: "));
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(run_args(&["numconverter", "--gcd", "12,18,0x24", "10", "16"]), b"GCD base 10: 6\nGCD base 16: 6\n");
+        assert_eq!(run_args(&["numconverter", "--gcd", "0b1000,0o20,2:1100", "2", "--bare"]), b"100\n");
+        assert_eq!(run_args(&["numconverter", "--lcm", "4,6,10", "10", "--bare"]), b"60\n");
+        assert_eq!(run_args(&["numconverter", "--lcm", "0x100,0x1000,3", "16"]), b"LCM base 16: 3000\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--lcm", "4,6", "10", "--json"])).unwrap().trim_end(),
+                   r#"{"inputs":["4","6"],"lcm":12,"results":[{"base":10,"value":"12"}]}"#);
+        assert_eq!(run_err(&["numconverter", "--lcm", "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,2"]), ErrorCode::OverflowErr);
+        assert_eq!(run_err(&["numconverter", "--gcd", "12"]), ErrorCode::InputBaseErr);
+        assert_eq!(run_err(&["numconverter", "--gcd", "12,0xZZ"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--diff", "0xDEADBEEF", "0xDEADBFEF", "h"])).unwrap(),
+                   "Base 16: DEAD_BEEF\n         DEAD_BFEF\nXOR:     0000_0100\nBits differ: 8\n");
+        assert_eq!(run_args(&["numconverter", "--diff", "5", "2:110", "2", "--bare"]),
+                   b"101\n110\n011\n0, 1\n");
+        assert_eq!(run_args(&["numconverter", "--diff", "7", "0o7", "10", "--bare"]), b"7\n7\n0\nnone\n");
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--diff", "0xF0", "0xF1", "16", "--json"])).unwrap(),
+                   concat!(r#"{"a":{"input":"0xF0","from_base":16,"decimal":240},"#,
+                           r#""b":{"input":"0xF1","from_base":16,"decimal":241},"xor":1,"differing_bits":[0],"#,
+                           r#""results":[{"base":16,"a":"F0","b":"F1","xor":"01"}]}"#, "\n"));
+        assert_eq!(run_err(&["numconverter", "--diff", "0xZZ", "1"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_range() {
+        let out = String::from_utf8(run_args(&["numconverter", "--range", "0..16", "10", "16", "2"])).unwrap();
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 17);
+        assert_eq!(rows[0], "Base 10  Base 16  Base 2");
+        assert_eq!(rows[1], "      0        0       0");
+        assert_eq!(rows[11], "     10        A    1010");
+        assert_eq!(rows[16], "     15        F    1111");
+        assert_eq!(run_args(&["numconverter", "--range", "0x0E..=0x10", "h", "--bare", "--pad", "2"]),
+                   b"0E\n0F\n10\n");
+        assert_eq!(run_args(&["numconverter", "--range", "1..3", "16", "2", "--markdown"]),
+                   b"| Input | Base 16 | Base 2 |\n|---:|---:|---:|\n| `1` | `1` | `1` |\n| `2` | `2` | `10` |\n");
+        assert_eq!(run_args(&["numconverter", "--range", "0..=16", "--step", "4", "10", "--bare"]), b" 0\n 4\n 8\n12\n16\n");
+        assert_eq!(run_args(&["numconverter", "--range", "1..=4096", "--step", "*2", "h", "--bare", "--no-sep"]),
+                   b"   1\n   2\n   4\n   8\n  10\n  20\n  40\n  80\n 100\n 200\n 400\n 800\n1000\n");
+        assert_eq!(run_err(&["numconverter", "--range", "0..16", "--step", "*2"]), ErrorCode::InputBaseErr);
+        assert_eq!(run_err(&["numconverter", "--range", "0..=65536"]), ErrorCode::InputBaseErr);
+        assert_eq!(run_args(&["numconverter", "--range", "0..=65536", "--limit", "65537", "10", "--bare",
+                             "--no-sep"]).len(),
+                   65537 * "65536\n".len());
+    }
+
+    #[test]
+    fn test_random() {
+        let args = ["numconverter", "--random", "--seed", "0", "--to", "16", "--bare", "--no-sep"];
+        assert_eq!(run_args(&args), b"6E789E6AA1B965F4\n");
+        assert_eq!(run_args(&args), run_args(&args));
+        assert_eq!(run_args(&["numconverter", "--random", "--seed", "0", "--random-range", "0", "0xFF", "h", "--bare"]),
+                   run_args(&["numconverter", "--random", "--seed", "0", "--random-range", "0", "255", "h", "--bare"]));
+        let out = String::from_utf8(run_args(&["numconverter", "--random", "--seed", "1", "--random-range", "10", "20",
+                                               "--random-count", "50", "10", "--bare"])).unwrap();
+        let values: Vec<u32> = out.split_whitespace().map(|v| v.parse().unwrap()).collect();
+        assert_eq!(values.len(), 50);
+        assert!(values.iter().all(|v| (10..=20).contains(v)));
+        assert_eq!(run_args(&["numconverter", "--random", "--seed", "3", "--random-range", "7", "7", "2", "--bare"]),
+                   b"111\n");
+        assert_eq!(run_err(&["numconverter", "--random", "--random-range", "9", "1"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let start = Instant::now();
+        let out = String::from_utf8(run_args(&["numconverter", "--benchmark", "--to", "2", "16"])).unwrap();
+        assert!(start.elapsed().as_secs() < 30);
+        assert!(out.starts_with("Converted 100000 numbers in "), "{}", out);
+        assert!(out.ends_with(" conversions/sec)\n"));
+        assert_eq!(out.lines().count(), 1);
+        let out = String::from_utf8(run_args(&["numconverter", "--benchmark", "--count", "10", "--benchmark-warmup",
+                                               "5", "h"])).unwrap();
+        assert!(out.starts_with("Converted 10 numbers in "));
+        assert_eq!(run_err(&["numconverter", "--benchmark", "--count", "1", "40"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    #[cfg(not(feature = "clipboard"))]
+    fn test_watch_clipboard_needs_feature() {
+        assert_eq!(run_err(&["numconverter", "--watch-clipboard", "h"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_interactive() {
+        let opt = Opt::from_iter(&["numconverter", "--interactive", "--no-history", "--bare"]);
+        let mut out = Vec::new();
+        let input = "h FF 2\n\nb 101 10\n:history\n:!1\nh XYZ\n:bogus\n:q\nd 1\n";
+        let mut lines = repl::PlainLines { input: input.as_bytes(), prompt: false };
+        run_interactive(&opt, &test_aliases(&opt), &mut lines, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "1111_1111\n5\n    1  h FF 2\n    2  b 101 10\nh FF 2\n1111_1111\n");
+
+        let mut out = Vec::new();
+        let input = "mask = h F0 10\nmask 2\n$ans 16\nh = 1\nn = $nope\nb $mask 10\ntop = d 1 10\n:vars\n";
+        let mut lines = repl::PlainLines { input: input.as_bytes(), prompt: false };
+        run_interactive(&opt, &test_aliases(&opt), &mut lines, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().split('\n').collect::<Vec<_>>(),
+                   ["240", "1111_0000", "F0", "240", "1", "mask = 240", "top = 1", "$ans = 1", ""]);
+    }
+
+    #[test]
+    fn test_parallel_batch() {
+        let contents: String = (0..10_000).map(|i| if i % 997 == 0 { "XYZ\n".to_string() } else { format!("{}\n", i) })
+            .collect();
+        let path = batch_file("parallel", &contents);
+        let path = path.to_str().unwrap();
+        for flags in [&["--keep-going"][..], &["--jsonl"], &[]] {
+            let args = |parallel: bool| {
+                let mut args = vec!["numconverter", "-i", path, "h", "2"];
+                args.extend_from_slice(flags);
+                if parallel {
+                    args.push("--parallel");
+                }
+                let opt = Opt::from_iter(&args);
+                let mut out = Vec::new();
+                let result = run(&opt, &test_aliases(&opt), &mut out);
+                (result, out)
+            };
+            assert_eq!(args(true), args(false));
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_round() {
+        let round = |value: &str, base: &str, mode: &str| {
+            String::from_utf8(run_args(&["numconverter", value, base, "--bare", "--precision", "4", "--round", mode]))
+                .unwrap()
+        };
+        assert_eq!(round("0.999999", "10", "trunc"), "0.9999\n");
+        assert_eq!(round("0.999999", "10", "nearest"), "1.0000\n");
+        assert_eq!(round("7.999999", "16", "up"), "8.0000\n");
+        assert_eq!(round("0.1", "2", "nearest"), "0.0010\n");
+        assert_eq!(round("0.1", "2", "down"), "0.0001\n");
+        assert_eq!(round("1.5", "2", "up"), "1.1\n");
+        let json = run_args(&["numconverter", "0.999999", "10", "--precision", "4", "--round", "nearest", "--json"]);
+        assert!(String::from_utf8(json).unwrap().contains(r#""value":"1.0000""#));
+    }
+
+    #[test]
+    fn test_repeating() {
+        assert_eq!(run_args(&["numconverter", "0.1", "2", "16", "--bare", "--repeating"]), b"0.0(0011)\n0.1(9)\n");
+        assert_eq!(run_args(&["numconverter", "b", "0.01", "10", "--bare", "--repeating"]), b"0.25\n");
+        assert_eq!(run_args(&["numconverter", "0.1", "10", "--from-base", "3", "--bare", "--repeating"]),
+                   b"0.(3)\n");
+        assert_eq!(run_args(&["numconverter", "2.0", "10", "--from-base", "3", "--bare", "--repeating"]),
+                   b"2.0\n");
+        // Past --max-period it's --precision digits as usual
+        assert_eq!(run_args(&["numconverter", "0.1", "2", "--bare", "--repeating", "--max-period", "3", "--precision", "6"]),
+                   b"0.000110\n");
+    }
+
+    #[test]
+    fn test_rational_input() {
+        assert_eq!(run_args(&["numconverter", "-f", "10", "3/7", "2", "10", "--bare", "--precision", "6"]),
+                   b"0.011011\n0.428571\n");
+        assert_eq!(run_args(&["numconverter", "3/7", "10", "--bare", "--repeating"]), b"0.(428571)\n");
+        assert_eq!(run_args(&["numconverter", "22/7", "2", "16", "--bare", "--as-fraction"]), b"1_0110/111\n16/7\n");
+        assert_eq!(run_args(&["numconverter", "6/8", "10", "--bare", "--as-fraction"]), b"3/4\n");
+        assert_eq!(run_args(&["numconverter", "h", "1_0/4", "10", "--bare"]), b"4\n");
+        assert_eq!(run_err(&["numconverter", "1/0", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "b", "1/2", "10"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_scientific_input() {
+        assert_eq!(run_args(&["numconverter", "1.5e3", "10", "16", "--bare"]), b"1500\n5DC\n");
+        assert_eq!(run_args(&["numconverter", "6.02E23", "10", "--bare"]), b"6020_0000_0000_0000_0000_0000\n");
+        assert_eq!(run_args(&["numconverter", "2e-4", "10", "--bare", "--precision", "6"]), b"0.0002\n");
+        assert_eq!(run_args(&["numconverter", "2e-4", "10", "--bare", "--as-fraction"]), b"1/5000\n");
+        assert_eq!(run_args(&["numconverter", "125e-2", "2", "--bare", "--precision", "4"]), b"1.01\n");
+        assert_eq!(run_args(&["numconverter", "1,5e1", "10", "--bare", "--radix-point", ","]), b"15\n");
+        // e is a digit in hex
+        assert_eq!(run_args(&["numconverter", "h", "1e3", "10", "--bare"]), b"483\n");
+        assert_eq!(run_err(&["numconverter", "1.5e", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "e5", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "1e40", "10"]), ErrorCode::OverflowErr);
+    }
+
+    #[test]
+    fn test_digit_count_only() {
+        assert_eq!(run_args(&["numconverter", "h", "FFFF", "2", "--digit-count-only"]), b"Base 02 digits: 16\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--digit-count-only", "--bare"]), b"2\n");
+        assert_eq!(run_args(&["numconverter", "255", "--only", "8", "--digit-count-only", "--bare"]), b"3\n");
+        assert_eq!(run_args(&["numconverter", "0", "--digit-count-only"]), b"2:1 8:1 10:1 16:1\n");
+        // Padding and separators aren't digits of the value
+        assert_eq!(run_args(&["numconverter", "255", "2", "10", "--pad", "12", "--digit-count-only"]), b"2:8 10:3\n");
+        let all = String::from_utf8(run_args(&["numconverter", "h", "FFFF_FFFF", "--all-bases", "--digit-count-only"])).unwrap();
+        assert!(all.starts_with("2:32 3:21 4:16 "));
+        assert!(all.ends_with(" 16:8 17:8 18:8 19:8 20:8 21:8 22:8 23:8 24:7 25:7 26:7 27:7 28:7 29:7 30:7 31:7 32:7 33:7 34:7 35:7 36:7\n"));
+        assert_eq!(run_err(&["numconverter", "255", "2", "--all-bases"]), ErrorCode::TargetBaseErr);
+    }
+
+    #[test]
+    fn test_hex_float_input() {
+        assert_eq!(run_args(&["numconverter", "0x1.8p3", "10", "16"]),
+                   b"Base 10: 12\nBase 16: C\nNearest f32: 12 (0x41400000)\nNearest f64: 12 (0x4028000000000000)\n");
+        assert_eq!(run_args(&["numconverter", "0x1p10", "--only", "10"]), b"1024\n");
+        assert_eq!(run_args(&["numconverter", "0x1.8p-1", "10", "--bare", "--as-fraction"]),
+                   b"3/4\n0.75 (0x3F400000)\n0.75 (0x3FE8000000000000)\n");
+        assert_eq!(run_args(&["numconverter", "-w", "8", "--bare", "--", "-0x1p3", "16"]),
+                   b"F8\n-8 (0xC1000000)\n-8 (0xC020000000000000)\n");
+        // The f32 is rounded but the conversion is exact
+        assert_eq!(run_args(&["numconverter", "0x1.000001p24", "10", "--bare", "--no-sep"]),
+                   b"16777217\n16777216 (0x4B800000)\n16777217 (0x4170000010000000)\n");
+        assert_eq!(run_err(&["numconverter", "--", "-0x1.8p0", "10"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "0x1.8p", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "0x1p128", "10"]), ErrorCode::OverflowErr);
+    }
+
+    #[test]
+    fn test_rust_literal_input() {
+        assert_eq!(run_args(&["numconverter", "0xFF_u32", "10", "--bare"]), b"255\n");
+        assert_eq!(run_args(&["numconverter", "42i64", "16", "--bare"]), b"2A\n");
+        assert_eq!(run_args(&["numconverter", "0b1010_1010u8", "16", "--bare"]), b"AA\n");
+        assert_eq!(run_args(&["numconverter", "0o17u16", "10", "--bare"]), b"15\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "-1i8", "16"]), b"FF\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "-128i8", "2"]), b"1000_0000\n");
+        assert_eq!(run_args(&["numconverter", "1u128", "2", "--bare"]), b"1\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--no-sep", "--", "-1i128", "16"]), format!("{:X}\n", u128::MAX).as_bytes());
+        assert_eq!(run_args(&["numconverter", "7usize", "10", "--bare"]), b"7\n");
+        // rustc rejects 0xFFi8, but the bits are still an 8 bit pattern
+        assert_eq!(run_args(&["numconverter", "0xFFi8", "10", "--bare"]), b"255\n");
+        // An explicit --width wins
+        assert_eq!(run_args(&["numconverter", "-w", "16", "300u8", "16", "--bare"]), b"12C\n");
+        assert_eq!(run_err(&["numconverter", "256u8", "10"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "128i8", "10"]), ErrorCode::WidthErr);
+        assert_eq!(run_args(&["numconverter", "256u8", "10", "--bare", "--on-overflow", "wrap"]), b"0\n");
+        // Too large a base for the suffix to be anything but digits
+        assert_eq!(run_args(&["numconverter", "-f", "36", "u8", "10", "--bare"]), b"1088\n");
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(run_args(&["numconverter", "255", "2", "16", "--entropy"]),
+                   b"Base 02: 1111_1111\n  Entropy: 0.00 bits\nBase 16: FF\n  Entropy: 0.00 bits\n");
+        assert_eq!(run_args(&["numconverter", "h", "0123_4567_89AB_CDEF", "16", "--bare", "--entropy"]),
+                   b"123_4567_89AB_CDEF\n3.91\n");
+        // Padding isn't part of the value
+        assert_eq!(run_args(&["numconverter", "2", "2", "--bare", "--pad", "8", "--entropy"]),
+                   b"0000_0010\n1.00\n");
+    }
+
+    #[test]
+    fn test_c_literal_input() {
+        assert_eq!(run_args(&["numconverter", "0xFFFFFFFFUL", "10", "--bare", "--no-sep"]), b"4294967295\n");
+        assert_eq!(run_args(&["numconverter", "123456789LL", "16", "--bare"]), b"75B_CD15\n");
+        assert_eq!(run_args(&["numconverter", "UINT64_C(0x123)", "10", "--bare"]), b"291\n");
+        assert_eq!(run_args(&["numconverter", "0755u", "10", "--bare"]), b"493\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "-1L", "16"]), b"FFFF_FFFF_FFFF_FFFF\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "INT8_C(-1)", "16"]), b"FF\n");
+        // The type grows to fit, and negating an unsigned value wraps, as in C
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "-0x1FFFFFFFFU", "16"]), b"FFFF_FFFE_0000_0001\n");
+        assert_eq!(run_args(&["numconverter", "--bare", "--", "-1U", "16"]), b"FFFF_FFFF\n");
+        assert_eq!(run_err(&["numconverter", "UINT8_C(256)", "10"]), ErrorCode::WidthErr);
+        // A prefix on its own gives the base but no width
+        assert_eq!(run_args(&["numconverter", "0x1F", "10", "--bare"]), b"31\n");
+        assert_eq!(run_args(&["numconverter", "0B101", "10", "--bare"]), b"5\n");
+        assert_eq!(run_args(&["numconverter", "h", "0B101", "10", "--bare"]), b"4_5313\n");
+        assert_eq!(run_err(&["numconverter", "0xFFUZ", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "12UZ", "10"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_negation() {
+        assert_eq!(run_args(&["numconverter", "--negation", "255", "--width", "8", "--to", "2", "16"]),
+                   b"Base 02: 0000_0001\nBase 16: 01\n");
+        assert_eq!(run_args(&["numconverter", "--negation", "1", "-w", "16", "16", "--bare"]), b"FFFF\n");
+        assert_eq!(run_args(&["numconverter", "--negation", "h", "80", "-w", "8", "16", "--bare"]), b"80\n");
+        assert_eq!(run_args(&["numconverter", "--negation", "-w", "8", "--bare", "--", "-5", "10"]), b"005\n");
+        assert_eq!(run_args(&["numconverter", "--negation", "--ones-complement", "h", "F0", "-w", "8", "16", "--bare"]),
+                   b"0F\n15\n");
+        assert_eq!(run_args(&["numconverter", "--negation", "--sign-magnitude", "5", "-w", "8", "16", "--bare"]),
+                   b"85\n-5\n");
+        // After --extend-to, in the wider width
+        assert_eq!(run_args(&["numconverter", "--negation", "--signed", "1", "-w", "8", "--extend-to", "16", "16", "--bare"]),
+                   b"FFFF\n");
+    }
+
+    #[test]
+    fn test_rotate_and_shift() {
+        assert_eq!(run_args(&["numconverter", "--rotate-left", "4", "h", "AB", "-w", "8", "16", "--bare"]), b"BA\n");
+        assert_eq!(run_args(&["numconverter", "--rotate-right", "1", "b", "101", "-w", "3", "2", "--bare"]), b"110\n");
+        for shift in 0..8u32 {
+            let shift_arg = shift.to_string();
+            let left = run_args(&["numconverter", "--shift-left", &shift_arg, "h", "AB", "-w", "8", "16", "--bare"]);
+            assert_eq!(left, format!("{:X}\n", (0xABu32 << shift) & 0xFF).into_bytes(), "left by {}", shift);
+            let right = run_args(&["numconverter", "--shift-right", &shift_arg, "h", "AB", "-w", "8", "16", "--bare"]);
+            assert_eq!(right, format!("{:X}\n", 0xABu32 >> shift).into_bytes(), "right by {}", shift);
+        }
+        assert_eq!(run_args(&["numconverter", "--shift-left", "4", "255", "16", "--bare"]), b"FF0\n");
+        assert_eq!(run_err(&["numconverter", "--rotate-left", "8", "1", "-w", "8", "2"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "--shift-right", "128", "1", "2"]), ErrorCode::WidthErr);
+    }
+
+    #[test]
+    fn test_digit_list() {
+        assert_eq!(run_args(&["numconverter", "113097", "60", "--digit-list"]), b"Base 60: [31, 24, 57]\n");
+        assert_eq!(run_args(&["numconverter", "113097", "60", "--digit-list", "--digit-sep", ":", "--bare"]), b"31:24:57\n");
+        assert_eq!(run_args(&["numconverter", "7", "60", "--digit-list", "--bare"]), b"[7]\n");
+        assert_eq!(run_args(&["numconverter", "0", "2", "--digit-list", "--bare"]), b"[0]\n");
+        assert_eq!(run_args(&["numconverter", "h", "FF", "16", "2", "--digit-list"]),
+                   b"Base 16: [15, 15]\nBase 02: [1, 1, 1, 1, 1, 1, 1, 1]\n");
+        assert_eq!(run_args(&["numconverter", "35", "--only", "36", "--digit-list"]), b"[35]\n");
+        assert_eq!(run_err(&["numconverter", "5", "1", "--digit-list"]), ErrorCode::TargetBaseErr);
+    }
+
+    #[test]
+    fn test_rol_ror() {
+        let rot = |flag: &str, n: &str, value: &str, width: &str| {
+            String::from_utf8(run_args(&["numconverter", flag, n, "h", value, "-w", width, "16", "--bare", "--no-sep"])).unwrap()
+        };
+        assert_eq!(rot("--rol", "0", "AB", "8"), "AB\n");
+        assert_eq!(rot("--ror", "0", "AB", "8"), "AB\n");
+        assert_eq!(rot("--rol", "8", "AB", "8"), "AB\n");
+        assert_eq!(rot("--ror", "64", "1234", "64"), "1234\n");
+        // N past the width is taken modulo the width
+        assert_eq!(rot("--rol", "12", "AB", "8"), "BA\n");
+        assert_eq!(rot("--ror", "68", "AB", "8"), "BA\n");
+        // Across the top bit
+        assert_eq!(rot("--rol", "1", "81", "8"), "3\n");
+        assert_eq!(rot("--ror", "1", "81", "8"), "C0\n");
+        assert_eq!(rot("--rol", "4", "F000000000000001", "64"), "1F\n");
+        assert_eq!(rot("--ror", "1", "1", "64"), "8000000000000000\n");
+        assert!(Opt::from_iter_safe(&["numconverter", "--rol", "1", "5", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_bit_ops_in_command_line_order() {
+        assert_eq!(run_args(&["numconverter", "--rol", "4", "--mask", "0xF0", "h", "AB", "-w", "8", "16", "--bare"]), b"B0\n");
+        assert_eq!(run_args(&["numconverter", "--mask", "0xF0", "--rol", "4", "h", "AB", "-w", "8", "16", "--bare"]), b"A\n");
+        assert_eq!(run_args(&["numconverter", "--shift-left", "1", "--ror", "1", "h", "81", "-w", "8", "16", "--bare"]), b"1\n");
+        assert_eq!(run_args(&["numconverter", "--ror", "1", "--shift-left", "1", "h", "81", "-w", "8", "16", "--bare"]), b"80\n");
+        assert_eq!(run_args(&["numconverter", "--not", "--xor-mask", "0x0F", "h", "00", "-w", "8", "16", "--bare"]), b"F0\n");
+    }
+
+    #[test]
+    fn test_single_bits() {
+        assert_eq!(run_args(&["numconverter", "--set-bit", "7", "--clear-bit", "0", "--toggle-bit", "3", "h", "1", "16", "--bare"]),
+                   b"88\n");
+        assert_eq!(run_args(&["numconverter", "--set-bit", "0", "--set-bit", "127", "0", "16", "--bare", "--no-sep"]),
+                   format!("{:X}\n", 1u128 << 127 | 1).into_bytes());
+        // In the order given
+        assert_eq!(run_args(&["numconverter", "--set-bit", "2", "--toggle-bit", "2", "0", "10", "--bare"]), b"0\n");
+        assert_eq!(run_args(&["numconverter", "--toggle-bit", "2", "--set-bit", "2", "0", "10", "--bare"]), b"4\n");
+        assert_eq!(run_args(&["numconverter", "--clear-bit", "0", "--mask", "0", "--set-bit", "1", "3", "10", "--bare"]), b"2\n");
+        assert_eq!(run_err(&["numconverter", "--set-bit", "128", "0", "10"]), ErrorCode::WidthErr);
+        assert_eq!(run_err(&["numconverter", "--toggle-bit", "8", "0", "-w", "8", "10"]), ErrorCode::WidthErr);
+        let json = String::from_utf8(run_args(&["numconverter", "--set-bit", "7", "--clear-bit", "0", "h", "1", "16", "--json"])).unwrap();
+        assert!(is_json(json.trim_end()));
+        assert!(json.contains(r#""bit_ops":{"applied":[{"op":"set-bit","bit":7},{"op":"clear-bit","bit":0}],"value":128}"#), "{}", json);
+    }
+
+    #[test]
+    fn test_test_bit() {
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "7", "--silent"]), (String::new(), 0));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "6", "--silent"]), (String::new(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "7"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "0"]), ("false\n".to_string(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "81", "--test-bit", "7", "--test-bit", "0"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "7", "--test-bit", "0", "--all"]), ("false\n".to_string(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "7", "--test-bit", "0", "--any"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "1", "--test-bit", "0", "--any"]), ("false\n".to_string(), 1));
+        // After the bit operations, and errors don't look like false
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--clear-bit", "7", "--test-bit", "7"]), ("false\n".to_string(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "--test-bit", "128"]), (String::new(), 2));
+        assert_eq!(run_exit(&["numconverter", "h", "80", "-w", "8", "--test-bit", "8"]), (String::new(), 2));
+        assert_eq!(run_exit(&["numconverter", "h", "XY", "--test-bit", "0"]), (String::new(), 2));
+        assert!(Opt::from_iter_safe(&["numconverter", "h", "80", "--any"]).is_err());
+    }
+
+    #[test]
+    fn test_factor() {
+        assert_eq!(run_args(&["numconverter", "--factor", "360", "10", "16"]),
+                   b"Base 10: 360\nBase 16: 168\nPrime: no\nBase 10 factors: 2^3 * 3^2 * 5\nBase 16 factors: 2^3 * 3^2 * 5\n");
+        assert_eq!(run_args(&["numconverter", "--factor", "561", "2", "--bare"]),
+                   b"10_0011_0001\nno\n11 * 1011 * 10001\n");
+        assert_eq!(run_args(&["numconverter", "--factor", "97", "10", "--bare"]), b"97\nyes\n");
+        assert_eq!(run_args(&["numconverter", "12", "--factor", "10", "--bare"]), b"12\nno\n2^2 * 3\n");
+        assert_eq!(run_args(&["numconverter", "--factor", "1", "10"]), b"Base 10: 1\nPrime: no\nFactors: none\n");
+        assert_eq!(run_args(&["numconverter", "--factor", "1", "10", "--bare"]), b"1\nno\nnone\n");
+        let semiprime = (4_294_967_279u128 * 4_294_967_291).to_string();
+        assert_eq!(run_args(&["numconverter", "--factor", &semiprime, "16", "--bare"]),
+                   b"FFFF_FFEA_0000_0055\nno\nFFFFFFEF * FFFFFFFB\n");
+        assert_eq!(run_args(&["numconverter", "--factor", "--factor-timeout-ms", "0", &semiprime, "16", "--bare"]),
+                   b"FFFF_FFEA_0000_0055\nno\ntimeout\n");
+        let json = String::from_utf8(run_args(&["numconverter", "--factor", "12", "10", "--json"])).unwrap();
+        assert!(json.contains(r#""factor":{"prime":false,"timed_out":false,"factors":[{"factor":2,"exponent":2},{"factor":3,"exponent":1}]}"#),
+                "{}", json);
+        let json = String::from_utf8(run_args(&["numconverter", "--factor", "--factor-timeout-ms", "0", &semiprime, "--json"])).unwrap();
+        assert!(json.contains(r#""factor":{"prime":false,"timed_out":true,"factors":null}"#), "{}", json);
+    }
+
+    #[test]
+    fn test_width_needed() {
+        assert_eq!(run_args(&["numconverter", "--width-needed", "255", "16", "10"]),
+                   b"Base 16: FF\nBase 10: 255\nBits needed: 8\nBase 16 digits needed: 2\nBase 10 digits needed: 3\n");
+        assert_eq!(run_args(&["numconverter", "--width-needed", "256", "2", "--bare"]), b"1_0000_0000\n9\n9\n");
+        assert_eq!(run_args(&["numconverter", "--width-needed", "0", "36", "--bare"]), b"0\n1\n1\n");
+        let max = u128::MAX.to_string();
+        assert_eq!(run_args(&["numconverter", "--width-needed", &max, "--only", "10", "--bare", "--no-sep"]),
+                   format!("{}\n", max).into_bytes());
+        let json = String::from_utf8(run_args(&["numconverter", "--width-needed", "1000", "10", "16", "--json"])).unwrap();
+        assert!(json.contains(r#""width_needed":{"bits":10,"digits":[{"base":10,"digits":4},{"base":16,"digits":3}]}"#), "{}", json);
+    }
+
+    #[test]
+    fn test_complement() {
+        assert_eq!(String::from_utf8(run_args(&["numconverter", "--complement", "240", "10", "16"])).unwrap(),
+                   "Base 10: 16\nBase 16: 10\n240 \u{2192} 256 (complement: 16)\n");
+        assert_eq!(run_args(&["numconverter", "--complement", "256", "10", "--bare"]), b"0\n");
+        assert_eq!(run_args(&["numconverter", "--complement", "0", "10", "--bare"]), b"1\n");
+        assert_eq!(run_args(&["numconverter", "--complement", "1000", "2", "--bare"]), b"1_1000\n");
+        assert_eq!(run_args(&["numconverter", "--complement", "--power2-complement-base", "10", "1001", "10", "--bare"]),
+                   b"8999\n");
+        let json = String::from_utf8(run_args(&["numconverter", "--complement", "255", "10", "--json"])).unwrap();
+        assert!(json.contains(r#""complement":{"of":255,"power":256,"value":1}"#), "{}", json);
+        assert_eq!(run_err(&["numconverter", "--complement", &u128::MAX.to_string(), "10"]), ErrorCode::OverflowErr);
+        assert!(Opt::from_iter_safe(&["numconverter", "--power2-complement-base", "10", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_alignment() {
+        assert_eq!(run_args(&["numconverter", "--alignment", "0x1000", "16"]),
+                   b"Base 16: 1000\nAlignment: 12 trailing zero bits, 4096-byte aligned\n");
+        assert_eq!(run_args(&["numconverter", "--alignment", "7", "10", "--bare"]), b"7\n0 trailing zero bits, 1-byte aligned\n");
+        assert_eq!(run_args(&["numconverter", "--alignment", "0", "10"]),
+                   b"Base 10: 0\nAlignment: 0 is aligned to every power of two\n");
+        let json = String::from_utf8(run_args(&["numconverter", "--alignment", "0x1800", "16", "--json"])).unwrap();
+        assert!(json.contains(r#""alignment":{"trailing_zeros":11,"aligned_to":2048}"#), "{}", json);
+    }
+
+    #[test]
+    fn test_aligned_to() {
+        assert_eq!(run_exit(&["numconverter", "0x1000", "--aligned-to", "4096", "--silent"]), (String::new(), 0));
+        assert_eq!(run_exit(&["numconverter", "0x1004", "--aligned-to", "8"]), ("false\n".to_string(), 1));
+        assert_eq!(run_exit(&["numconverter", "h", "1008", "--aligned-to", "0x8"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "0", "--aligned-to", "64"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "24", "--aligned-to", "12"]), ("true\n".to_string(), 0));
+        assert_eq!(run_exit(&["numconverter", "8", "--aligned-to", "0"]), (String::new(), 2));
+        assert!(Opt::from_iter_safe(&["numconverter", "8", "--aligned-to", "8", "--test-bit", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_masks() {
+        assert_eq!(run_args(&["numconverter", "--mask", "0xFF", "0x1234", "16", "--bare"]), b"34\n");
+        assert_eq!(run_args(&["numconverter", "--mask", "FF", "h", "1234", "16", "--bare"]), b"34\n");
+        assert_eq!(run_args(&["numconverter", "--or-mask", "0xFF", "0x1234", "16", "--bare"]), b"12FF\n");
+        assert_eq!(run_args(&["numconverter", "--xor-mask", "0xFF", "0x1234", "16", "--bare"]), b"12CB\n");
+        assert_eq!(run_args(&["numconverter", "--not", "0x1234", "-w", "16", "16", "--bare"]), b"EDCB\n");
+        assert_eq!(run_args(&["numconverter", "--mask", "0", "0x1234", "16", "--bare"]), b"0\n");
+        assert_eq!(run_args(&["numconverter", "--xor-mask", "0", "0x1234", "16", "--bare"]), b"1234\n");
+        let all_ones = "0xFFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF";
+        assert_eq!(run_args(&["numconverter", "--mask", all_ones, "0x1234", "16", "--bare"]), b"1234\n");
+        assert_eq!(run_args(&["numconverter", "--or-mask", all_ones, "0", "16", "--bare", "--no-sep"]),
+                   format!("{:X}\n", u128::MAX).into_bytes());
+        assert_eq!(run_args(&["numconverter", "--not", "0", "16", "--bare", "--no-sep"]), format!("{:X}\n", u128::MAX).into_bytes());
+        assert_eq!(run_err(&["numconverter", "--mask", "0xZZ", "1", "2"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_digit_freq() {
+        assert_eq!(run_args(&["numconverter", "113097", "16", "2", "--digit-freq"]),
+                   b"Base 16: 1_B9C9\n  Digits: 1:1 9:2 B:1 C:1 (5 total)\n\
+                     Base 02: 1_1011_1001_1100_1001\n  Digits: 0:7 1:10 (17 total)\n");
+        assert_eq!(run_args(&["numconverter", "255", "8", "--bare", "--digit-freq", "--all-digits"]),
+                   b"377\n0:0 1:0 2:0 3:1 4:0 5:0 6:0 7:2\n");
+        assert_eq!(run_args(&["numconverter", "255", "16", "--json", "--digit-freq"]),
+                   b"{\"input\":\"255\",\"from_base\":10,\"ok\":true,\"decimal\":255,\"results\":[{\"base\":16,\
+                     \"value\":\"FF\",\"digit_freq\":{\"F\":2},\"digit_total\":2}]}\n");
+    }
+
+    #[test]
+    fn test_unary() {
+        assert_eq!(run_args(&["numconverter", "5", "2", "--unary"]), b"Base 02: 101\nUnary: 11111\n");
+        assert_eq!(run_args(&["numconverter", "0", "2", "--unary", "--bare"]), b"0\n\n");
+        assert_eq!(run_args(&["numconverter", "1", "10", "--unary", "--json"]),
+                   b"{\"input\":\"1\",\"from_base\":10,\"ok\":true,\"decimal\":1,\"results\":[{\"base\":10,\"value\":\"1\"}],\
+                     \"unary\":\"1\"}\n");
+        assert_eq!(run_err(&["numconverter", "1001", "2", "--unary"]), ErrorCode::OverflowErr);
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(run_args(&["numconverter", "--percent=255", "127", "16"]), b"Base 16: 7F\nPercent: 49.80%\n");
+        assert_eq!(run_args(&["numconverter", "--percent", "128", "-w", "8", "10", "--bare"]), b"128\n50.20%\n");
+        assert_eq!(run_args(&["numconverter", "--percent=0x100", "128", "10", "--bare", "--ratio-precision", "3"]),
+                   b"128\n50.000%\n");
+        assert_eq!(run_args(&["numconverter", "--permille=3", "1", "10", "--bare"]), "1\n333.33\u{2030}\n".as_bytes());
+        let json = String::from_utf8(run_args(&["numconverter", "--percent=255", "--permille=255", "127", "16", "--json"])).unwrap();
+        assert!(json.contains("\"percent\":\"49.80%\",\"permille\":\"498.04\u{2030}\""), "{}", json);
+        assert_eq!(run_err(&["numconverter", "--percent=0", "1", "10"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "--permille=0x0", "1", "10"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_metric_prefix() {
+        assert_eq!(run_args(&["numconverter", "--metric-prefix", "1073741824", "16"]),
+                   b"Base 16: 4000_0000\nSI prefix: 1.074 G\nBinary prefix: 1.000 Gi\n");
+        assert_eq!(run_args(&["numconverter", "--metric-prefix", "--metric-base", "10", "1000", "10", "--bare"]),
+                   b"1000\n1.000 k\n");
+        assert_eq!(run_args(&["numconverter", "--metric-prefix", "--metric-base", "2", "h", "100000", "16", "--bare"]),
+                   b"10_0000\n1.000 Mi\n");
+        let json = String::from_utf8(run_args(&["numconverter", "--metric-prefix", "1073741824", "16", "--json"])).unwrap();
+        assert!(json.contains(r#""metric_prefix":{"si":"1.074 G","binary":"1.000 Gi"}"#), "{}", json);
+        assert!(Opt::from_iter_safe(&["numconverter", "--metric-base", "10", "5"]).is_err());
+        assert!(Opt::from_iter_safe(&["numconverter", "--metric-prefix", "--metric-base", "16", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_luhn() {
+        assert_eq!(run_args(&["numconverter", "79927398713", "10", "--luhn", "check"]),
+                   b"Base 10: 799_2739_8713\nLuhn: valid\n");
+        let mut out = Vec::new();
+        let opt = Opt::from_iter(&["numconverter", "79927398710", "10", "--luhn", "check", "--bare"]);
+        assert_eq!(run(&opt, &test_aliases(&opt), &mut out), Err(ErrorCode::CheckErr));
+        assert_eq!(out, b"799_2739_8710\ninvalid\n");
+        assert_eq!(exit_code(&opt, &ErrorCode::CheckErr), 1);
+        assert_eq!(run_args(&["numconverter", "7992739871", "10", "16", "--luhn", "append"]),
+                   b"Base 10: 799_2739_8713\nBase 16: 12_9C0B_5139\nLuhn check digit: 3\n");
+        assert_eq!(run_args(&["numconverter", "7992739871", "10", "--luhn", "append", "--json"]),
+                   b"{\"input\":\"7992739871\",\"from_base\":10,\"ok\":true,\"decimal\":79927398713,\
+                     \"results\":[{\"base\":10,\"value\":\"79927398713\"}],\"luhn_check_digit\":3}\n");
+        assert_eq!(run_err(&["numconverter", "h", "FF", "10", "--luhn", "check"]), ErrorCode::InputBaseErr);
+    }
+
+    #[test]
+    fn test_abbrev() {
+        assert_eq!(run_args(&["numconverter", "1000000", "10", "--abbrev"]), b"Base 10: 100_0000\n  Abbreviated: 1M\n");
+        assert_eq!(run_args(&["numconverter", "255", "10", "--abbrev-only"]), b"Base 10: 255\n");
+        assert_eq!(run_args(&["numconverter", "1024", "2", "10", "--abbrev-only"]), b"Base 02: 1Ki\nBase 10: 1k\n");
+        assert_eq!(run_args(&["numconverter", "1023", "2", "--abbrev-only", "--bare"]), b"1023\n");
+        assert_eq!(run_args(&["numconverter", "1099511627776", "10", "--abbrev-only", "--abbrev-precision", "3", "--bare"]),
+                   b"1.1T\n");
+        assert_eq!(run_args(&["numconverter", "1000000", "10", "--abbrev-only", "--abbrev-style", "decimal", "--bare"]),
+                   b"1_000_000\n");
+        assert_eq!(run_args(&["numconverter", "1000", "16", "--abbrev", "--json"]),
+                   b"{\"input\":\"1000\",\"from_base\":10,\"ok\":true,\"decimal\":1000,\"results\":[{\"base\":16,\
+                     \"value\":\"3E8\",\"abbreviated\":\"1000\"}]}\n");
+    }
+
+    #[test]
+    fn test_hamming() {
+        assert_eq!(run_args(&["numconverter", "h", "DEADBEEF", "16", "2", "--hamming", "0xDEADBFEF"]),
+                   b"Base 16: DEAD_BEEF\nBase 02: 1101_1110_1010_1101_1011_1110_1110_1111\n\
+                     Hamming distance: 1 (XOR 16:100 2:100000000)\n");
+        assert_eq!(run_args(&["numconverter", "b", "1111", "2", "--hamming", "0", "--bare"]), b"1111\n4\n1111\n");
+        assert_eq!(run_args(&["numconverter", "5", "10", "--hamming", "5", "--bare"]), b"5\n0\n0\n");
+        assert_eq!(run_args(&["numconverter", "255", "--hamming", "254", "10", "16", "--bare"]), b"255\nFF\n1\n1\n1\n");
+        assert_eq!(run_args(&["numconverter", "h", "F0", "16", "--hamming", "0x0F", "--json"]),
+                   b"{\"input\":\"F0\",\"from_base\":16,\"ok\":true,\"decimal\":240,\"results\":[{\"base\":16,\"value\":\"F0\"}],\
+                     \"hamming\":{\"distance\":8,\"xor\":255}}\n");
+        assert_eq!(run_err(&["numconverter", "h", "F0", "16", "-w", "8", "--hamming", "0x100"]), ErrorCode::WidthErr);
+    }
+
+    #[test]
+    fn test_parity() {
+        assert_eq!(run_args(&["numconverter", "b", "1011", "2", "--parity", "even"]),
+                   b"Base 02: 1011\nEven parity bit: 1 (3 bits set)\n");
+        assert_eq!(run_args(&["numconverter", "b", "1011", "2", "--parity", "odd", "-w", "8", "--bare"]),
+                   b"1011\n0 (3 bits set)\n");
+        assert_eq!(run_args(&["numconverter", "h", "8B", "16", "--check-parity", "8"]), b"Base 16: 8B\nEven parity: ok\n");
+        let mut out = Vec::new();
+        let opt = Opt::from_iter(&["numconverter", "h", "8B", "16", "--check-parity", "8", "--parity", "odd"]);
+        assert_eq!(run(&opt, &test_aliases(&opt), &mut out), Err(ErrorCode::CheckErr));
+        assert_eq!(out, b"Base 16: 8B\nOdd parity: error\n");
+        assert_eq!(run_args(&["numconverter", "h", "B", "16", "--parity", "even", "--json"]),
+                   b"{\"input\":\"B\",\"from_base\":16,\"ok\":true,\"decimal\":11,\"results\":[{\"base\":16,\"value\":\"B\"}],\
+                     \"parity\":{\"scheme\":\"even\",\"bits_set\":3,\"parity_bit\":1}}\n");
+        assert_eq!(run_err(&["numconverter", "h", "1FF", "16", "--check-parity", "8"]), ErrorCode::WidthErr);
+    }
+
+    #[test]
+    fn test_reverse_bits() {
+        assert_eq!(run_args(&["numconverter", "--reverse-bits", "b", "1101", "-w", "8", "2", "--bare"]), b"1011_0000\n");
+        assert_eq!(run_args(&["numconverter", "--reverse-bits", "h", "1", "-w", "12", "16", "--bare"]), b"800\n");
+        let once = run_args(&["numconverter", "--reverse-bits", "h", "1234", "-w", "16", "16", "--bare"]);
+        assert_eq!(once, b"2C48\n");
+        assert_eq!(run_args(&["numconverter", "--reverse-bits", "h", "2C48", "-w", "16", "16", "--bare"]), b"1234\n");
+        assert!(Opt::from_iter_safe(&["numconverter", "--reverse-bits", "5", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_reverse_bytes() {
+        assert_eq!(run_args(&["numconverter", "--reverse-bytes", "h", "123456", "16", "--bare"]), b"56_3412\n");
+        // Leading zero bytes only count with --width
+        assert_eq!(run_args(&["numconverter", "--reverse-bytes", "h", "001234", "16", "--bare"]), b"3412\n");
+        assert_eq!(run_args(&["numconverter", "--reverse-bytes", "h", "1234", "-w", "32", "16", "--bare"]), b"3412_0000\n");
+        assert_eq!(run_args(&["numconverter", "--reverse-bytes", "h", "1", "10", "--bare"]), b"1\n");
+        // Bits first, then bytes
+        assert_eq!(run_args(&["numconverter", "--reverse-bits", "--reverse-bytes", "h", "0102", "-w", "16", "2", "--bare"]),
+                   b"1000_0000_0100_0000\n");
+    }
+
+    #[test]
+    fn test_crc32() {
+        let digits = "313233343536373839";
+        assert_eq!(run_args(&["numconverter", "--crc32=be", "h", digits, "16", "10"]),
+                   b"Base 16: CBF4_3926\nBase 10: 34_2178_0262\n");
+        assert_eq!(run_args(&["numconverter", "--crc32c=be", "h", digits, "16", "--bare"]), b"E306_9283\n");
+        assert_eq!(run_args(&["numconverter", "--crc32=le", "h", "393837363534333231", "16", "--bare"]), b"CBF4_3926\n");
+        // One zero byte, and the default little endian order
+        assert_eq!(run_args(&["numconverter", "0", "16", "--bare", "--crc32"]), b"D202_EF8D\n");
+        assert_eq!(run_args(&["numconverter", "h", "1234", "-w", "32", "16", "--bare", "--crc32=be"]),
+                   format!("{}\n", insert_separators(&format!("{:X}", checksum::crc32(&[0, 0, 0x12, 0x34])), '_', 4)).into_bytes());
+    }
+
+    #[test]
+    fn test_mod() {
+        assert_eq!(run_args(&["numconverter", "--mod", "0x1000", "h", "DEADBEEF", "16", "--bare"]), b"EEF\n");
+        assert_eq!(run_args(&["numconverter", "--mod", "10", "1234", "10", "--bare"]), b"4\n");
+        assert_eq!(run_args(&["numconverter", "--mod", "0b100", "7", "10", "--bare"]), b"3\n");
+        // After the bit operations
+        assert_eq!(run_args(&["numconverter", "--shift-left", "4", "--mod", "7", "3", "10", "--bare"]), b"6\n");
+        // Euclidean for negative values, truncated on request
+        assert_eq!(run_args(&["numconverter", "--mod", "3", "-7", "10", "-w", "8", "--signed", "--bare"]), b"2\n");
+        assert_eq!(run_args(&["numconverter", "--mod", "7", "-7", "10", "-w", "8", "--signed", "--bare"]), b"0\n");
+        assert_eq!(run_args(&["numconverter", "--mod", "3", "--truncated-mod", "-7", "16", "-w", "8", "--signed",
+                              "--bare"]), b"FF\n");
+        assert_eq!(run_err(&["numconverter", "--mod", "0", "5"]), ErrorCode::BaseConversionErr);
+        assert_eq!(run_err(&["numconverter", "--mod", "0xZZ", "5"]), ErrorCode::BaseConversionErr);
+    }
+
+    #[test]
+    fn test_radix_point_as_fraction() {
+        let as_fraction = |args: &[&str]| {
+            let args = [&["numconverter"][..], args, &["--bare", "--as-fraction"]].concat();
+            String::from_utf8(run_args(&args)).unwrap()
+        };
+        // Denominators that are a power of the input base
+        assert_eq!(as_fraction(&["h", "0.8", "10"]), "1/2\n");
+        assert_eq!(as_fraction(&["h", "0.1", "10", "16"]), "1/16\n1/10\n");
+        assert_eq!(as_fraction(&["0.25", "10"]), "1/4\n");
+        assert_eq!(as_fraction(&["b", "10.11", "10"]), "11/4\n");
+        // And ones that aren't once reduced
+        assert_eq!(as_fraction(&["0.5", "10", "2"]), "1/2\n1/10\n");
+        assert_eq!(as_fraction(&["0.1", "16"]), "1/A\n");
+        assert_eq!(as_fraction(&["1.60", "10"]), "8/5\n");
+        // A whole number has no denominator
+        assert_eq!(as_fraction(&["5.0", "10"]), "5\n");
+        assert_eq!(as_fraction(&["5", "10"]), "5\n");
     }
 
     #[test]
-    fn test_convert_to_base_10() {
-        assert_eq!(convert_to_base_10(Some("10111011".to_owned()), 2, '_'), Ok(187));
-        assert_eq!(convert_to_base_10(Some("273".to_owned()), 8, '_'), Ok(187));
-        assert_eq!(convert_to_base_10(Some("187".to_owned()), 10, '_'), Ok(187));
-        assert_eq!(convert_to_base_10(Some("BB".to_owned()), 16, '_'), Ok(187));
-        assert_eq!(convert_to_base_10(None, 10, '_'), Err(ErrorCode::InputBaseErr));
+    fn test_sep_length_zero() {
+        assert_eq!(run_args(&["numconverter", "h", "FFFF", "2", "--sep-length", "0"]), b"Base 02: 1111111111111111\n");
+        assert_eq!(run_args(&["numconverter", "h", "FFFF", "2", "-l", "8"]), b"Base 02: 11111111_11111111\n");
     }
 }
@@ -0,0 +1,113 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Markdown tables for `--markdown` and `--format markdown` output.
+//!
+//! Every column holds numbers, so they are all right aligned, and cells are
+//! code spans so the `_` separators aren't read as emphasis.
+
+/// The header row with `labels` and the alignment row under it
+pub fn markdown_header(labels: &[String]) -> String {
+    let alignments = vec!["---:"; labels.len()];
+    format!("| {} |\n|{}|", labels.join(" | "), alignments.join("|"))
+}
+
+/// One row of the table, with each cell as a code span
+pub fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells.iter().map(|cell| code_span(cell)).collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn code_span(cell: &str) -> String {
+    format!("`{}`", cell.replace('|', "\\|"))
+}
+
+/// A table of one number, `input`, with a row for each `(base, value)` in
+/// `results` and the columns padded to line up
+pub fn format_as_markdown_table(results: &[(u32, String)], input: &str) -> String {
+    markdown_table(results, input, None)
+}
+
+/// `format_as_markdown_table` with the row for `bold_base` in bold
+pub fn format_as_markdown_table_bold(results: &[(u32, String)], input: &str, bold_base: u32) -> String {
+    markdown_table(results, input, Some(bold_base))
+}
+
+fn markdown_table(results: &[(u32, String)], input: &str, bold_base: Option<u32>) -> String {
+    let mut rows = vec![vec!["Input".to_string(), "Base".to_string(), "Value".to_string()]];
+    for (base, value) in results {
+        let row = vec![code_span(input), base.to_string(), code_span(value)];
+        rows.push(match bold_base {
+            Some(bold) if bold == *base => row.iter().map(|cell| format!("**{}**", cell)).collect(),
+            _                           => row,
+        });
+    }
+    let widths: Vec<usize> = (0..3)
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+    let line = |row: &[String]| {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:>1$}", cell, width)).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let alignments: Vec<String> = widths.iter().map(|&width| "-".repeat(width + 1) + ":").collect();
+    let mut lines = vec![line(&rows[0]), format!("|{}|", alignments.join("|"))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_header() {
+        assert_eq!(markdown_header(&["Input".to_string(), "Base 2".to_string()]),
+                   "| Input | Base 2 |\n|---:|---:|");
+    }
+
+    #[test]
+    fn test_markdown_row() {
+        assert_eq!(markdown_row(&["255".to_string(), "1111_1111".to_string()]), "| `255` | `1111_1111` |");
+        assert_eq!(markdown_row(&["a|b".to_string()]), "| `a\\|b` |");
+    }
+
+    /// The pipes in `line` that separate cells
+    fn cell_pipes(line: &str) -> usize {
+        line.replace("\\|", "").matches('|').count()
+    }
+
+    #[test]
+    fn test_format_as_markdown_table() {
+        let results = vec![(2, "1111_1111".to_string()), (10, "255".to_string()), (16, "FF".to_string())];
+        let table = format_as_markdown_table(&results, "255");
+        assert_eq!(table, "| Input | Base |       Value |\n\
+                           |------:|-----:|------------:|\n\
+                           | `255` |    2 | `1111_1111` |\n\
+                           | `255` |   10 |       `255` |\n\
+                           | `255` |   16 |        `FF` |");
+        // Every row has the same cells and length, as Markdown linters want
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines.iter().all(|line| cell_pipes(line) == 4 && line.len() == lines[0].len()), "{}", table);
+    }
+
+    #[test]
+    fn test_format_as_markdown_table_escapes() {
+        let table = format_as_markdown_table(&[(36, "A|B".to_string())], "x|y");
+        assert_eq!(table.lines().nth(2), Some("| `x\\|y` |   36 | `A\\|B` |"));
+        assert!(table.lines().all(|line| cell_pipes(line) == 4), "{}", table);
+    }
+
+    #[test]
+    fn test_format_as_markdown_table_bold() {
+        let results = vec![(2, "11".to_string()), (16, "3".to_string())];
+        let table = format_as_markdown_table_bold(&results, "3", 16);
+        assert_eq!(table.lines().nth(2), Some("|     `3` |      2 |    `11` |"));
+        assert_eq!(table.lines().nth(3), Some("| **`3`** | **16** | **`3`** |"));
+    }
+}
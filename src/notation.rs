@@ -0,0 +1,159 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Alternative notations for numbers: Morse code and Unicode sub/superscripts.
+
+use crate::ErrorCode;
+
+static SUBSCRIPT_DIGITS:   [char; 10] = ['₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉'];
+static SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+fn to_script_str(s: &str, table: &[char; 10], name: &str) -> Result<String, String> {
+    s.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => Ok(table[d as usize]),
+            None    => Err(format!("'{}' has no Unicode {} form", c, name)),
+        })
+        .collect()
+}
+
+/// Map each decimal digit in `s` to its Unicode subscript form
+pub fn to_subscript_str(s: &str) -> Result<String, String> {
+    to_script_str(s, &SUBSCRIPT_DIGITS, "subscript")
+}
+
+/// Map each decimal digit in `s` to its Unicode superscript form
+pub fn to_superscript_str(s: &str) -> Result<String, String> {
+    to_script_str(s, &SUPERSCRIPT_DIGITS, "superscript")
+}
+
+/// A symbol marking output in `base`, so the bases can be told apart
+/// without color: a shape for bases 2, 8, 10 and 16 and the base in
+/// superscript for the others
+pub fn base_symbol(base: u32) -> String {
+    match base {
+        2  => "▲".to_string(),
+        8  => "●".to_string(),
+        10 => "■".to_string(),
+        16 => "◆".to_string(),
+        _  => base.to_string().chars().map(|c| SUPERSCRIPT_DIGITS[c.to_digit(10).unwrap_or(0) as usize]).collect(),
+    }
+}
+
+static MORSE_DIGITS: [&str; 10] = [
+    "-----", ".----", "..---", "...--", "....-",
+    ".....", "-....", "--...", "---..", "----.",
+];
+
+pub fn digit_to_morse(d: u8) -> &'static str {
+    MORSE_DIGITS[d as usize]
+}
+
+/// Morse code for each decimal digit of `n`, separated by spaces
+pub fn number_to_morse(n: u128) -> String {
+    n.to_string()
+        .bytes()
+        .map(|b| digit_to_morse(b - b'0'))
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Parse a Morse-encoded decimal number.
+///
+/// Digits are separated by spaces; `/` is a word separator and is skipped, so
+/// "..--- / ....-" reads as 24.
+pub fn morse_to_number(s: &str) -> Result<u128, ErrorCode> {
+    let mut num: u128 = 0;
+    let mut digits = 0;
+    for token in s.split(|c: char| c.is_whitespace() || c == '/') {
+        if token.is_empty() {
+            continue;
+        }
+        let digit = match MORSE_DIGITS.iter().position(|&m| m == token) {
+            Some(v) => v as u128,
+            None    => {
+                eprintln!("Invalid Morse digit {}", token);
+                return Err(ErrorCode::BaseConversionErr);
+            },
+        };
+        num = match num.checked_mul(10).and_then(|v| v.checked_add(digit)) {
+            Some(v) => v,
+            None    => {
+                eprintln!("Morse input {} is too large to convert", s);
+                return Err(ErrorCode::BaseConversionErr);
+            },
+        };
+        digits += 1;
+    }
+
+    if digits == 0 {
+        eprintln!("No Morse digits found in {}", s);
+        return Err(ErrorCode::BaseConversionErr);
+    }
+    Ok(num)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_scripts() {
+        assert_eq!(to_subscript_str("0123456789").unwrap(),   "₀₁₂₃₄₅₆₇₈₉");
+        assert_eq!(to_superscript_str("0123456789").unwrap(), "⁰¹²³⁴⁵⁶⁷⁸⁹");
+        assert!(to_subscript_str("1F").is_err());
+        assert!(to_superscript_str("1F").is_err());
+    }
+
+    #[test]
+    fn test_base_symbol() {
+        assert_eq!(base_symbol(2),  "▲");
+        assert_eq!(base_symbol(8),  "●");
+        assert_eq!(base_symbol(10), "■");
+        assert_eq!(base_symbol(16), "◆");
+        assert_eq!(base_symbol(36), "³⁶");
+        assert_eq!(base_symbol(3),  "³");
+    }
+
+    #[test]
+    fn test_digit_to_morse() {
+        assert_eq!(digit_to_morse(0), "-----");
+        assert_eq!(digit_to_morse(1), ".----");
+        assert_eq!(digit_to_morse(2), "..---");
+        assert_eq!(digit_to_morse(3), "...--");
+        assert_eq!(digit_to_morse(4), "....-");
+        assert_eq!(digit_to_morse(5), ".....");
+        assert_eq!(digit_to_morse(6), "-....");
+        assert_eq!(digit_to_morse(7), "--...");
+        assert_eq!(digit_to_morse(8), "---..");
+        assert_eq!(digit_to_morse(9), "----.");
+    }
+
+    #[test]
+    fn test_number_to_morse() {
+        assert_eq!(number_to_morse(0),    "-----");
+        assert_eq!(number_to_morse(42),   "....- ..---");
+        assert_eq!(number_to_morse(1337), ".---- ...-- ...-- --...");
+    }
+
+    #[test]
+    fn test_morse_to_number() {
+        assert_eq!(morse_to_number("....- ..---"), Ok(42));
+        assert_eq!(morse_to_number(".---- ...-- / ...-- --..."), Ok(1337));
+        assert_eq!(morse_to_number(".-.-"), Err(ErrorCode::BaseConversionErr));
+        assert_eq!(morse_to_number(" / "), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_morse_round_trip() {
+        for n in 1000..=9999 {
+            assert_eq!(morse_to_number(&number_to_morse(n)), Ok(n));
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversion settings for using the library the way the command line does.
+
+use crate::{as_string_base, convert_to_base_10, insert_separators, ErrorCode};
+
+/// How to read a number and which bases to write it in.
+///
+/// The defaults match the command line: base 10 input converted to bases 2,
+/// 8, 10 and 16, with a `_` every 4 digits.
+///
+/// ```
+/// use numconverter::options::ConversionOptions;
+///
+/// let results = ConversionOptions::default().convert("255").unwrap();
+/// assert_eq!(results, vec!["1111_1111", "377", "255", "FF"]);
+/// ```
+///
+/// The `with_*` methods change one setting at a time:
+///
+/// ```
+/// use numconverter::options::ConversionOptions;
+///
+/// let options = ConversionOptions::default()
+///     .with_from_base(16)
+///     .with_to_bases(vec![2, 16])
+///     .with_pad(12)
+///     .with_sep_char(' ')
+///     .with_lowercase(true);
+/// assert_eq!(options.convert("ab").unwrap(), vec!["0000 1010 1011", "0000 0000 00ab"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionOptions {
+    pub from_base: u32,
+    pub to_bases: Vec<u32>,
+    /// Put between groups of digits, and ignored in the input
+    pub sep_char: char,
+    /// Digits per group, counting from the right
+    pub sep_length: u32,
+    pub no_sep: bool,
+    /// Add leading zeros up to this many digits
+    pub pad: usize,
+    /// Write digits past 9 as lowercase letters
+    pub lowercase: bool,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            from_base: 10,
+            to_bases: vec![2, 8, 10, 16],
+            sep_char: '_',
+            sep_length: 4,
+            no_sep: false,
+            pad: 0,
+            lowercase: false,
+        }
+    }
+}
+
+impl ConversionOptions {
+    pub fn with_from_base(mut self, base: u32) -> Self {
+        self.from_base = base;
+        self
+    }
+
+    pub fn with_to_bases(mut self, bases: Vec<u32>) -> Self {
+        self.to_bases = bases;
+        self
+    }
+
+    pub fn with_sep_char(mut self, sep_char: char) -> Self {
+        self.sep_char = sep_char;
+        self
+    }
+
+    pub fn with_sep_length(mut self, sep_length: u32) -> Self {
+        self.sep_length = sep_length;
+        self
+    }
+
+    pub fn with_no_sep(mut self, no_sep: bool) -> Self {
+        self.no_sep = no_sep;
+        self
+    }
+
+    pub fn with_pad(mut self, pad: usize) -> Self {
+        self.pad = pad;
+        self
+    }
+
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Read `input` in `from_base` and write it in each of `to_bases`, in order
+    pub fn convert(&self, input: &str) -> Result<Vec<String>, ErrorCode> {
+        let num = convert_to_base_10(Some(input.to_string()), self.from_base, self.sep_char)?;
+        self.to_bases.iter().map(|&base| {
+            let mut digits = as_string_base(&num, base).map_err(|_| ErrorCode::TargetBaseErr)?;
+            if digits.len() < self.pad {
+                digits = "0".repeat(self.pad - digits.len()) + &digits;
+            }
+            if self.lowercase {
+                digits.make_ascii_lowercase();
+            }
+            Ok(if self.no_sep { digits } else { insert_separators(&digits, self.sep_char, self.sep_length) })
+        }).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let options = ConversionOptions::default();
+        assert_eq!(options.from_base, 10);
+        assert_eq!(options.to_bases, vec![2, 8, 10, 16]);
+        assert_eq!(options.convert("65535"), Ok(vec![
+            "1111_1111_1111_1111".to_string(), "17_7777".to_string(), "6_5535".to_string(), "FFFF".to_string(),
+        ]));
+        assert_eq!(options.convert("6_5535"), options.convert("65535"));
+        assert_eq!(options.convert("FF"), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_with_from_base() {
+        let options = ConversionOptions::default().with_from_base(2).with_to_bases(vec![10]);
+        assert_eq!(options.from_base, 2);
+        assert_eq!(options.convert("1010"), Ok(vec!["10".to_string()]));
+    }
+
+    #[test]
+    fn test_with_to_bases() {
+        let options = ConversionOptions::default().with_to_bases(vec![36, 3]);
+        assert_eq!(options.convert("35"), Ok(vec!["Z".to_string(), "1022".to_string()]));
+        let options = options.with_to_bases(vec![37]);
+        assert_eq!(options.convert("35"), Err(ErrorCode::TargetBaseErr));
+    }
+
+    #[test]
+    fn test_with_sep_char() {
+        let options = ConversionOptions::default().with_to_bases(vec![10]).with_sep_char(',');
+        assert_eq!(options.sep_char, ',');
+        assert_eq!(options.convert("1,000,000"), Ok(vec!["100,0000".to_string()]));
+    }
+
+    #[test]
+    fn test_with_sep_length() {
+        let options = ConversionOptions::default().with_to_bases(vec![10]).with_sep_length(3);
+        assert_eq!(options.convert("1000000"), Ok(vec!["1_000_000".to_string()]));
+        let options = options.with_sep_length(0);
+        assert_eq!(options.convert("1000000"), Ok(vec!["1000000".to_string()]));
+    }
+
+    #[test]
+    fn test_with_no_sep() {
+        let options = ConversionOptions::default().with_to_bases(vec![2]).with_no_sep(true);
+        assert!(options.no_sep);
+        assert_eq!(options.convert("255"), Ok(vec!["11111111".to_string()]));
+    }
+
+    #[test]
+    fn test_with_pad() {
+        let options = ConversionOptions::default().with_to_bases(vec![16]).with_pad(4);
+        assert_eq!(options.convert("10"), Ok(vec!["000A".to_string()]));
+        assert_eq!(options.convert("65536"), Ok(vec!["1_0000".to_string()]));
+    }
+
+    #[test]
+    fn test_with_lowercase() {
+        let options = ConversionOptions::default().with_to_bases(vec![16]).with_lowercase(true);
+        assert!(options.lowercase);
+        assert_eq!(options.convert("48879"), Ok(vec!["beef".to_string()]));
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! OSC 52 escape sequences for `--osc52`, which ask the terminal to put a
+//! value on its clipboard. This works over SSH, where there's no local
+//! clipboard to talk to.
+
+use std::{env, fs::OpenOptions, io::Write};
+
+/// The longest base64 payload sent. Terminals drop or truncate sequences
+/// around this size.
+pub const MAX_PAYLOAD: usize = 100_000;
+
+/// GNU screen passes through at most this much of a sequence at a time
+const SCREEN_CHUNK: usize = 76;
+
+/// A terminal multiplexer the sequence has to be passed through
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    /// The multiplexer this process is running in, from `$TMUX` and `$TERM`
+    pub fn detect() -> Self {
+        if env::var_os("TMUX").is_some() {
+            Multiplexer::Tmux
+        } else if env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+            Multiplexer::Screen
+        } else {
+            Multiplexer::None
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with `=` padding
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// The escape sequence that puts `text` on the clipboard, wrapped for `mux`.
+/// Screen gets the payload in chunks small enough for it to pass on.
+pub fn osc52_sequence(text: &str, mux: Multiplexer) -> Result<String, String> {
+    let payload = base64(text.as_bytes());
+    if payload.len() > MAX_PAYLOAD {
+        return Err(format!("{} bytes is too much for the terminal clipboard, the most is {}",
+                           payload.len(), MAX_PAYLOAD));
+    }
+    Ok(match mux {
+        Multiplexer::None => format!("\x1b]52;c;{}\x07", payload),
+        Multiplexer::Tmux => format!("\x1bPtmux;\x1b\x1b]52;c;{}\x07\x1b\\", payload),
+        Multiplexer::Screen => {
+            let chunks: Vec<&str> = payload.as_bytes()
+                .chunks(SCREEN_CHUNK)
+                .map(|c| std::str::from_utf8(c).unwrap())
+                .collect();
+            format!("\x1bP\x1b]52;c;{}\x07\x1b\\", chunks.join("\x1b\\\x1bP"))
+        },
+    })
+}
+
+/// Send `text` to the terminal's clipboard through `/dev/tty`, so it works
+/// when stdout is piped. Without a terminal this only warns.
+pub fn copy_to_terminal(text: &str) {
+    let sequence = match osc52_sequence(text, Multiplexer::detect()) {
+        Ok(v)  => v,
+        Err(e) => {
+            eprintln!("Warning: not copied with --osc52, {}", e);
+            return;
+        },
+    };
+    let written = OpenOptions::new().write(true).open("/dev/tty")
+        .and_then(|mut tty| tty.write_all(sequence.as_bytes()).and_then(|_| tty.flush()));
+    if let Err(e) = written {
+        eprintln!("Warning: no terminal to copy to with --osc52 ({})", e);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(b"DEADBEEF"), "REVBREJFRUY=");
+        assert_eq!(base64(&[0xFF, 0xFE]), "//4=");
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("FF", Multiplexer::None).unwrap(), "\x1b]52;c;RkY=\x07");
+        assert_eq!(osc52_sequence("FF", Multiplexer::Tmux).unwrap(), "\x1bPtmux;\x1b\x1b]52;c;RkY=\x07\x1b\\");
+        assert_eq!(osc52_sequence("FF", Multiplexer::Screen).unwrap(), "\x1bP\x1b]52;c;RkY=\x07\x1b\\");
+    }
+
+    #[test]
+    fn test_osc52_chunks() {
+        // 128 binary digits are 172 base64 characters, three chunks for screen
+        let digits = "1".repeat(128);
+        let sequence = osc52_sequence(&digits, Multiplexer::Screen).unwrap();
+        assert_eq!(sequence.matches("\x1bP").count(), 3);
+        assert_eq!(sequence.replace("\x1b\\\x1bP", ""), format!("\x1bP\x1b]52;c;{}\x07\x1b\\", base64(digits.as_bytes())));
+        assert!(osc52_sequence(&"F".repeat(MAX_PAYLOAD), Multiplexer::None).is_err());
+        assert!(osc52_sequence(&"F".repeat(MAX_PAYLOAD / 4 * 3), Multiplexer::None).is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Chains of conversions, each step reading the digits the last one wrote.
+
+use crate::{as_string_base, convert_to_base_10, ErrorCode};
+
+/// A list of `(from_base, to_base)` steps run one after another.
+///
+/// Each step reads the previous step's digits in its `from_base`, so a step
+/// whose `from_base` isn't the last `to_base` reinterprets the digits rather
+/// than converting the value. `10 -> 16 -> 2` gives the same digits as
+/// `10 -> 2`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConversionPipeline {
+    steps: Vec<(u32, u32)>,
+}
+
+impl ConversionPipeline {
+    /// A pipeline with no steps, which gives back its input
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step from `from_base` to `to_base`
+    pub fn push(&mut self, from_base: u32, to_base: u32) -> &mut Self {
+        self.steps.push((from_base, to_base));
+        self
+    }
+
+    /// The steps, in the order they run
+    pub fn steps(&self) -> &[(u32, u32)] {
+        &self.steps
+    }
+
+    /// Run every step on `input` and return the last step's digits. `_`
+    /// separators in `input` are ignored.
+    pub fn run(&self, input: &str) -> Result<String, ErrorCode> {
+        Ok(match self.run_trace(input)?.pop() {
+            Some((_, digits)) => digits,
+            None              => input.to_string(),
+        })
+    }
+
+    /// Run every step on `input`, returning each step's `to_base` with the
+    /// digits it wrote
+    pub fn run_trace(&self, input: &str) -> Result<Vec<(u32, String)>, ErrorCode> {
+        let mut trace: Vec<(u32, String)> = Vec::with_capacity(self.steps.len());
+        for &(from_base, to_base) in &self.steps {
+            let digits = trace.last().map_or(input, |(_, digits)| digits.as_str());
+            let num = convert_to_base_10(Some(digits.to_string()), from_base, '_')?;
+            let digits = as_string_base(&num, to_base).map_err(|_| ErrorCode::TargetBaseErr)?;
+            trace.push((to_base, digits));
+        }
+        Ok(trace)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let mut pipeline = ConversionPipeline::new();
+        assert_eq!(pipeline.run("255"), Ok("255".to_string()));
+        pipeline.push(10, 16).push(16, 2);
+        assert_eq!(pipeline.steps(), &[(10, 16), (16, 2)]);
+        assert_eq!(pipeline.run("255"), Ok("11111111".to_string()));
+        assert_eq!(pipeline.run("255"), ConversionPipeline::new().push(10, 2).run("255"));
+        assert_eq!(pipeline.run("1_000"), Ok("1111101000".to_string()));
+        assert_eq!(pipeline.run("FF"), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut pipeline = ConversionPipeline::new();
+        pipeline.push(10, 36).push(36, 10);
+        for input in &["0", "35", "1296", "340282366920938463463374607431768211455"] {
+            assert_eq!(pipeline.run(input), Ok(input.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_run_trace() {
+        let mut pipeline = ConversionPipeline::new();
+        pipeline.push(10, 16).push(16, 8).push(8, 2);
+        assert_eq!(pipeline.run_trace("255"),
+                   Ok(vec![(16, "FF".to_string()), (8, "377".to_string()), (2, "11111111".to_string())]));
+        assert_eq!(ConversionPipeline::new().run_trace("255"), Ok(vec![]));
+        // Reading hex digits as decimal reinterprets them
+        assert_eq!(ConversionPipeline::new().push(10, 16).push(10, 2).run_trace("34"),
+                   Ok(vec![(16, "22".to_string()), (2, "10110".to_string())]));
+        assert_eq!(ConversionPipeline::new().push(10, 37).run_trace("1"), Err(ErrorCode::TargetBaseErr));
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Progress lines on stderr for long batch files.
+
+use std::io::{self, IsTerminal};
+
+/// How many conversions between progress lines
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// Counts conversions and prints `Progress: 1000/5000 (20.0%) ...` to
+/// stderr every `interval` of them. On a terminal the line is updated in
+/// place with `\r`.
+pub struct ProgressReporter {
+    pub total:    usize,
+    pub current:  usize,
+    pub interval: usize,
+    enabled: bool,
+    tty:     bool,
+    printed: usize,
+}
+
+impl ProgressReporter {
+    /// A reporter for `total` conversions. It stays quiet unless `enabled`
+    /// and there are more than `interval` conversions.
+    pub fn new(total: usize, interval: usize, enabled: bool) -> Self {
+        ProgressReporter {
+            total,
+            current: 0,
+            interval,
+            enabled: enabled && interval > 0 && total > interval,
+            tty:     io::stderr().is_terminal(),
+            printed: 0,
+        }
+    }
+
+    /// Count one conversion, printing a progress line every `interval`
+    pub fn tick(&mut self) {
+        self.current += 1;
+        if let Some(line) = self.progress_line() {
+            self.print(&line);
+        }
+    }
+
+    /// Print the final count if it wasn't just printed, and end the line on
+    /// a terminal
+    pub fn finish(&mut self) {
+        if !self.enabled || self.printed == self.current {
+            if self.tty && self.printed > 0 {
+                eprintln!();
+            }
+            return;
+        }
+        let line = self.format_line();
+        self.print(&line);
+        if self.tty {
+            eprintln!();
+        }
+    }
+
+    /// The line to print after the current conversion, if any
+    fn progress_line(&self) -> Option<String> {
+        if self.enabled && self.current.is_multiple_of(self.interval) {
+            Some(self.format_line())
+        } else {
+            None
+        }
+    }
+
+    fn format_line(&self) -> String {
+        let percent = if self.total == 0 { 100.0 } else { self.current as f64 * 100.0 / self.total as f64 };
+        format!("Progress: {}/{} ({:.1}%) ...", self.current, self.total, percent)
+    }
+
+    fn print(&mut self, line: &str) {
+        if self.tty {
+            eprint!("\r{}", line);
+        } else {
+            eprintln!("{}", line);
+        }
+        self.printed = self.current;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_intervals() {
+        let mut progress = ProgressReporter::new(5000, 1000, true);
+        let mut lines = Vec::new();
+        for _ in 0..5000 {
+            progress.current += 1;
+            if let Some(line) = progress.progress_line() {
+                lines.push(line);
+            }
+        }
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "Progress: 1000/5000 (20.0%) ...");
+        assert_eq!(lines[4], "Progress: 5000/5000 (100.0%) ...");
+    }
+
+    #[test]
+    fn test_progress_final_count() {
+        let mut progress = ProgressReporter::new(2500, 1000, true);
+        for _ in 0..2500 {
+            progress.tick();
+        }
+        assert_eq!(progress.current, 2500);
+        assert_eq!(progress.printed, 2000);
+        progress.finish();
+        assert_eq!(progress.printed, 2500);
+        assert_eq!(progress.format_line(), "Progress: 2500/2500 (100.0%) ...");
+    }
+
+    #[test]
+    fn test_progress_quiet() {
+        // Too few lines, or turned off with --no-progress or --silent
+        for progress in &mut [ProgressReporter::new(1000, 1000, true), ProgressReporter::new(5000, 1000, false)] {
+            for _ in 0..progress.total {
+                progress.tick();
+            }
+            progress.finish();
+            assert_eq!(progress.printed, 0);
+        }
+    }
+}
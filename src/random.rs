@@ -0,0 +1,104 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Random input values for `--random`, for demos and tests.
+//!
+//! The generator is SplitMix64: small, fast and reproducible from a seed,
+//! but not suitable for anything that needs to be unpredictable.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of random bits
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    fn next_u128(&mut self) -> u128 {
+        ((self.next_u64() as u128) << 64) | self.next_u64() as u128
+    }
+}
+
+/// The SplitMix64 generator
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// A generator that gives the same values every time for `seed`
+    pub fn seed_from_u64(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// A generator seeded from the clock and process id, different each run
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        Self::seed_from_u64(nanos ^ ((std::process::id() as u64) << 32))
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// A value from `min` to `max`, both included, with every value equally
+/// likely. `min` must not be more than `max`.
+pub fn random_number(rng: &mut impl Rng, min: u128, max: u128) -> u128 {
+    let span = max - min;
+    if span == u128::MAX {
+        return rng.next_u128();
+    }
+    let n = span + 1;
+    // Values above the last whole multiple of n would favour the low end
+    let rejected = (u128::MAX % n + 1) % n;
+    loop {
+        let x = rng.next_u128();
+        if x <= u128::MAX - rejected {
+            return min + x % n;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_values() {
+        // The first SplitMix64 outputs for seed 0
+        let mut rng = SplitMix64::seed_from_u64(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+        let mut a = SplitMix64::seed_from_u64(42);
+        let mut b = SplitMix64::seed_from_u64(42);
+        assert_eq!(random_number(&mut a, 0, 1000), random_number(&mut b, 0, 1000));
+    }
+
+    #[test]
+    fn test_random_number_bounds() {
+        let mut rng = SplitMix64::seed_from_u64(7);
+        for _ in 0..1000 {
+            let n = random_number(&mut rng, 10, 20);
+            assert!((10..=20).contains(&n));
+        }
+        assert_eq!(random_number(&mut rng, 5, 5), 5);
+        let mut seen = [false; 4];
+        for _ in 0..100 {
+            seen[random_number(&mut rng, 0, 3) as usize] = true;
+        }
+        assert_eq!(seen, [true; 4]);
+        random_number(&mut rng, 0, u128::MAX);
+        assert!(random_number(&mut rng, u128::MAX - 1, u128::MAX) >= u128::MAX - 1);
+    }
+}
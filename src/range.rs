@@ -0,0 +1,209 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Spans of values for `--range`.
+
+use std::{fmt, str::FromStr};
+use crate::{parse_line_with_prefix, ErrorCode};
+
+/// The most values `--range` converts unless `--limit` says otherwise
+pub const DEFAULT_RANGE_LIMIT: usize = 65536;
+
+/// How `--step` gets from one value of a range to the next
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    /// Add N, from `N` or `+N`
+    Add(u128),
+    /// Multiply by N, from `*N`, for 1, 2, 4, 8, ...
+    Multiply(u128),
+}
+
+impl Default for Step {
+    fn default() -> Self {
+        Step::Add(1)
+    }
+}
+
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (step, digits): (fn(u128) -> Step, &str) = match s.strip_prefix('*') {
+            Some(digits) => (Step::Multiply, digits),
+            None         => (Step::Add, s.strip_prefix('+').unwrap_or(s)),
+        };
+        match parse_line_with_prefix(digits, 10) {
+            Ok((n, _)) => Ok(step(n)),
+            Err(_)     => Err(format!("Invalid step {}, expected N or *N", s)),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Add(n)      => write!(f, "{}", n),
+            Step::Multiply(n) => write!(f, "*{}", n),
+        }
+    }
+}
+
+/// A span of values, `START..END` without END or `START..=END` with it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeSpec {
+    pub start: u128,
+    pub end: u128,
+    pub inclusive: bool,
+}
+
+impl RangeSpec {
+    /// Parse `START..END` or `START..=END`. Either bound can have a base
+    /// prefix (see `split_base_prefix`), otherwise it's in `default_base`.
+    pub fn parse(spec: &str, default_base: u32) -> Result<RangeSpec, ErrorCode> {
+        let (start, end, inclusive) = match spec.find("..") {
+            Some(i) if spec[i + 2..].starts_with('=') => (&spec[..i], &spec[i + 3..], true),
+            Some(i)                                    => (&spec[..i], &spec[i + 2..], false),
+            None => {
+                eprintln!("Invalid range {}, expected START..END or START..=END", spec);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        };
+        let (start, _) = parse_line_with_prefix(start, default_base)?;
+        let (end, _) = parse_line_with_prefix(end, default_base)?;
+        if start > end {
+            eprintln!("Range {} ends before it starts", spec);
+            return Err(ErrorCode::InputBaseErr);
+        }
+        Ok(RangeSpec { start, end, inclusive })
+    }
+
+    /// How many values are in the range
+    pub fn len(&self) -> u128 {
+        let len = self.end - self.start;
+        if self.inclusive { len.saturating_add(1) } else { len }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `value` is before END, or is END for `START..=END`
+    fn contains(&self, value: u128) -> bool {
+        value < self.end || (self.inclusive && value == self.end)
+    }
+
+    /// The values from START by `step` that are in the range. Steps that
+    /// would never reach END, or give more than `limit` values, are an
+    /// error before any values are made.
+    pub fn values(&self, step: Step, limit: usize) -> Result<Vec<u128>, ErrorCode> {
+        let count = match step {
+            Step::Add(0) | Step::Multiply(0) | Step::Multiply(1) => None,
+            Step::Multiply(_) if self.start == 0 && self.contains(0) => None,
+            Step::Add(n) => Some(self.len().div_ceil(n)),
+            // At most 128 doublings before a u128 overflows
+            Step::Multiply(n) => Some(self.geometric(n, usize::MAX).len() as u128),
+        };
+        let count = match count {
+            Some(v) => v,
+            None    => {
+                eprintln!("--step {} never reaches the end of the range", step);
+                return Err(ErrorCode::InputBaseErr);
+            },
+        };
+        if count > limit as u128 {
+            eprintln!("Range has {} values, more than the limit of {} (see --limit)", count, limit);
+            return Err(ErrorCode::InputBaseErr);
+        }
+        Ok(match step {
+            Step::Add(n)      => (0..count).map(|i| self.start + i * n).collect(),
+            Step::Multiply(n) => self.geometric(n, count as usize),
+        })
+    }
+
+    /// Up to `max` values START, START * n, START * n^2, ... in the range
+    fn geometric(&self, n: u128, max: usize) -> Vec<u128> {
+        let mut values = Vec::new();
+        let mut value = Some(self.start);
+        while let Some(v) = value.filter(|&v| self.contains(v) && values.len() < max) {
+            values.push(v);
+            value = v.checked_mul(n);
+        }
+        values
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(RangeSpec::parse("0..16", 10), Ok(RangeSpec { start: 0, end: 16, inclusive: false }));
+        assert_eq!(RangeSpec::parse("0..=0xFF", 10), Ok(RangeSpec { start: 0, end: 255, inclusive: true }));
+        assert_eq!(RangeSpec::parse("10..20", 16), Ok(RangeSpec { start: 16, end: 32, inclusive: false }));
+        assert_eq!(RangeSpec::parse("0b100..2:1000", 10).map(|r| (r.start, r.end)), Ok((4, 8)));
+        assert_eq!(RangeSpec::parse("16", 10), Err(ErrorCode::InputBaseErr));
+        assert_eq!(RangeSpec::parse("5..2", 10), Err(ErrorCode::InputBaseErr));
+        assert_eq!(RangeSpec::parse("0..G", 16), Err(ErrorCode::BaseConversionErr));
+    }
+
+    #[test]
+    fn test_range_values() {
+        let step = Step::default();
+        assert_eq!(RangeSpec::parse("0..4", 10).unwrap().values(step, 10), Ok(vec![0, 1, 2, 3]));
+        assert_eq!(RangeSpec::parse("0..=4", 10).unwrap().values(step, 10), Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(RangeSpec::parse("3..3", 10).unwrap().values(step, 10), Ok(vec![]));
+        assert_eq!(RangeSpec::parse("0..=255", 10).unwrap().len(), 256);
+        assert_eq!(RangeSpec::parse("0..11", 10).unwrap().values(step, 10), Err(ErrorCode::InputBaseErr));
+        assert_eq!(RangeSpec::parse("0..10", 10).unwrap().values(step, 10).map(|v| v.len()), Ok(10));
+        let everything = RangeSpec { start: 0, end: u128::MAX, inclusive: true };
+        assert_eq!(everything.len(), u128::MAX);
+        assert!(everything.values(step, DEFAULT_RANGE_LIMIT).is_err());
+    }
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!("4".parse::<Step>(), Ok(Step::Add(4)));
+        assert_eq!("+4".parse::<Step>(), Ok(Step::Add(4)));
+        assert_eq!("*2".parse::<Step>(), Ok(Step::Multiply(2)));
+        assert_eq!("0x10".parse::<Step>(), Ok(Step::Add(16)));
+        assert!("*".parse::<Step>().is_err());
+        assert!("-1".parse::<Step>().is_err());
+        assert_eq!(Step::Multiply(2).to_string(), "*2");
+    }
+
+    #[test]
+    fn test_arithmetic_step() {
+        let values = |spec, step| RangeSpec::parse(spec, 10).unwrap().values(Step::Add(step), 100);
+        assert_eq!(values("0..16", 4), Ok(vec![0, 4, 8, 12]));
+        // END is only included by ..= and when the step lands on it
+        assert_eq!(values("0..=16", 4), Ok(vec![0, 4, 8, 12, 16]));
+        assert_eq!(values("0..=15", 4), Ok(vec![0, 4, 8, 12]));
+        assert_eq!(values("0..17", 4), Ok(vec![0, 4, 8, 12, 16]));
+        assert_eq!(values("5..6", 10), Ok(vec![5]));
+        assert_eq!(values("0..10", 0), Err(ErrorCode::InputBaseErr));
+        assert_eq!(RangeSpec::parse("0..1000", 10).unwrap().values(Step::Add(10), 99), Err(ErrorCode::InputBaseErr));
+        assert_eq!(RangeSpec::parse("0..1000", 10).unwrap().values(Step::Add(10), 100).map(|v| v.len()), Ok(100));
+    }
+
+    #[test]
+    fn test_geometric_step() {
+        let values = |spec, step| RangeSpec::parse(spec, 10).unwrap().values(Step::Multiply(step), 100);
+        assert_eq!(values("1..=4096", 2).map(|v| v.len()), Ok(13));
+        assert_eq!(values("1..=4096", 2).unwrap().last(), Some(&4096));
+        assert_eq!(values("1..4096", 2).unwrap().last(), Some(&2048));
+        assert_eq!(values("3..100", 3), Ok(vec![3, 9, 27, 81]));
+        assert_eq!(values("0..=0xFF", 2), Err(ErrorCode::InputBaseErr));
+        assert_eq!(values("1..100", 1), Err(ErrorCode::InputBaseErr));
+        assert_eq!(values("1..100", 0), Err(ErrorCode::InputBaseErr));
+        let everything = RangeSpec { start: 1, end: u128::MAX, inclusive: true };
+        assert_eq!(everything.values(Step::Multiply(2), 128).map(|v| v.len()), Ok(128));
+        assert_eq!(everything.values(Step::Multiply(2), 127), Err(ErrorCode::InputBaseErr));
+    }
+}
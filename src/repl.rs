@@ -0,0 +1,324 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The prompt for `--interactive`: its `:` commands, variables and the
+//! history kept between sessions.
+
+use std::{collections::HashMap, env, fs, io::{self, BufRead, IsTerminal, Write}, path::{Path, PathBuf}};
+
+const PROMPT: &str = "> ";
+
+/// How many of the latest entries `:history` lists
+const HISTORY_LISTED: usize = 20;
+
+/// Where the history is kept, in `$XDG_DATA_HOME` or `~/.local/share`
+pub fn history_file() -> Option<PathBuf> {
+    let data = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(data.join("numconverter").join("history"))
+}
+
+/// The lines entered at the prompt, oldest first
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    max: usize,
+}
+
+impl History {
+    /// An empty history keeping at most `max` lines
+    pub fn new(max: usize) -> Self {
+        History { entries: Vec::new(), max }
+    }
+
+    /// The history saved at `path`, or an empty one if there isn't a file yet
+    pub fn load(path: &Path, max: usize) -> io::Result<Self> {
+        let mut history = History::new(max);
+        match fs::read_to_string(path) {
+            Ok(contents) => contents.lines().for_each(|line| history.add(line)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        Ok(history)
+    }
+
+    /// Write the history to `path`, readable only by the user
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        for entry in &self.entries {
+            writeln!(file, "{}", entry)?;
+        }
+        Ok(())
+    }
+
+    /// Add a line, dropping the oldest once there are more than the maximum.
+    /// Blank lines and repeats of the last line aren't added.
+    pub fn add(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > self.max {
+            let extra = self.entries.len() - self.max;
+            self.entries.drain(..extra);
+        }
+    }
+
+    /// Entry `index`, counting from 1 for the oldest as `:history` does
+    pub fn get(&self, index: usize) -> Option<&str> {
+        index.checked_sub(1).and_then(|i| self.entries.get(i)).map(String::as_str)
+    }
+
+    /// The latest entries with their indices, for `:history`
+    pub fn recent(&self) -> impl Iterator<Item = (usize, &str)> {
+        let skip = self.entries.len().saturating_sub(HISTORY_LISTED);
+        self.entries.iter().enumerate().skip(skip).map(|(i, entry)| (i + 1, entry.as_str()))
+    }
+
+    /// The entry `back` before the end, 1 for the latest, for Up and Down
+    #[cfg(all(feature = "interactive", unix))]
+    pub fn latest(&self, back: usize) -> Option<&str> {
+        self.entries.len().checked_sub(back).and_then(|i| self.entries.get(i)).map(String::as_str)
+    }
+}
+
+/// Where the prompt's lines come from
+pub trait ReadLine {
+    /// The next line, or `None` once there are no more. `history` is what
+    /// Up and Down step through where there's line editing.
+    fn read_line(&mut self, history: &History) -> io::Result<Option<String>>;
+}
+
+/// Lines read as they are, writing the prompt to stdout first if `prompt`
+pub struct PlainLines<R> {
+    pub input: R,
+    pub prompt: bool,
+}
+
+impl<R: BufRead> ReadLine for PlainLines<R> {
+    fn read_line(&mut self, _history: &History) -> io::Result<Option<String>> {
+        if self.prompt {
+            let mut stdout = io::stdout();
+            write!(stdout, "{}", PROMPT)?;
+            stdout.flush()?;
+        }
+        let mut line = String::new();
+        match self.input.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+}
+
+/// Lines from stdin, with line editing when it's a terminal and numconverter
+/// is built with the interactive feature
+pub fn stdin_lines() -> Box<dyn ReadLine> {
+    let terminal = io::stdin().is_terminal();
+    #[cfg(all(feature = "interactive", unix))]
+    {
+        if terminal {
+            return Box::new(crate::line_editor::LineEditor::new(PROMPT));
+        }
+    }
+    Box::new(PlainLines { input: io::stdin().lock(), prompt: terminal })
+}
+
+/// Values named at the prompt with `NAME = ...`, and `$ans`, the value of
+/// the last line that converted
+#[derive(Debug, Default)]
+pub struct Variables {
+    values: HashMap<String, u128>,
+    ans: Option<u128>,
+}
+
+impl Variables {
+    /// The value `word` names, as `NAME`, `$NAME` or `$ans`
+    pub fn get(&self, word: &str) -> Option<u128> {
+        match word {
+            "$ans" => self.ans,
+            _      => self.values.get(word.strip_prefix('$').unwrap_or(word)).copied(),
+        }
+    }
+
+    /// Check `name` can be a variable: an identifier that isn't `ans` and
+    /// isn't a base by `is_base`, so it can't be mistaken for a base_char
+    pub fn check_name(name: &str, is_base: impl Fn(&str) -> bool) -> Result<(), String> {
+        if name == "ans" {
+            Err("ans is kept for $ans, the last value converted".to_string())
+        } else if is_base(name) {
+            Err(format!("{} is already a base, pick another name", name))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: u128) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn set_ans(&mut self, value: u128) {
+        self.ans = Some(value);
+    }
+
+    /// Every variable and its value by name, then `$ans`, for `:vars`
+    pub fn list(&self) -> Vec<(String, u128)> {
+        let mut list: Vec<(String, u128)> = self.values.iter().map(|(name, &value)| (name.clone(), value)).collect();
+        list.sort();
+        list.extend(self.ans.map(|ans| ("$ans".to_string(), ans)));
+        list
+    }
+
+    /// Put the value of a variable used as the number in `words` in its
+    /// place, in decimal. The variable can be the first word or follow a
+    /// base_char, which is dropped since a variable is already a value.
+    pub fn substitute(&self, words: &[&str], is_base: impl Fn(&str) -> bool) -> Result<Vec<String>, String> {
+        let at = match words {
+            [first, ..] if !is_base(first) => 0,
+            [_, _, ..]                     => 1,
+            _                              => return Ok(words.iter().map(|w| w.to_string()).collect()),
+        };
+        let value = match self.get(words[at]) {
+            Some(v)                            => v,
+            None if words[at].starts_with('$') => return Err(format!("{} isn't set", words[at])),
+            None                               => return Ok(words.iter().map(|w| w.to_string()).collect()),
+        };
+        Ok(["d".to_string(), value.to_string()].iter().cloned()
+            .chain(words[at + 1..].iter().map(|w| w.to_string()))
+            .collect())
+    }
+}
+
+/// Whether `name` is a simple identifier, so `NAME = ...` is an assignment
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A line entered at the prompt
+#[derive(Debug, PartialEq)]
+pub enum Command<'a> {
+    /// Numbers to convert, written like the positional arguments
+    Convert(&'a str),
+    /// `NAME = ...`, convert the rest of the line and keep its value as NAME
+    Assign(&'a str, &'a str),
+    /// `:vars`
+    Vars,
+    /// `:history`
+    History,
+    /// `:!N`, run history entry N again
+    Rerun(usize),
+    /// `:quit` or `:q`
+    Quit,
+    Unknown(&'a str),
+}
+
+impl<'a> Command<'a> {
+    pub fn parse(line: &'a str) -> Self {
+        let line = line.trim();
+        let command = match line.strip_prefix(':') {
+            Some(v) => v,
+            None    => return match line.split_once('=') {
+                Some((name, rest)) if is_identifier(name.trim().trim_start_matches('$')) => {
+                    Command::Assign(name.trim().trim_start_matches('$'), rest.trim())
+                },
+                _ => Command::Convert(line),
+            },
+        };
+        match command {
+            "history"    => Command::History,
+            "vars"       => Command::Vars,
+            "quit" | "q" => Command::Quit,
+            _ => match command.strip_prefix('!').and_then(|n| n.parse().ok()) {
+                Some(n) => Command::Rerun(n),
+                None    => Command::Unknown(line),
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_add() {
+        let mut history = History::new(3);
+        for line in ["h FF", "h FF", "", "  ", "b 101", "d 10 16", "o 17"] {
+            history.add(line);
+        }
+        // Repeats and blank lines are skipped, and the oldest dropped over the cap
+                assert_eq!(history.get(1), Some("b 101"));
+        assert_eq!(history.get(3), Some("o 17"));
+        assert_eq!(history.get(0), None);
+        assert_eq!(history.get(4), None);
+        assert_eq!(history.recent().collect::<Vec<_>>(), [(1, "b 101"), (2, "d 10 16"), (3, "o 17")]);
+    }
+
+    #[test]
+    fn test_history_file() {
+        let path = env::temp_dir().join(format!("numconverter-history-{}", std::process::id())).join("history");
+        assert_eq!(History::load(&path, 10).unwrap().recent().count(), 0);
+        let mut history = History::new(10);
+        history.add("h FF");
+        history.add("b 1010 8");
+        history.save(&path).unwrap();
+        let loaded = History::load(&path, 1).unwrap();
+        assert_eq!(loaded.recent().collect::<Vec<_>>(), [(1, "b 1010 8")]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(Command::parse(" h FF 2 "), Command::Convert("h FF 2"));
+        assert_eq!(Command::parse(":history"), Command::History);
+        assert_eq!(Command::parse(":!3"), Command::Rerun(3));
+        assert_eq!(Command::parse(":q"), Command::Quit);
+        assert_eq!(Command::parse(":!x"), Command::Unknown(":!x"));
+        assert_eq!(Command::parse(":vars"), Command::Vars);
+        assert_eq!(Command::parse(":ans"), Command::Unknown(":ans"));
+        assert_eq!(Command::parse("x = h FF"), Command::Assign("x", "h FF"));
+        assert_eq!(Command::parse("$mask=0xF0"), Command::Assign("mask", "0xF0"));
+        assert_eq!(Command::parse("h 1=2"), Command::Convert("h 1=2"));
+        assert_eq!(Command::parse("2x = 3"), Command::Convert("2x = 3"));
+    }
+
+    #[test]
+    fn test_variables() {
+        let is_base = |w: &str| ["h", "b", "d", "hex"].contains(&w);
+        let mut vars = Variables::default();
+        assert_eq!(vars.substitute(&["h", "FF", "2"], is_base), Ok(vec!["h".into(), "FF".into(), "2".into()]));
+        assert!(vars.substitute(&["$ans", "2"], is_base).is_err());
+        vars.set("mask", 0xF0);
+        vars.set_ans(255);
+        assert_eq!(vars.substitute(&["mask", "2"], is_base), Ok(vec!["d".into(), "240".into(), "2".into()]));
+        assert_eq!(vars.substitute(&["h", "$mask"], is_base), Ok(vec!["d".into(), "240".into()]));
+        assert_eq!(vars.substitute(&["$ans", "16"], is_base), Ok(vec!["d".into(), "255".into(), "16".into()]));
+        assert_eq!(vars.substitute(&["other"], is_base), Ok(vec!["other".into()]));
+        assert_eq!(vars.list(), [("mask".to_string(), 240), ("$ans".to_string(), 255)]);
+        assert!(Variables::check_name("hex", is_base).is_err());
+        assert!(Variables::check_name("ans", is_base).is_err());
+        assert!(Variables::check_name("total", is_base).is_ok());
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Number properties printed by `--stats`.
+
+use std::collections::HashMap;
+
+/// Factorization is done by trial division, so `largest_prime_factor` and
+/// `euler_totient` are only run on values below this.
+pub const FACTOR_LIMIT: u128 = 1_000_000_000_000_000;
+
+/// Sum of the base 10 digits of `n`
+pub fn digit_sum(n: u128) -> u32 {
+    let mut sum = 0;
+    let mut tmp = n;
+    while tmp > 0 {
+        sum += (tmp % 10) as u32;
+        tmp /= 10;
+    }
+    sum
+}
+
+/// Repeated digit sum of `n` down to a single base 10 digit
+pub fn digital_root(n: u128) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        1 + ((n - 1) % 9) as u32
+    }
+}
+
+pub fn is_even(n: u128) -> bool {
+    n.is_multiple_of(2)
+}
+
+/// Largest `r` such that `r * r <= n`
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method, starting from a power of two above the root
+    let mut x: u128 = 1 << ((128 - n.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Largest `r` such that `r * r * r <= n`
+pub fn icbrt(n: u128) -> u128 {
+    let mut lo: u128 = 0;
+    let mut hi: u128 = 1 << 43;
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        match mid.checked_mul(mid).and_then(|sq| sq.checked_mul(mid)) {
+            Some(cube) if cube <= n => lo = mid,
+            _ => hi = mid - 1,
+        }
+    }
+    lo
+}
+
+pub fn is_perfect_square(n: u128) -> bool {
+    let r = isqrt(n);
+    r * r == n
+}
+
+pub fn is_perfect_cube(n: u128) -> bool {
+    let r = icbrt(n);
+    r * r * r == n
+}
+
+/// Prime factors of `n` with their exponents, smallest first.
+///
+/// Uses trial division, so this is slow for values with large prime factors.
+pub fn prime_factors(n: u128) -> Vec<(u128, u32)> {
+    let mut factors = Vec::new();
+    let mut tmp = n;
+    let mut p: u128 = 2;
+    while tmp > 1 && p * p <= tmp {
+        let mut exp = 0;
+        while tmp.is_multiple_of(p) {
+            tmp /= p;
+            exp += 1;
+        }
+        if exp > 0 {
+            factors.push((p, exp));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if tmp > 1 {
+        factors.push((tmp, 1));
+    }
+    factors
+}
+
+/// Largest prime factor of `n`, or `None` for 0 and 1
+pub fn largest_prime_factor(n: u128) -> Option<u128> {
+    if n < 2 {
+        return None;
+    }
+    prime_factors(n).last().map(|&(p, _)| p)
+}
+
+/// Euler's totient φ(n), the count of integers in 1..=n coprime to `n`
+pub fn euler_totient(n: u128) -> u128 {
+    prime_factors(n)
+        .iter()
+        .fold(n, |phi, &(p, _)| phi / p * (p - 1))
+}
+
+/// The Shannon entropy of the digits of `s` in bits per digit,
+/// `-Σ p(d) log₂ p(d)` over the frequency `p(d)` of each digit. All the same
+/// digit is 0, and each of a base's digits equally often is `log₂(base)`.
+pub fn shannon_entropy(s: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for ch in s.chars() {
+        *counts.entry(ch).or_insert(0) += 1;
+    }
+    let len = s.chars().count() as f64;
+    counts.values().fold(0.0, |entropy, &count| {
+        let p = count as f64 / len;
+        entropy + p * (1.0 / p).log2()
+    })
+}
+
+/// How many times each digit of `base` occurs in `s`, in digit order.
+/// Digits that don't occur are left out unless `all_digits`.
+pub fn digit_frequencies(s: &str, base: u32, all_digits: bool) -> Vec<(char, usize)> {
+    (0..base)
+        .filter_map(|d| std::char::from_digit(d, base))
+        .map(|d| d.to_ascii_uppercase())
+        .map(|d| (d, s.chars().filter(|&c| c.to_ascii_uppercase() == d).count()))
+        .filter(|&(_, count)| all_digits || count > 0)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_small() {
+        assert_eq!(digit_sum(1), 1);
+        assert_eq!(digital_root(1), 1);
+        assert!(!is_even(1));
+        assert!(is_perfect_square(1));
+        assert!(is_perfect_cube(1));
+        assert_eq!(largest_prime_factor(1), None);
+        assert_eq!(euler_totient(1), 1);
+
+        assert_eq!(digit_sum(2), 2);
+        assert!(is_even(2));
+        assert!(!is_perfect_square(2));
+        assert!(!is_perfect_cube(2));
+        assert_eq!(largest_prime_factor(2), Some(2));
+        assert_eq!(euler_totient(2), 1);
+
+        assert_eq!(digit_sum(12), 3);
+        assert_eq!(digital_root(12), 3);
+        assert_eq!(largest_prime_factor(12), Some(3));
+        assert_eq!(euler_totient(12), 4);
+    }
+
+    #[test]
+    fn test_stats_powers() {
+        assert_eq!(digit_sum(100), 1);
+        assert!(is_perfect_square(100));
+        assert!(!is_perfect_cube(100));
+        assert_eq!(largest_prime_factor(100), Some(5));
+        assert_eq!(euler_totient(100), 40);
+
+        assert_eq!(digit_sum(1024), 7);
+        assert_eq!(digital_root(1024), 7);
+        assert!(is_perfect_square(1024));
+        assert!(!is_perfect_cube(1024));
+        assert_eq!(largest_prime_factor(1024), Some(2));
+        assert_eq!(euler_totient(1024), 512);
+
+        assert!(is_perfect_cube(1 << 126));
+        assert!(!is_perfect_square(u128::MAX));
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("FF"), 0.0);
+        assert_eq!(shannon_entropy("1111111"), 0.0);
+        assert_eq!(shannon_entropy("10"), 1.0);
+        assert_eq!(shannon_entropy("0123456789ABCDEF"), 4.0);
+        assert!((shannon_entropy("0123456789") - 10f64.log2()).abs() < 1e-12);
+        // 3/4 and 1/4
+        assert!((shannon_entropy("1110") - 0.811_278_124_459_132_9).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_digit_frequencies() {
+        assert_eq!(digit_frequencies("1111_1111", 2, false), vec![('1', 8)]);
+        assert_eq!(digit_frequencies("1111_1111", 2, true), vec![('0', 0), ('1', 8)]);
+        assert_eq!(digit_frequencies("B9C9", 16, false), vec![('9', 2), ('B', 1), ('C', 1)]);
+        assert_eq!(digit_frequencies("", 10, false), vec![]);
+        assert_eq!(digit_frequencies("0", 36, true).len(), 36);
+    }
+
+    #[test]
+    fn test_stats_large_prime() {
+        let p = 999_999_999_999_989;
+        assert!(p < FACTOR_LIMIT);
+        assert_eq!(digital_root(p), 8);
+        assert_eq!(largest_prime_factor(p), Some(p));
+        assert_eq!(euler_totient(p), p - 1);
+    }
+}
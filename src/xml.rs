@@ -0,0 +1,140 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! XML documents for `--format xml` output.
+
+/// Escape `s` for a quoted XML attribute value
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&'  => escaped.push_str("&amp;"),
+            '<'  => escaped.push_str("&lt;"),
+            '>'  => escaped.push_str("&gt;"),
+            '"'  => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c    => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// An XML document of one number, `input` in `from_base`, with a `result`
+/// element for each `(base, value)` in `results`. With an `indent`, each
+/// `result` is on a line of its own, indented that many spaces.
+pub fn format_as_xml(results: &[(u32, String)], input: &str, from_base: u32, indent: Option<usize>) -> String {
+    let (newline, pad) = match indent {
+        Some(n) => ("\n", " ".repeat(n)),
+        None    => ("", String::new()),
+    };
+    let mut xml = format!("<?xml version=\"1.0\"?>\n<conversion input=\"{}\" from_base=\"{}\">", escape(input), from_base);
+    for (base, value) in results {
+        xml.push_str(&format!("{}{}<result base=\"{}\" value=\"{}\"/>", newline, pad, base, escape(value)));
+    }
+    xml + newline + "</conversion>"
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An element of a parsed document: its name, attributes and children
+    #[derive(Debug, PartialEq)]
+    struct Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<Element>,
+    }
+
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+    }
+
+    /// Parse the elements of `xml` after its declaration, panicking if it
+    /// isn't well formed
+    fn parse(xml: &str) -> Element {
+        let body = xml.strip_prefix("<?xml version=\"1.0\"?>\n").expect("no XML declaration");
+        let mut stack: Vec<Element> = Vec::new();
+        let mut root = None;
+        let mut rest = body.trim_start();
+        while !rest.is_empty() {
+            assert!(rest.starts_with('<'), "text outside a tag: {}", rest);
+            let end = rest.find('>').expect("unclosed tag");
+            let tag = &rest[1..end];
+            rest = rest[end + 1..].trim_start();
+            if let Some(name) = tag.strip_prefix('/') {
+                let element = stack.pop().expect("close tag without an open one");
+                assert_eq!(element.name, name);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None         => root = Some(element),
+                }
+                continue;
+            }
+            let empty = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let name_end = tag.find(' ').unwrap_or(tag.len());
+            let mut attributes = Vec::new();
+            let mut attrs = &tag[name_end..];
+            while let Some(eq) = attrs.find("=\"") {
+                let close = attrs[eq + 2..].find('"').expect("unquoted attribute") + eq + 2;
+                attributes.push((attrs[..eq].trim().to_string(), unescape(&attrs[eq + 2..close])));
+                attrs = &attrs[close + 1..];
+            }
+            assert!(attrs.trim().is_empty(), "junk in tag: {}", attrs);
+            let element = Element { name: tag[..name_end].to_string(), attributes, children: Vec::new() };
+            match (empty, stack.last_mut()) {
+                (true, Some(parent)) => parent.children.push(element),
+                (true, None)         => root = Some(element),
+                (false, _)           => stack.push(element),
+            }
+        }
+        assert!(stack.is_empty(), "unclosed elements");
+        root.expect("no root element")
+    }
+
+    fn attrs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_format_as_xml() {
+        let results = vec![(2, "11111111".to_string()), (16, "FF".to_string())];
+        assert_eq!(format_as_xml(&results, "255", 10, None),
+                   "<?xml version=\"1.0\"?>\n<conversion input=\"255\" from_base=\"10\">\
+                    <result base=\"2\" value=\"11111111\"/><result base=\"16\" value=\"FF\"/></conversion>");
+        assert_eq!(format_as_xml(&results, "255", 10, Some(2)),
+                   "<?xml version=\"1.0\"?>\n<conversion input=\"255\" from_base=\"10\">\n  \
+                    <result base=\"2\" value=\"11111111\"/>\n  <result base=\"16\" value=\"FF\"/>\n</conversion>");
+        assert_eq!(format_as_xml(&[], "0", 16, Some(4)),
+                   "<?xml version=\"1.0\"?>\n<conversion input=\"0\" from_base=\"16\">\n</conversion>");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let results = vec![(36, "<&>".to_string()), (64, "a\"b'c".to_string()), (10, "1_000".to_string())];
+        for &indent in &[None, Some(0), Some(3)] {
+            let root = parse(&format_as_xml(&results, "x<y & \"z\"", 64, indent));
+            assert_eq!(root.name, "conversion");
+            assert_eq!(root.attributes, attrs(&[("input", "x<y & \"z\""), ("from_base", "64")]));
+            assert_eq!(root.children.len(), results.len());
+            for (child, (base, value)) in root.children.iter().zip(&results) {
+                assert_eq!(child.name, "result");
+                assert_eq!(child.attributes, attrs(&[("base", &base.to_string()), ("value", value)]));
+                assert!(child.children.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("FF"), "FF");
+        assert_eq!(escape("<a b=\"c\">&'"), "&lt;a b=&quot;c&quot;&gt;&amp;&apos;");
+    }
+}
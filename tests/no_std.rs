@@ -0,0 +1,28 @@
+// Copyright (c) 2019 Zachary Nielsen
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The allocation-free conversions, used from a `no_std` crate.
+
+#![no_std]
+
+use numconverter::{ErrorCode, fixed::{as_string_base_fixed, ArrayString}};
+
+#[test]
+fn test_no_std_round_trip() {
+    let digits: ArrayString<32> = as_string_base_fixed(0xDEAD_BEEF, 16).unwrap();
+    assert_eq!(digits, "DEADBEEF");
+    assert_eq!(u128::from_str_radix(&digits, 16), Ok(0xDEAD_BEEF));
+    let digits: ArrayString<128> = as_string_base_fixed(u128::MAX, 2).unwrap();
+    assert_eq!(u128::from_str_radix(&digits, 2), Ok(u128::MAX));
+}
+
+#[test]
+fn test_no_std_errors() {
+    assert_eq!(as_string_base_fixed::<2>(0xFFF, 16).map(|s| s.len()), Err(ErrorCode::OverflowErr));
+    assert_eq!(as_string_base_fixed::<2>(1, 40).map(|s| s.len()), Err(ErrorCode::TargetBaseErr));
+}